@@ -1,18 +1,19 @@
+use crate::matrix::Matrix;
 use std::f64;
 
-pub fn one_hot_accuracy(targets: &[std::vec::Vec<f64>], outputs: &[std::vec::Vec<f64>]) -> f64 {
+pub fn one_hot_accuracy(targets: &Matrix, outputs: &Matrix) -> f64 {
     targets
         .iter()
-        .zip(outputs)
+        .zip(outputs.iter())
         .filter(|(t, o)| argmax(t) == argmax(&o))
         .count() as f64
         / targets.len() as f64
 }
 
-pub fn rounded_accuracy(targets: &[std::vec::Vec<f64>], outputs: &[std::vec::Vec<f64>]) -> f64 {
+pub fn rounded_accuracy(targets: &Matrix, outputs: &Matrix) -> f64 {
     targets
         .iter()
-        .zip(outputs)
+        .zip(outputs.iter())
         .map(|(t, o)| {
             t.iter()
                 .zip(o.iter())
@@ -24,10 +25,10 @@ pub fn rounded_accuracy(targets: &[std::vec::Vec<f64>], outputs: &[std::vec::Vec
         / targets.len() as f64
 }
 
-pub fn binary_accuracy(targets: &[std::vec::Vec<f64>], outputs: &[std::vec::Vec<f64>]) -> f64 {
+pub fn binary_accuracy(targets: &Matrix, outputs: &Matrix) -> f64 {
     targets
         .iter()
-        .zip(outputs)
+        .zip(outputs.iter())
         .map(|(t, o)| {
             t.iter()
                 .zip(o.iter())
@@ -39,10 +40,192 @@ pub fn binary_accuracy(targets: &[std::vec::Vec<f64>], outputs: &[std::vec::Vec<
         / targets.len() as f64
 }
 
-pub fn argmax(vec: &Vec<f64>) -> usize {
+pub fn argmax(vec: &[f64]) -> usize {
     vec.iter()
         .enumerate()
         .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
         .map(|(index, _)| index)
         .unwrap()
 }
+
+/// Target and predicted class of each sample with one-hot outputs, the largest output, or of
+/// each output with binary outputs, zero if negative and one if positive
+pub fn classes(targets: &Matrix, outputs: &Matrix, one_hot: bool) -> Vec<(usize, usize)> {
+    if one_hot {
+        targets
+            .iter()
+            .zip(outputs.iter())
+            .map(|(t, o)| (argmax(t), argmax(o)))
+            .collect()
+    } else {
+        targets
+            .iter()
+            .zip(outputs.iter())
+            .flat_map(|(t, o)| t.iter().zip(o.iter()))
+            .map(|(t, o)| ((*t > 0.0) as usize, (*o > 0.0) as usize))
+            .collect()
+    }
+}
+
+/// Number of samples of each target class, by row, predicted as each class, by column
+pub fn confusion_matrix(targets: &Matrix, outputs: &Matrix, one_hot: bool) -> Vec<Vec<usize>> {
+    let count = if one_hot { targets.columns() } else { 2 };
+    let mut matrix = vec![vec![0; count]; count];
+    for (target, prediction) in classes(targets, outputs, one_hot) {
+        matrix[target][prediction] += 1;
+    }
+    matrix
+}
+
+/// Mean recall of the classes present in the targets
+pub fn balanced_accuracy(targets: &Matrix, outputs: &Matrix, one_hot: bool) -> f64 {
+    let matrix = confusion_matrix(targets, outputs, one_hot);
+    let recalls = matrix
+        .iter()
+        .enumerate()
+        .map(|(class, row)| (row[class], row.iter().sum::<usize>()))
+        .filter(|(_, total)| *total > 0)
+        .map(|(correct, total)| correct as f64 / total as f64)
+        .collect::<Vec<f64>>();
+    recalls.iter().sum::<f64>() / recalls.len() as f64
+}
+
+/// Mean F1 score of the classes present in the targets or predictions
+pub fn macro_f1(targets: &Matrix, outputs: &Matrix, one_hot: bool) -> f64 {
+    let matrix = confusion_matrix(targets, outputs, one_hot);
+    let scores = (0..matrix.len())
+        .map(|class| {
+            let actual = matrix[class].iter().sum::<usize>();
+            let predicted = matrix.iter().map(|row| row[class]).sum::<usize>();
+            (matrix[class][class], actual + predicted)
+        })
+        .filter(|(_, total)| *total > 0)
+        .map(|(correct, total)| 2.0 * correct as f64 / total as f64)
+        .collect::<Vec<f64>>();
+    scores.iter().sum::<f64>() / scores.len() as f64
+}
+
+/// Area under the ROC curve of the outputs as scores. One-hot outputs give the mean of each
+/// class against the rest, binary outputs pool all outputs. Without both positive and negative
+/// targets the area is 0.5.
+pub fn auc(targets: &Matrix, outputs: &Matrix, one_hot: bool) -> f64 {
+    if one_hot {
+        let count = targets.columns();
+        let areas = (0..count)
+            .filter_map(|class| {
+                let scored = targets
+                    .iter()
+                    .zip(outputs.iter())
+                    .map(|(t, o)| (o[class], argmax(t) == class))
+                    .collect::<Vec<_>>();
+                ranked_auc(scored)
+            })
+            .collect::<Vec<f64>>();
+        if areas.is_empty() {
+            0.5
+        } else {
+            areas.iter().sum::<f64>() / areas.len() as f64
+        }
+    } else {
+        ranked_auc(
+            targets
+                .iter()
+                .zip(outputs.iter())
+                .flat_map(|(t, o)| t.iter().zip(o.iter()))
+                .map(|(t, o)| (*o, *t > 0.0))
+                .collect(),
+        )
+        .unwrap_or(0.5)
+    }
+}
+
+/// Probability that a random positive scores above a random negative, from the rank sum of the
+/// positives with ties given their mean rank
+fn ranked_auc(mut scored: Vec<(f64, bool)>) -> Option<f64> {
+    let positives = scored.iter().filter(|(_, positive)| *positive).count();
+    let negatives = scored.len() - positives;
+    if positives == 0 || negatives == 0 {
+        return None;
+    }
+
+    scored.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut rank_sum = 0.0;
+    let mut start = 0;
+    while start < scored.len() {
+        let end = start
+            + scored[start..]
+                .iter()
+                .take_while(|(score, _)| *score == scored[start].0)
+                .count()
+                .max(1);
+        let rank = (start + end + 1) as f64 / 2.0;
+        let tied = scored[start..end].iter().filter(|(_, p)| *p).count();
+        rank_sum += rank * tied as f64;
+        start = end;
+    }
+
+    let positives = positives as f64;
+    Some((rank_sum - positives * (positives + 1.0) / 2.0) / (positives * negatives as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one_hot() -> (Matrix, Matrix) {
+        let targets = [0, 0, 0, 0, 1, 2]
+            .iter()
+            .map(|c| (0..3).map(|i| if i == *c { 1.0 } else { 0.0 }).collect())
+            .collect::<Vec<_>>();
+        let outputs = vec![
+            vec![0.8, 0.1, 0.1],
+            vec![0.6, 0.3, 0.1],
+            vec![0.7, 0.1, 0.2],
+            vec![0.2, 0.7, 0.1],
+            vec![0.3, 0.6, 0.1],
+            vec![0.5, 0.1, 0.4],
+        ];
+        (Matrix::from(targets), Matrix::from(outputs))
+    }
+
+    #[test]
+    fn test_class_metrics() {
+        let (targets, outputs) = one_hot();
+        assert_eq!(
+            confusion_matrix(&targets, &outputs, true),
+            vec![vec![3, 1, 0], vec![0, 1, 0], vec![1, 0, 0]]
+        );
+        assert_eq!(one_hot_accuracy(&targets, &outputs), 4.0 / 6.0);
+        assert_eq!(
+            balanced_accuracy(&targets, &outputs, true),
+            (0.75 + 1.0 + 0.0) / 3.0
+        );
+        assert_eq!(
+            macro_f1(&targets, &outputs, true),
+            (6.0 / 8.0 + 2.0 / 3.0 + 0.0) / 3.0
+        );
+
+        // Two binary outputs pooled
+        let targets = Matrix::from(vec![vec![1.0, -1.0], vec![-1.0, -1.0]]);
+        let outputs = Matrix::from(vec![vec![0.5, 0.2], vec![-0.5, -0.1]]);
+        assert_eq!(
+            confusion_matrix(&targets, &outputs, false),
+            vec![vec![2, 1], vec![0, 1]]
+        );
+        assert!((balanced_accuracy(&targets, &outputs, false) - 5.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_auc() {
+        let column = |values: &[f64]| Matrix::from_values(values.to_vec(), 1);
+        let targets = column(&[1.0, 1.0, -1.0, -1.0]);
+        assert_eq!(auc(&targets, &column(&[0.9, 0.4, 0.5, 0.1]), false), 0.75);
+        assert_eq!(auc(&targets, &column(&[0.5, 0.5, 0.5, 0.1]), false), 0.75);
+        assert_eq!(auc(&column(&[1.0, 1.0]), &column(&[0.1, 0.2]), false), 0.5);
+
+        // Only class 2 is ranked above all others
+        let (targets, outputs) = one_hot();
+        let expected = (0.75 + 0.8 + 1.0) / 3.0;
+        assert!((auc(&targets, &outputs, true) - expected).abs() < 1e-12);
+    }
+}
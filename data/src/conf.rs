@@ -1,3 +1,5 @@
+use crate::format::{Format, TargetType};
+use crate::metric::Metric;
 use bson;
 use envconfig::Envconfig;
 use lazy_static::lazy_static;
@@ -5,9 +7,49 @@ use serde::Serialize;
 
 #[derive(Envconfig, Serialize)]
 pub struct DatasetConfig {
+    /// Dataset file, or a synthetic task like "gen:retina" (see generate::generate)
     #[envconfig(from = "DATASET", default = "datasets/generated/iris")]
     pub dataset: String,
 
+    /// Auto, Native, CSV, LIBSVM, ARFF or IDX, auto detects by file extension
+    #[envconfig(from = "DATASET_FORMAT", default = "auto")]
+    pub dataset_format: Format,
+
+    /// Name or index of the CSV or ARFF target column, the last column if empty
+    #[envconfig(from = "TARGET_COLUMN", default = "")]
+    pub target_column: String,
+
+    /// Auto, Categorical or Numeric, categorical targets are one-hot encoded
+    #[envconfig(from = "TARGET_TYPE", default = "auto")]
+    pub target_type: TargetType,
+
+    #[envconfig(from = "CSV_HEADER", default = "true")]
+    pub csv_header: bool,
+
+    /// IDX labels of the IDX images in DATASET, by default the images path with "images"
+    /// replaced by "labels" and "idx3" by "idx1"
+    #[envconfig(from = "IDX_LABELS", default = "")]
+    pub idx_labels: String,
+
+    /// Pixels removed from each side of images before resizing
+    #[envconfig(from = "IMAGE_CROP", default = "0")]
+    #[serde(with = "bson::compat::u2f")]
+    pub image_crop: u64,
+
+    /// Resolution images are resized to, zero to keep the cropped size
+    #[envconfig(from = "IMAGE_WIDTH", default = "0")]
+    #[serde(with = "bson::compat::u2f")]
+    pub image_width: u64,
+
+    #[envconfig(from = "IMAGE_HEIGHT", default = "0")]
+    #[serde(with = "bson::compat::u2f")]
+    pub image_height: u64,
+
+    /// Samples kept after shuffling, zero to keep all
+    #[envconfig(from = "MAX_SAMPLES", default = "0")]
+    #[serde(with = "bson::compat::u2f")]
+    pub max_samples: u64,
+
     #[envconfig(from = "SEED", default = "0")]
     #[serde(with = "bson::compat::u2f")]
     pub seed: u64,
@@ -18,8 +60,35 @@ pub struct DatasetConfig {
     #[envconfig(from = "TEST_FRACTION", default = "0.0")]
     pub test_fraction: f64,
 
+    /// Keep the class proportions of classification data in each split
+    #[envconfig(from = "STRATIFY", default = "false")]
+    pub stratify: bool,
+
+    /// Folds of k-fold cross-validation, replacing TEST_FRACTION with one evolution testing
+    /// on each fold, zero or one to disable
+    #[envconfig(from = "FOLDS", default = "0")]
+    #[serde(with = "bson::compat::u2f")]
+    pub folds: u64,
+
+    /// Comma separated steps fitted to the training inputs: impute, minmax, zscore or pca:<k>
+    #[envconfig(from = "PREPROCESSING", default = "")]
+    pub preprocessing: String,
+
     #[envconfig(from = "ADD_BIAS_INPUT", default = "false")]
     pub add_bias_input: bool,
+
+    /// Metric maximized as fitness (see metric::Metric), auto for crossentropy with one-hot
+    /// classes and otherwise MSE
+    #[envconfig(from = "FITNESS", default = "auto")]
+    pub fitness: Metric,
+
+    /// Comma separated metrics reported for the validation and test splits, auto for those of
+    /// the task. Measured in every evaluation, so none by default.
+    #[envconfig(from = "METRICS", default = "")]
+    pub metrics: String,
+
+    #[envconfig(from = "HUBER_DELTA", default = "1.0")]
+    pub huber_delta: f64,
 }
 
 impl Default for DatasetConfig {
@@ -1,8 +1,11 @@
 use crate::conf::DATA;
+use crate::format::Samples;
+use crate::idx::ImageShape;
+use crate::matrix::Matrix;
+use crate::preprocess::{parse_steps, Pipeline, Step};
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Error},
+    io::{Error, ErrorKind},
     path::Path,
 };
 
@@ -11,13 +14,18 @@ pub struct Dataset {
     pub dimensions: Dimensions,
     pub is_classification: bool,
     pub one_hot_output: bool,
+    /// Shape of the inputs, before any bias input, if they form an image
+    pub image: Option<ImageShape>,
+    /// Transformation of the raw inputs, fitted to the training inputs
+    pub preprocessing: Pipeline,
 
-    pub training_inputs: Vec<Vec<f64>>,
-    pub training_targets: Vec<Vec<f64>>,
-    pub validation_inputs: Vec<Vec<f64>>,
-    pub validation_targets: Vec<Vec<f64>>,
-    pub test_inputs: Vec<Vec<f64>>,
-    pub test_targets: Vec<Vec<f64>>,
+    /// Inputs and targets of each split, one sample per row
+    pub training_inputs: Matrix,
+    pub training_targets: Matrix,
+    pub validation_inputs: Matrix,
+    pub validation_targets: Matrix,
+    pub test_inputs: Matrix,
+    pub test_targets: Matrix,
 
     pub total_count: usize,
     pub training_count: usize,
@@ -31,99 +39,120 @@ pub struct Dimensions {
     pub outputs: u64,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Split {
+    Training,
+    Validation,
+    Test,
+}
+
 impl Dataset {
-    pub fn load() -> Dataset {
-        Self::load_specific(&DATA.dataset)
-            .ok()
-            .expect("unable to load dataset")
+    /// Load the configured dataset, with the given fold as test split when FOLDS is above one
+    pub fn load(fold: u64) -> Dataset {
+        Self::load_specific(&DATA.dataset, fold)
+            .unwrap_or_else(|error| panic!("unable to load dataset: {}", error))
     }
 
-    pub fn load_specific<P: AsRef<Path>>(path: P) -> Result<Dataset, Error> {
-        let file = File::open(path)?;
-        let mut buffered = BufReader::new(file);
-
-        let mut inputs: Vec<Vec<f64>> = Vec::new();
-        let mut targets: Vec<Vec<f64>> = Vec::new();
-
-        let mut read_state: bool = false;
-
-        let mut line: String = String::new();
-        buffered.read_line(&mut line)?;
-        line.retain(|c| !c.is_whitespace());
-        let is_classification = line == "true";
-
-        line.clear();
-        buffered.read_line(&mut line)?;
-        line.retain(|c| !c.is_whitespace());
-        let one_hot_encoded = line == "true";
-
-        buffered.read_line(&mut line)?;
-
-        for line in buffered.lines() {
-            let mut line: String = line?;
-            line.retain(|c| !c.is_whitespace());
-
-            if line.len() == 0 {
-                if !read_state {
-                    read_state = true;
-                    continue;
-                } else {
-                    break;
-                }
-            }
-
-            let mut line: Vec<f64> = line
-                .split(|c| c == ',')
-                .map(|val| val.parse().unwrap())
-                .collect();
-
-            if !read_state {
-                if DATA.add_bias_input {
-                    line.push(1.0);
-                }
-                inputs.push(line);
-            } else {
-                targets.push(line);
-            }
-        }
-
-        assert_ne!(inputs.len(), 0);
-        assert_eq!(inputs.len(), targets.len());
+    /// Load a dataset in the configured format, or the format given by its extension
+    pub fn load_specific<P: AsRef<Path>>(path: P, fold: u64) -> Result<Dataset, Error> {
+        let Samples {
+            mut inputs,
+            mut targets,
+            is_classification,
+            one_hot_output,
+            image,
+        } = Samples::read(path.as_ref(), &DATA)?;
 
         inputs.shuffle(&mut StdRng::seed_from_u64(DATA.seed));
         targets.shuffle(&mut StdRng::seed_from_u64(DATA.seed));
+        if DATA.max_samples > 0 {
+            inputs.truncate(DATA.max_samples as usize);
+            targets.truncate(DATA.max_samples as usize);
+        }
 
         let total_count = inputs.len();
+        let folds = DATA.folds as usize;
+        let fold = fold as usize;
+        if folds > 1 && (folds > total_count || fold >= folds) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("fold {} of {} with {} samples", fold, folds, total_count),
+            ));
+        }
+        let test_count = if folds > 1 {
+            0
+        } else {
+            (total_count as f64 * DATA.test_fraction).round() as usize
+        };
         let validation_count = (total_count as f64 * DATA.validation_fraction).round() as usize;
-        let test_count = (total_count as f64 * DATA.test_fraction).round() as usize;
-        let training_count = total_count - validation_count - test_count;
+        let classes = if is_classification && DATA.stratify {
+            Some(
+                targets
+                    .iter()
+                    .map(|target| class(target, one_hot_output))
+                    .collect::<Vec<usize>>(),
+            )
+        } else {
+            None
+        };
+        let splits = assign_splits(
+            total_count,
+            classes.as_deref(),
+            validation_count.min(total_count),
+            test_count.min(total_count),
+            folds,
+            fold,
+        );
 
-        let i = training_count + validation_count;
-        let test_inputs = inputs[i..].iter().cloned().collect::<Vec<_>>();
-        let test_targets = targets[i..].iter().cloned().collect::<Vec<_>>();
-        inputs.truncate(i);
-        targets.truncate(i);
+        let mut training = (Vec::new(), Vec::new());
+        let mut validation = (Vec::new(), Vec::new());
+        let mut test = (Vec::new(), Vec::new());
+        for ((input, target), split) in inputs.into_iter().zip(targets).zip(splits) {
+            let (inputs, targets) = match split {
+                Split::Training => &mut training,
+                Split::Validation => &mut validation,
+                Split::Test => &mut test,
+            };
+            inputs.push(input);
+            targets.push(target);
+        }
+        let (mut inputs, targets) = training;
+        let (mut validation_inputs, validation_targets) = validation;
+        let (mut test_inputs, test_targets) = test;
+        let training_count = inputs.len();
+        let validation_count = validation_inputs.len();
+        let test_count = test_inputs.len();
 
-        let i = training_count;
-        let validation_inputs = inputs[i..].iter().cloned().collect::<Vec<_>>();
-        let validation_targets = targets[i..].iter().cloned().collect::<Vec<_>>();
-        inputs.truncate(i);
-        targets.truncate(i);
+        // Preprocessing is fitted to the training inputs only
+        let steps = parse_steps(&DATA.preprocessing)?;
+        let preprocessing = Pipeline::fit(&steps, &inputs, DATA.add_bias_input);
+        for inputs in [&mut inputs, &mut validation_inputs, &mut test_inputs].iter_mut() {
+            preprocessing.apply_all(inputs);
+            if inputs.iter().flatten().any(|x| x.is_nan()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "missing input values, add the impute preprocessing step",
+                ));
+            }
+        }
+        let image = image.filter(|_| !steps.iter().any(|step| matches!(step, Step::Pca(_))));
 
         Ok(Dataset {
             dimensions: Dimensions {
                 inputs: inputs[0].len() as u64,
                 outputs: targets[0].len() as u64,
             },
-            is_classification: is_classification,
-            one_hot_output: one_hot_encoded,
+            is_classification,
+            one_hot_output,
+            image,
+            preprocessing,
 
-            training_inputs: inputs,
-            training_targets: targets,
-            validation_inputs,
-            validation_targets,
-            test_inputs,
-            test_targets,
+            training_inputs: Matrix::from(inputs),
+            training_targets: Matrix::from(targets),
+            validation_inputs: Matrix::from(validation_inputs),
+            validation_targets: Matrix::from(validation_targets),
+            test_inputs: Matrix::from(test_inputs),
+            test_targets: Matrix::from(test_targets),
 
             total_count,
             training_count,
@@ -132,3 +161,149 @@ impl Dataset {
         })
     }
 }
+
+/// Class of a classification target, the position of the largest one-hot output, or the sign
+/// of each binary output
+fn class(target: &[f64], one_hot: bool) -> usize {
+    if one_hot {
+        target
+            .iter()
+            .enumerate()
+            .fold((0, f64::NEG_INFINITY), |best, (i, t)| {
+                if *t > best.1 {
+                    (i, *t)
+                } else {
+                    best
+                }
+            })
+            .0
+    } else {
+        target
+            .iter()
+            .fold(0, |class, t| class << 1 | (*t > 0.0) as usize)
+    }
+}
+
+/// Whether the r-th of count items is selected when spreading the selected number of items
+/// evenly over them
+fn spread(r: usize, selected: usize, count: usize) -> bool {
+    (r + 1) * selected / count > r * selected / count
+}
+
+/// Split of each of count samples. The test split is the given fold of all samples when there
+/// are more than one, otherwise the last samples, and the validation split the last samples
+/// before it. With the class of each sample, every split instead takes samples spread evenly
+/// over each class, keeping the class proportions of the whole dataset.
+fn assign_splits(
+    count: usize,
+    classes: Option<&[usize]>,
+    validation_count: usize,
+    test_count: usize,
+    folds: usize,
+    fold: usize,
+) -> Vec<Split> {
+    let mut order = (0..count).collect::<Vec<usize>>();
+    if let Some(classes) = classes {
+        order.sort_by_key(|i| classes[*i]);
+    }
+    let stratified = classes.is_some();
+
+    let mut splits = vec![Split::Training; count];
+    for (r, i) in order.iter().enumerate() {
+        let test = match (folds > 1, stratified) {
+            (true, true) => r % folds == fold,
+            (true, false) => r * folds / count == fold,
+            (false, true) => spread(r, test_count, count),
+            (false, false) => r + test_count >= count,
+        };
+        if test {
+            splits[*i] = Split::Test;
+        }
+    }
+
+    let remaining = order
+        .into_iter()
+        .filter(|i| splits[*i] != Split::Test)
+        .collect::<Vec<usize>>();
+    let validation_count = validation_count.min(remaining.len());
+    for (r, i) in remaining.iter().enumerate() {
+        let validation = if stratified {
+            spread(r, validation_count, remaining.len())
+        } else {
+            r + validation_count >= remaining.len()
+        };
+        if validation {
+            splits[*i] = Split::Validation;
+        }
+    }
+    splits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(splits: &[Split], classes: &[usize], class: usize) -> (usize, usize, usize) {
+        let count = |split| {
+            splits
+                .iter()
+                .zip(classes.iter())
+                .filter(|(s, c)| **s == split && **c == class)
+                .count()
+        };
+        (
+            count(Split::Training),
+            count(Split::Validation),
+            count(Split::Test),
+        )
+    }
+
+    #[test]
+    fn test_class() {
+        assert_eq!(class(&[0.0, 0.0, 1.0], true), 2);
+        assert_eq!(class(&[0.2, 0.7, 0.1], true), 1);
+        assert_eq!(class(&[1.0, -1.0], false), 2);
+        assert_eq!(class(&[-1.0, 1.0], false), 1);
+    }
+
+    #[test]
+    fn test_contiguous_splits() {
+        use Split::*;
+        assert_eq!(
+            assign_splits(6, None, 2, 1, 0, 0),
+            vec![Training, Training, Training, Validation, Validation, Test]
+        );
+        assert_eq!(
+            assign_splits(6, None, 1, 0, 3, 1),
+            vec![Training, Training, Test, Test, Training, Validation]
+        );
+    }
+
+    #[test]
+    fn test_stratified_splits() {
+        // A large and a small class, interleaved
+        let classes = (0..40)
+            .map(|i| if i % 8 == 0 { 1 } else { 0 })
+            .collect::<Vec<usize>>();
+        let splits = assign_splits(40, Some(&classes), 8, 10, 0, 0);
+        assert_eq!(counts(&splits, &classes, 0), (20, 7, 8));
+        assert_eq!(counts(&splits, &classes, 1), (2, 1, 2));
+
+        // Every sample is tested in exactly one fold, and each fold has both classes
+        let mut tested = vec![0; 40];
+        for fold in 0..5 {
+            let splits = assign_splits(40, Some(&classes), 4, 0, 5, fold);
+            assert_eq!(counts(&splits, &classes, 1).2, 1);
+            assert_eq!(
+                splits.iter().filter(|s| **s == Split::Validation).count(),
+                4
+            );
+            for (i, split) in splits.iter().enumerate() {
+                if *split == Split::Test {
+                    tested[i] += 1;
+                }
+            }
+        }
+        assert_eq!(tested, vec![1; 40]);
+    }
+}
@@ -1,27 +1,29 @@
-pub fn normalize(list: &Vec<f64>) -> Vec<f64> {
+use crate::matrix::Matrix;
+
+pub fn normalize(list: &[f64]) -> Vec<f64> {
     let sum = list.iter().sum::<f64>();
 
     if sum != 0.0 {
         list.iter().map(|x| x / sum).collect::<Vec<_>>()
     } else {
-        list.clone()
+        list.to_vec()
     }
 }
 
-pub fn mse(targets: &[std::vec::Vec<f64>], predictions: &[std::vec::Vec<f64>], norm: bool) -> f64 {
+pub fn mse(targets: &Matrix, predictions: &Matrix, norm: bool) -> f64 {
     targets
         .iter()
-        .zip(predictions)
+        .zip(predictions.iter())
         .map(|(t, p)| mse_single(t, &p, norm))
         .sum::<f64>()
         / targets.len() as f64
 }
 
-pub fn mse_single(target: &Vec<f64>, prediction: &Vec<f64>, norm: bool) -> f64 {
+pub fn mse_single(target: &[f64], prediction: &[f64], norm: bool) -> f64 {
     let prediction = if norm {
         normalize(prediction)
     } else {
-        prediction.clone()
+        prediction.to_vec()
     };
 
     target
@@ -32,24 +34,20 @@ pub fn mse_single(target: &Vec<f64>, prediction: &Vec<f64>, norm: bool) -> f64 {
         / target.len() as f64
 }
 
-pub fn crossentropy(
-    targets: &[std::vec::Vec<f64>],
-    predictions: &[std::vec::Vec<f64>],
-    norm: bool,
-) -> f64 {
+pub fn crossentropy(targets: &Matrix, predictions: &Matrix, norm: bool) -> f64 {
     targets
         .iter()
-        .zip(predictions)
+        .zip(predictions.iter())
         .map(|(t, p)| crossentropy_single(t, &p, norm))
         .sum::<f64>()
         / targets.len() as f64
 }
 
-pub fn crossentropy_single(target: &Vec<f64>, prediction: &Vec<f64>, norm: bool) -> f64 {
+pub fn crossentropy_single(target: &[f64], prediction: &[f64], norm: bool) -> f64 {
     let prediction = if norm {
         normalize(prediction)
     } else {
-        prediction.clone()
+        prediction.to_vec()
     };
 
     let e = 0.0000001;
@@ -67,3 +65,92 @@ pub fn crossentropy_single(target: &Vec<f64>, prediction: &Vec<f64>, norm: bool)
         .map(|(t, p)| t * p.ln())
         .sum::<f64>()
 }
+
+/// Mean over samples of the mean absolute error of each output
+pub fn mae(targets: &Matrix, predictions: &Matrix) -> f64 {
+    mean_over_outputs(targets, predictions, |t, p| (t - p).abs())
+}
+
+pub fn rmse(targets: &Matrix, predictions: &Matrix) -> f64 {
+    mse(targets, predictions, false).sqrt()
+}
+
+/// Squared error for errors up to delta, otherwise linear
+pub fn huber(targets: &Matrix, predictions: &Matrix, delta: f64) -> f64 {
+    mean_over_outputs(targets, predictions, |t, p| {
+        let e = (t - p).abs();
+        if e <= delta {
+            0.5 * e * e
+        } else {
+            delta * (e - 0.5 * delta)
+        }
+    })
+}
+
+/// Coefficient of determination over all outputs, with the total variance of each output
+/// around its mean. Constant targets give one if predicted exactly, otherwise zero.
+pub fn r_squared(targets: &Matrix, predictions: &Matrix) -> f64 {
+    let count = targets.columns();
+    let means = (0..count)
+        .map(|i| targets.iter().map(|t| t[i]).sum::<f64>() / targets.len() as f64)
+        .collect::<Vec<f64>>();
+
+    let mut residual = 0.0;
+    let mut total = 0.0;
+    for (t, p) in targets.iter().zip(predictions.iter()) {
+        for ((t, p), mean) in t.iter().zip(p.iter()).zip(means.iter()) {
+            residual += (t - p).powi(2);
+            total += (t - mean).powi(2);
+        }
+    }
+
+    if total > 0.0 {
+        1.0 - residual / total
+    } else if residual == 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn mean_over_outputs(
+    targets: &Matrix,
+    predictions: &Matrix,
+    error: impl Fn(f64, f64) -> f64,
+) -> f64 {
+    targets
+        .iter()
+        .zip(predictions.iter())
+        .map(|(t, p)| {
+            t.iter()
+                .zip(p.iter())
+                .map(|(t, p)| error(*t, *p))
+                .sum::<f64>()
+                / t.len() as f64
+        })
+        .sum::<f64>()
+        / targets.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regression_errors() {
+        let targets = Matrix::from(vec![vec![1.0, 0.0], vec![3.0, 2.0]]);
+        let predictions = Matrix::from(vec![vec![2.0, 0.0], vec![3.0, -1.0]]);
+        assert_eq!(mae(&targets, &predictions), 1.0);
+        assert_eq!(rmse(&targets, &predictions), 2.5f64.sqrt());
+        assert_eq!(huber(&targets, &predictions, 1.0), (0.5 + 2.5) / 4.0);
+        assert_eq!(r_squared(&targets, &predictions), 1.0 - 10.0 / 4.0);
+        assert_eq!(r_squared(&targets, &targets), 1.0);
+        assert_eq!(
+            r_squared(
+                &Matrix::from_values(vec![1.0, 1.0], 1),
+                &Matrix::from_values(vec![1.0, 2.0], 1)
+            ),
+            0.0
+        );
+    }
+}
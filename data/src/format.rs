@@ -0,0 +1,769 @@
+use crate::conf::DatasetConfig;
+use crate::generate;
+use crate::idx::{self, ImageShape};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    io::{Error, ErrorKind},
+    path::Path,
+    str,
+};
+
+/// File format of a dataset
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum Format {
+    /// Given by the file extension
+    Auto,
+    /// Two boolean header lines, input rows, a blank line and target rows
+    Native,
+    /// Comma separated values, one sample per row
+    Csv,
+    /// Sparse rows of a label followed by index:value pairs, indices starting at one
+    Libsvm,
+    /// Attribute-Relation File Format, with numeric and nominal attributes
+    Arff,
+    /// Binary arrays of images and labels, like MNIST
+    Idx,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseFormatError;
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot parse dataset format")
+    }
+}
+
+impl str::FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Format::Auto),
+            "native" => Ok(Format::Native),
+            "csv" => Ok(Format::Csv),
+            "libsvm" | "svm" => Ok(Format::Libsvm),
+            "arff" => Ok(Format::Arff),
+            "idx" => Ok(Format::Idx),
+            _ => Err(ParseFormatError {}),
+        }
+    }
+}
+
+impl Format {
+    /// Format given by the extension of a file, the native format if it is not recognized
+    pub fn detect(path: &Path) -> Format {
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .as_deref()
+        {
+            Some("csv") => Format::Csv,
+            Some("libsvm") | Some("svm") => Format::Libsvm,
+            Some("arff") => Format::Arff,
+            Some(extension) if extension.starts_with("idx") => Format::Idx,
+            _ => Format::Native,
+        }
+    }
+}
+
+/// Encoding of the target column in formats without one
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum TargetType {
+    /// Categorical if any value is not a number, or for LIBSVM and IDX if all labels are integers
+    Auto,
+    /// One-hot encoded classes
+    Categorical,
+    /// Regression target
+    Numeric,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseTargetTypeError;
+
+impl fmt::Display for ParseTargetTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot parse target type")
+    }
+}
+
+impl str::FromStr for TargetType {
+    type Err = ParseTargetTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(TargetType::Auto),
+            "categorical" => Ok(TargetType::Categorical),
+            "numeric" => Ok(TargetType::Numeric),
+            _ => Err(ParseTargetTypeError {}),
+        }
+    }
+}
+
+/// All samples of a dataset, before it is split
+#[derive(Debug)]
+pub struct Samples {
+    pub inputs: Vec<Vec<f64>>,
+    pub targets: Vec<Vec<f64>>,
+    pub is_classification: bool,
+    pub one_hot_output: bool,
+    /// Shape of inputs that form an image
+    pub image: Option<ImageShape>,
+}
+
+impl Samples {
+    /// Read a dataset file, or generate a synthetic task given by a path like "gen:retina"
+    pub fn read(path: &Path, config: &DatasetConfig) -> Result<Samples, Error> {
+        if let Some(task) = path.to_str().and_then(|path| path.strip_prefix("gen:")) {
+            return generate::generate(task, config.seed);
+        }
+
+        let format = match config.dataset_format {
+            Format::Auto => Format::detect(path),
+            format => format,
+        };
+        let text = || fs::read_to_string(path);
+
+        let samples = match format {
+            Format::Auto | Format::Native => parse_native(&text()?),
+            Format::Csv => parse_csv(
+                &text()?,
+                config.csv_header,
+                &config.target_column,
+                config.target_type,
+            ),
+            Format::Libsvm => parse_libsvm(&text()?, config.target_type),
+            Format::Arff => parse_arff(&text()?, &config.target_column, config.target_type),
+            Format::Idx => idx::read_samples(path, config),
+        }?;
+
+        if samples.inputs.is_empty() {
+            return Err(invalid("dataset has no samples".to_string()));
+        }
+        if samples.inputs.len() != samples.targets.len() {
+            return Err(invalid(format!(
+                "{} input rows but {} target rows",
+                samples.inputs.len(),
+                samples.targets.len()
+            )));
+        }
+        Ok(samples)
+    }
+}
+
+pub(crate) fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+fn parse_row(line: &str, number: usize) -> Result<Vec<f64>, Error> {
+    line.split(',')
+        .map(|value| {
+            parse_number(value)
+                .ok_or_else(|| invalid(format!("line {}: invalid number '{}'", number, value)))
+        })
+        .collect()
+}
+
+pub fn parse_native(text: &str) -> Result<Samples, Error> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split_whitespace().collect::<String>()));
+
+    let mut header = || {
+        lines
+            .next()
+            .map(|(_, line)| line == "true")
+            .unwrap_or(false)
+    };
+    let is_classification = header();
+    let one_hot_output = header();
+    header();
+
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    let mut read_targets = false;
+    for (number, line) in lines {
+        if line.is_empty() {
+            if !read_targets {
+                read_targets = true;
+                continue;
+            } else {
+                break;
+            }
+        }
+
+        let row = parse_row(&line, number)?;
+        if !read_targets {
+            inputs.push(row);
+        } else {
+            targets.push(row);
+        }
+    }
+
+    Ok(Samples {
+        inputs,
+        targets,
+        is_classification,
+        one_hot_output,
+        image: None,
+    })
+}
+
+fn is_missing(value: &str) -> bool {
+    value.is_empty() || value == "?"
+}
+
+/// Column of a table, with the declared categories of nominal columns
+struct Column {
+    name: String,
+    categories: Option<Vec<String>>,
+    values: Vec<String>,
+}
+
+impl Column {
+    fn new(name: String, categories: Option<Vec<String>>) -> Self {
+        Self {
+            name,
+            categories,
+            values: Vec::new(),
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        self.categories.is_none()
+            && self
+                .values
+                .iter()
+                .all(|v| is_missing(v) || parse_number(v).is_some())
+    }
+
+    /// Declared categories, or the distinct values in numeric or else lexical order
+    fn categories(&self) -> Vec<String> {
+        if let Some(categories) = &self.categories {
+            return categories.clone();
+        }
+
+        let mut seen = HashSet::new();
+        let mut categories = self
+            .values
+            .iter()
+            .filter(|value| !is_missing(value) && seen.insert(value.as_str()))
+            .cloned()
+            .collect::<Vec<String>>();
+        if categories.iter().all(|c| parse_number(c).is_some()) {
+            categories.sort_by(|a, b| {
+                parse_number(a)
+                    .unwrap()
+                    .partial_cmp(&parse_number(b).unwrap())
+                    .unwrap()
+            });
+        } else {
+            categories.sort();
+        }
+        categories
+    }
+
+    /// Encoded value of each row, a number or a one-hot encoded category. Missing values, if
+    /// allowed, are NaN numbers or categories of all zeros.
+    fn encode(&self, categorical: bool, allow_missing: bool) -> Result<Vec<Vec<f64>>, Error> {
+        if let Some(row) = self.values.iter().position(|v| is_missing(v)) {
+            if !allow_missing {
+                return Err(invalid(format!(
+                    "row {}: missing value in column '{}'",
+                    row + 1,
+                    self.name
+                )));
+            }
+        }
+
+        if !categorical {
+            return self
+                .values
+                .iter()
+                .map(|value| {
+                    if is_missing(value) {
+                        return Ok(vec![f64::NAN]);
+                    }
+                    parse_number(value).map(|x| vec![x]).ok_or_else(|| {
+                        invalid(format!(
+                            "column '{}' is not numeric, value '{}'",
+                            self.name, value
+                        ))
+                    })
+                })
+                .collect();
+        }
+
+        let categories = self.categories();
+        self.values
+            .iter()
+            .map(|value| {
+                let mut encoded = vec![0.0; categories.len()];
+                if is_missing(value) {
+                    return Ok(encoded);
+                }
+                let class = categories.iter().position(|c| c == value).ok_or_else(|| {
+                    invalid(format!(
+                        "undeclared category '{}' in column '{}'",
+                        value, self.name
+                    ))
+                })?;
+                encoded[class] = 1.0;
+                Ok(encoded)
+            })
+            .collect()
+    }
+}
+
+/// Samples of a table, with nominal and non-numeric input columns one-hot encoded
+fn encode(
+    mut columns: Vec<Column>,
+    target_column: &str,
+    target_type: TargetType,
+) -> Result<Samples, Error> {
+    let target = if target_column.is_empty() {
+        columns.len().checked_sub(1)
+    } else {
+        columns
+            .iter()
+            .position(|column| column.name == target_column)
+            .or_else(|| target_column.parse::<usize>().ok())
+            .filter(|index| *index < columns.len())
+    }
+    .ok_or_else(|| invalid(format!("no target column '{}'", target_column)))?;
+    let target = columns.remove(target);
+
+    let categorical = match target_type {
+        TargetType::Auto => !target.is_numeric(),
+        TargetType::Categorical => true,
+        TargetType::Numeric => false,
+    };
+    let targets = target.encode(categorical, false)?;
+
+    let mut inputs = vec![Vec::new(); targets.len()];
+    for column in columns.iter() {
+        for (input, value) in inputs
+            .iter_mut()
+            .zip(column.encode(!column.is_numeric(), true)?)
+        {
+            input.extend(value);
+        }
+    }
+
+    Ok(Samples {
+        inputs,
+        targets,
+        is_classification: categorical,
+        one_hot_output: categorical,
+        image: None,
+    })
+}
+
+/// Encoded class labels, categorical unless the type is numeric or, when automatic, any
+/// label is not an integer
+pub(crate) fn encode_labels(
+    labels: Vec<String>,
+    target_type: TargetType,
+) -> Result<(Vec<Vec<f64>>, bool), Error> {
+    let categorical = match target_type {
+        TargetType::Auto => labels
+            .iter()
+            .all(|label| parse_number(label).is_some_and(|x| x.fract() == 0.0)),
+        TargetType::Categorical => true,
+        TargetType::Numeric => false,
+    };
+    let mut column = Column::new("label".to_string(), None);
+    column.values = labels;
+    Ok((column.encode(categorical, false)?, categorical))
+}
+
+/// Fields of a delimited line, trimmed and with surrounding quotes removed
+fn split_fields(line: &str, delimiter: char, quotes: &[char]) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quote = None;
+    let mut quoted = false;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => field.push(c),
+            None if quotes.contains(&c) && field.trim().is_empty() => {
+                field.clear();
+                quote = Some(c);
+                quoted = true;
+            }
+            None if c == delimiter => {
+                fields.push(if quoted {
+                    field.clone()
+                } else {
+                    field.trim().to_string()
+                });
+                field.clear();
+                quoted = false;
+            }
+            None if quoted => {}
+            None => field.push(c),
+        }
+    }
+    fields.push(if quoted {
+        field
+    } else {
+        field.trim().to_string()
+    });
+    fields
+}
+
+/// Comma separated values, the target column given by header name or index
+pub fn parse_csv(
+    text: &str,
+    header: bool,
+    target_column: &str,
+    target_type: TargetType,
+) -> Result<Samples, Error> {
+    let mut rows = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, split_fields(line, ',', &['"'])));
+
+    let mut columns = match rows.next() {
+        Some((_, fields)) if header => fields
+            .into_iter()
+            .map(|name| Column::new(name, None))
+            .collect::<Vec<Column>>(),
+        Some((_, fields)) => {
+            let mut columns = (0..fields.len())
+                .map(|i| Column::new(i.to_string(), None))
+                .collect::<Vec<Column>>();
+            for (column, value) in columns.iter_mut().zip(fields) {
+                column.values.push(value);
+            }
+            columns
+        }
+        None => Vec::new(),
+    };
+
+    for (number, fields) in rows {
+        if fields.len() != columns.len() {
+            return Err(invalid(format!(
+                "line {}: {} fields, expected {}",
+                number,
+                fields.len(),
+                columns.len()
+            )));
+        }
+        for (column, value) in columns.iter_mut().zip(fields) {
+            column.values.push(value);
+        }
+    }
+
+    encode(columns, target_column, target_type)
+}
+
+/// Sparse rows of "label index:value ..." with indices starting at one and omitted values zero
+pub fn parse_libsvm(text: &str, target_type: TargetType) -> Result<Samples, Error> {
+    let mut labels = Vec::new();
+    let mut rows = Vec::<Vec<(usize, f64)>>::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        labels.push(fields.next().unwrap().to_string());
+        rows.push(
+            fields
+                .map(|field| {
+                    let mut pair = field.splitn(2, ':');
+                    let index = pair.next().and_then(|index| index.parse::<usize>().ok());
+                    let value = pair.next().and_then(parse_number);
+                    match (index, value) {
+                        (Some(index), Some(value)) if index > 0 => Ok((index - 1, value)),
+                        _ => Err(invalid(format!(
+                            "line {}: invalid feature '{}'",
+                            i + 1,
+                            field
+                        ))),
+                    }
+                })
+                .collect::<Result<_, _>>()?,
+        );
+    }
+
+    let (targets, categorical) = encode_labels(labels, target_type)?;
+
+    let features = rows
+        .iter()
+        .flat_map(|row| row.iter().map(|(index, _)| index + 1))
+        .max()
+        .unwrap_or(0);
+    let inputs = rows
+        .iter()
+        .map(|row| {
+            let mut input = vec![0.0; features];
+            for (index, value) in row.iter() {
+                input[*index] = *value;
+            }
+            input
+        })
+        .collect();
+
+    Ok(Samples {
+        inputs,
+        targets,
+        is_classification: categorical,
+        one_hot_output: categorical,
+        image: None,
+    })
+}
+
+/// Name and remaining text of an attribute declaration, the name possibly quoted
+fn split_name(declaration: &str) -> (String, &str) {
+    let declaration = declaration.trim_start();
+    match declaration.chars().next() {
+        Some(quote) if quote == '\'' || quote == '"' => {
+            let rest = &declaration[1..];
+            match rest.find(quote) {
+                Some(end) => (rest[..end].to_string(), &rest[end + 1..]),
+                None => (rest.to_string(), ""),
+            }
+        }
+        _ => {
+            let end = declaration
+                .find(char::is_whitespace)
+                .unwrap_or(declaration.len());
+            (declaration[..end].to_string(), &declaration[end..])
+        }
+    }
+}
+
+/// Attribute-Relation File Format with numeric and nominal attributes, the target attribute
+/// given by name or index. Nominal attributes are one-hot encoded in their declared order.
+pub fn parse_arff(
+    text: &str,
+    target_column: &str,
+    target_type: TargetType,
+) -> Result<Samples, Error> {
+    let mut columns = Vec::<Column>::new();
+    let mut data = false;
+
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+
+        let lower = line.to_lowercase();
+        if !data {
+            if lower.starts_with("@attribute") {
+                let (name, kind) = split_name(&line["@attribute".len()..]);
+                let kind = kind.trim();
+                let categories = if kind.starts_with('{') && kind.ends_with('}') {
+                    Some(split_fields(&kind[1..kind.len() - 1], ',', &['\'', '"']))
+                } else {
+                    match kind.to_lowercase().as_str() {
+                        "numeric" | "real" | "integer" => None,
+                        _ => {
+                            return Err(invalid(format!(
+                                "line {}: unsupported type of attribute '{}'",
+                                number, name
+                            )))
+                        }
+                    }
+                };
+                columns.push(Column::new(name, categories));
+            } else if lower.starts_with("@data") {
+                data = true;
+            }
+            continue;
+        }
+
+        if line.starts_with('{') && line.ends_with('}') {
+            // Sparse row, omitted values are zero or the first category
+            let mut values = columns
+                .iter()
+                .map(|column| match &column.categories {
+                    Some(categories) => categories[0].clone(),
+                    None => "0".to_string(),
+                })
+                .collect::<Vec<String>>();
+            for field in split_fields(&line[1..line.len() - 1], ',', &[]) {
+                if field.is_empty() {
+                    continue;
+                }
+                let (index, value) = split_name(&field);
+                match index.parse::<usize>() {
+                    Ok(index) if index < values.len() => {
+                        values[index] = split_fields(value, ',', &['\'', '"']).remove(0)
+                    }
+                    _ => {
+                        return Err(invalid(format!(
+                            "line {}: invalid sparse value '{}'",
+                            number, field
+                        )))
+                    }
+                }
+            }
+            for (column, value) in columns.iter_mut().zip(values) {
+                column.values.push(value);
+            }
+        } else {
+            let fields = split_fields(line, ',', &['\'', '"']);
+            if fields.len() != columns.len() {
+                return Err(invalid(format!(
+                    "line {}: {} values, expected {}",
+                    number,
+                    fields.len(),
+                    columns.len()
+                )));
+            }
+            for (column, value) in columns.iter_mut().zip(fields) {
+                column.values.push(value);
+            }
+        }
+    }
+
+    encode(columns, target_column, target_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect(Path::new("data/iris.CSV")), Format::Csv);
+        assert_eq!(Format::detect(Path::new("a9a.libsvm")), Format::Libsvm);
+        assert_eq!(Format::detect(Path::new("weather.arff")), Format::Arff);
+        assert_eq!(
+            Format::detect(Path::new("mnist/train-images.idx3-ubyte")),
+            Format::Idx
+        );
+        assert_eq!(
+            Format::detect(Path::new("datasets/generated/iris")),
+            Format::Native
+        );
+    }
+
+    #[test]
+    fn test_native() {
+        let samples = parse_native("true\ntrue\n\n1, 2\n3, 4\n\n0, 1\n1, 0\n").unwrap();
+        assert!(samples.is_classification && samples.one_hot_output);
+        assert_eq!(samples.inputs, vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        assert_eq!(samples.targets, vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+
+        assert!(parse_native("false\nfalse\n\n1, x\n\n0\n").is_err());
+    }
+
+    #[test]
+    fn test_csv() {
+        let text = "length,\"name, quoted\",width\n1.5,b,2\n0.5,a,3\n2.5,b,1\n";
+
+        let samples = parse_csv(text, true, "name, quoted", TargetType::Auto).unwrap();
+        assert!(samples.is_classification && samples.one_hot_output);
+        assert_eq!(samples.inputs[0], vec![1.5, 2.0]);
+        assert_eq!(
+            samples.targets,
+            vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![0.0, 1.0]]
+        );
+
+        // Last column by default, numeric targets are regression
+        let samples = parse_csv(text, true, "", TargetType::Auto).unwrap();
+        assert!(!samples.is_classification);
+        assert_eq!(samples.inputs[1], vec![0.5, 1.0, 0.0]);
+        assert_eq!(samples.targets, vec![vec![2.0], vec![3.0], vec![1.0]]);
+
+        // Classes of numeric labels are ordered by value
+        let samples = parse_csv("1,10\n2,9\n3,10\n", false, "1", TargetType::Categorical).unwrap();
+        assert_eq!(samples.inputs, vec![vec![1.0], vec![2.0], vec![3.0]]);
+        assert_eq!(samples.targets[1], vec![1.0, 0.0]);
+
+        assert!(parse_csv(text, true, "missing", TargetType::Auto).is_err());
+        assert!(parse_csv(text, true, "name, quoted", TargetType::Numeric).is_err());
+        assert!(parse_csv("a,b\n1,2\n3\n", true, "", TargetType::Auto).is_err());
+        assert!(parse_csv("a,b\n1,2\n3,\n", true, "", TargetType::Auto).is_err());
+
+        // Missing inputs
+        let samples = parse_csv("a,b,c\n1,x,2\n?,,4\n", true, "", TargetType::Auto).unwrap();
+        assert!(samples.inputs[1][0].is_nan());
+        assert_eq!(samples.inputs[1][1..], [0.0]);
+    }
+
+    #[test]
+    fn test_libsvm() {
+        let text = "+1 1:0.5 3:2 # comment\n-1 2:1.5\n\n+1 1:1\n";
+        let samples = parse_libsvm(text, TargetType::Auto).unwrap();
+        assert!(samples.is_classification);
+        assert_eq!(
+            samples.inputs,
+            vec![
+                vec![0.5, 0.0, 2.0],
+                vec![0.0, 1.5, 0.0],
+                vec![1.0, 0.0, 0.0]
+            ]
+        );
+        assert_eq!(samples.targets[0], vec![0.0, 1.0]);
+        assert_eq!(samples.targets[1], vec![1.0, 0.0]);
+
+        let samples = parse_libsvm("0.25 1:1\n1.5 1:2\n", TargetType::Auto).unwrap();
+        assert!(!samples.is_classification);
+        assert_eq!(samples.targets, vec![vec![0.25], vec![1.5]]);
+
+        assert!(parse_libsvm("1 0:1\n", TargetType::Auto).is_err());
+        assert!(parse_libsvm("1 1:x\n", TargetType::Auto).is_err());
+    }
+
+    #[test]
+    fn test_arff() {
+        let text = "% Comment\n\
+                    @RELATION weather\n\
+                    \n\
+                    @ATTRIBUTE outlook {sunny, overcast, rainy}\n\
+                    @attribute temperature REAL\n\
+                    @attribute 'play tennis' {yes, no}\n\
+                    \n\
+                    @DATA\n\
+                    sunny, 85, no\n\
+                    'rainy', 70, yes\n\
+                    {1 64, 2 yes}\n";
+
+        let samples = parse_arff(text, "", TargetType::Auto).unwrap();
+        assert!(samples.is_classification && samples.one_hot_output);
+        assert_eq!(
+            samples.inputs,
+            vec![
+                vec![1.0, 0.0, 0.0, 85.0],
+                vec![0.0, 0.0, 1.0, 70.0],
+                vec![1.0, 0.0, 0.0, 64.0]
+            ]
+        );
+        assert_eq!(
+            samples.targets,
+            vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 0.0]]
+        );
+
+        let samples = parse_arff(text, "temperature", TargetType::Auto).unwrap();
+        assert!(!samples.is_classification);
+        assert_eq!(samples.inputs[0], vec![1.0, 0.0, 0.0, 0.0, 1.0]);
+
+        assert!(parse_arff("@attribute name string\n@data\nx\n", "", TargetType::Auto).is_err());
+        assert!(parse_arff(
+            "@attribute a {x, y}\n@attribute b real\n@data\nz, 1\n",
+            "b",
+            TargetType::Auto
+        )
+        .is_err());
+    }
+}
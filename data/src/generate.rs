@@ -0,0 +1,289 @@
+use crate::format::{invalid, Samples};
+use crate::idx::ImageShape;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{f64::consts::PI, io::Error};
+
+/// Largest number of bits of parity, which has a sample for each of their combinations
+const MAX_PARITY_BITS: usize = 20;
+
+/// Synthetic task given by "name" or "name:size", like "retina" or "parity:4". The size is the
+/// number of bits for parity, the image resolution for boxes and otherwise the number of
+/// samples. Binary classes are single outputs of -1 or 1.
+pub fn generate(task: &str, seed: u64) -> Result<Samples, Error> {
+    let mut parts = task.splitn(2, ':');
+    let name = parts.next().unwrap();
+    let size = parts
+        .next()
+        .map(|size| {
+            size.parse::<usize>()
+                .map_err(|_| invalid(format!("invalid size '{}' of task {}", size, name)))
+        })
+        .transpose()?;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    match name {
+        "retina" => Ok(retina()),
+        "xor" => Ok(parity(2)),
+        "parity" => match size.unwrap_or(3) {
+            n if n <= MAX_PARITY_BITS => Ok(parity(n)),
+            _ => Err(invalid(format!(
+                "parity is limited to {} bits",
+                MAX_PARITY_BITS
+            ))),
+        },
+        "spirals" => Ok(spirals(size.unwrap_or(97))),
+        "circles" => Ok(circles(size.unwrap_or(200), &mut rng)),
+        "sine" => Ok(regression(size.unwrap_or(200), &mut rng, |x| {
+            (PI * x).sin()
+        })),
+        "polynomial" => Ok(regression(size.unwrap_or(200), &mut rng, |x| x.powi(3) - x)),
+        "boxes" => match size.unwrap_or(11) {
+            resolution if resolution >= 4 => Ok(boxes(resolution, &mut rng)),
+            _ => Err(invalid(
+                "boxes require a resolution of at least 4".to_string(),
+            )),
+        },
+        _ => Err(invalid(format!("unknown task '{}'", name))),
+    }
+}
+
+fn sign(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn binary(inputs: Vec<Vec<f64>>, targets: Vec<Vec<f64>>) -> Samples {
+    Samples {
+        inputs,
+        targets,
+        is_classification: true,
+        one_hot_output: false,
+        image: None,
+    }
+}
+
+/// Bits of a number as inputs of -1 or 1, most significant first
+fn bits(value: usize, count: usize) -> Vec<f64> {
+    (0..count)
+        .rev()
+        .map(|bit| sign(value >> bit & 1 == 1))
+        .collect()
+}
+
+/// Left and right retina problem, with all 256 combinations of two 2x2 retinas and one output
+/// per side telling whether it shows one of the patterns of that side
+fn retina() -> Samples {
+    let left_patterns = [
+        0b0111, 0b1011, 0b0011, 0b0000, 0b0100, 0b1000, 0b0001, 0b0010,
+    ];
+    let right_patterns = [
+        0b0100, 0b1000, 0b0001, 0b0010, 0b1101, 0b1110, 0b1100, 0b0000,
+    ];
+
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    for left in 0..16 {
+        for right in 0..16 {
+            let mut input = bits(left, 4);
+            input.extend(bits(right, 4));
+            inputs.push(input);
+            targets.push(vec![
+                sign(left_patterns.contains(&left)),
+                sign(right_patterns.contains(&right)),
+            ]);
+        }
+    }
+    binary(inputs, targets)
+}
+
+/// All combinations of n bits, with the output telling whether an odd number of them is set
+fn parity(n: usize) -> Samples {
+    let (inputs, targets) = (0..1 << n)
+        .map(|value: usize| (bits(value, n), vec![sign(value.count_ones() % 2 == 1)]))
+        .unzip();
+    binary(inputs, targets)
+}
+
+/// Two interleaved spirals of three turns, as in the benchmark by Lang and Witbrock, with n
+/// points on each
+fn spirals(n: usize) -> Samples {
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    for i in 0..n {
+        let fraction = if n > 1 {
+            i as f64 / (n - 1) as f64
+        } else {
+            0.0
+        };
+        let angle = 6.0 * PI * fraction;
+        let radius = (104.0 - 96.0 * fraction) / 104.0;
+        let (x, y) = (radius * angle.sin(), radius * angle.cos());
+
+        inputs.push(vec![x, y]);
+        targets.push(vec![1.0]);
+        inputs.push(vec![-x, -y]);
+        targets.push(vec![-1.0]);
+    }
+    binary(inputs, targets)
+}
+
+/// Points on two noisy concentric circles, the output telling whether a point is on the inner one
+fn circles(n: usize, rng: &mut StdRng) -> Samples {
+    let (inputs, targets) = (0..n)
+        .map(|i| {
+            let inner = i % 2 == 0;
+            let radius = if inner { 0.4 } else { 0.9 } + rng.gen_range(-0.1, 0.1);
+            let angle = rng.gen_range(0.0, 2.0 * PI);
+            (
+                vec![radius * angle.cos(), radius * angle.sin()],
+                vec![sign(inner)],
+            )
+        })
+        .unzip();
+    binary(inputs, targets)
+}
+
+/// Function of uniformly sampled inputs in [-1, 1]
+fn regression(n: usize, rng: &mut StdRng, function: impl Fn(f64) -> f64) -> Samples {
+    let (inputs, targets) = (0..n)
+        .map(|_| {
+            let x = rng.gen_range(-1.0, 1.0);
+            (vec![x], vec![function(x)])
+        })
+        .unzip();
+    Samples {
+        inputs,
+        targets,
+        is_classification: false,
+        one_hot_output: false,
+        image: None,
+    }
+}
+
+/// Visual discrimination of a 3x3 box and a single pixel box on a square image, as in the
+/// HyperNEAT experiments by Stanley et al. The target is the one-hot encoded center of the large
+/// box, which takes every position once, with the small box placed randomly outside it.
+fn boxes(resolution: usize, rng: &mut StdRng) -> Samples {
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    for y in 1..resolution - 1 {
+        for x in 1..resolution - 1 {
+            let mut input = vec![0.0; resolution * resolution];
+            for j in y - 1..=y + 1 {
+                for i in x - 1..=x + 1 {
+                    input[j * resolution + i] = 1.0;
+                }
+            }
+
+            let small = loop {
+                let (i, j) = (rng.gen_range(0, resolution), rng.gen_range(0, resolution));
+                if (i as i64 - x as i64).abs() > 1 || (j as i64 - y as i64).abs() > 1 {
+                    break j * resolution + i;
+                }
+            };
+            input[small] = 1.0;
+
+            let mut target = vec![0.0; resolution * resolution];
+            target[y * resolution + x] = 1.0;
+            inputs.push(input);
+            targets.push(target);
+        }
+    }
+
+    Samples {
+        inputs,
+        targets,
+        is_classification: true,
+        one_hot_output: true,
+        image: Some(ImageShape {
+            width: resolution,
+            height: resolution,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_tasks() {
+        let retina = generate("retina", 0).unwrap();
+        assert_eq!(retina.inputs.len(), 256);
+        assert_eq!(retina.inputs[0], vec![-1.0; 8]);
+        assert_eq!(retina.targets.iter().filter(|t| t[0] > 0.0).count(), 8 * 16);
+        assert_eq!(retina.targets[0], vec![1.0, 1.0]);
+
+        let parity = generate("parity:4", 0).unwrap();
+        assert_eq!(parity.inputs.len(), 16);
+        for (input, target) in parity.inputs.iter().zip(parity.targets.iter()) {
+            let ones = input.iter().filter(|x| **x > 0.0).count();
+            assert_eq!(target[0] > 0.0, ones % 2 == 1);
+        }
+        assert_eq!(generate("xor", 0).unwrap().targets.len(), 4);
+
+        let spirals = generate("spirals", 0).unwrap();
+        assert_eq!(spirals.inputs.len(), 2 * 97);
+        assert_eq!(spirals.inputs[0], vec![0.0, 1.0]);
+        assert_eq!(spirals.inputs[1], vec![-0.0, -1.0]);
+
+        let circles = generate("circles:50", 0).unwrap();
+        assert_eq!(circles.inputs.len(), 50);
+        for (input, target) in circles.inputs.iter().zip(circles.targets.iter()) {
+            let radius = (input[0].powi(2) + input[1].powi(2)).sqrt();
+            assert_eq!(target[0] > 0.0, radius < 0.65);
+        }
+    }
+
+    #[test]
+    fn test_regression() {
+        let sine = generate("sine:20", 3).unwrap();
+        assert!(!sine.is_classification);
+        assert_eq!(sine.inputs.len(), 20);
+        for (input, target) in sine.inputs.iter().zip(sine.targets.iter()) {
+            assert!(input[0].abs() <= 1.0);
+            assert_eq!(target[0], (PI * input[0]).sin());
+        }
+
+        // Seeded
+        assert_eq!(sine.inputs, generate("sine:20", 3).unwrap().inputs);
+        assert_ne!(sine.inputs, generate("sine:20", 4).unwrap().inputs);
+        assert_ne!(sine.targets, generate("polynomial:20", 3).unwrap().targets);
+    }
+
+    #[test]
+    fn test_boxes() {
+        let boxes = generate("boxes:7", 0).unwrap();
+        assert_eq!(boxes.inputs.len(), 25);
+        assert_eq!(
+            boxes.image,
+            Some(ImageShape {
+                width: 7,
+                height: 7
+            })
+        );
+        for (input, target) in boxes.inputs.iter().zip(boxes.targets.iter()) {
+            assert_eq!(input.iter().sum::<f64>(), 10.0);
+            let center = target.iter().position(|t| *t == 1.0).unwrap();
+            assert_eq!(
+                input[center - 8..=center - 6]
+                    .iter()
+                    .chain(input[center - 1..=center + 1].iter())
+                    .chain(input[center + 6..=center + 8].iter())
+                    .sum::<f64>(),
+                9.0
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(generate("unknown", 0).is_err());
+        assert!(generate("parity:x", 0).is_err());
+        assert!(generate("parity:64", 0).is_err());
+        assert!(generate("boxes:3", 0).is_err());
+    }
+}
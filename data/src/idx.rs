@@ -0,0 +1,294 @@
+use crate::conf::DatasetConfig;
+use crate::format::{encode_labels, invalid, Samples};
+use std::{
+    convert::TryInto,
+    fs,
+    io::Error,
+    path::{Path, PathBuf},
+};
+
+/// Width and height of inputs that form an image, stored row by row
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ImageShape {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl ImageShape {
+    pub fn pixels(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+/// Array read from an IDX file, with values in row-major order
+#[derive(Debug)]
+pub struct IdxArray {
+    pub dimensions: Vec<usize>,
+    pub values: Vec<f64>,
+    /// Values are unsigned bytes, like image pixels
+    pub unsigned_bytes: bool,
+}
+
+pub fn parse_idx(bytes: &[u8]) -> Result<IdxArray, Error> {
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+        return Err(invalid("not an IDX file".to_string()));
+    }
+    let (data_type, rank) = (bytes[2], bytes[3] as usize);
+    let size = match data_type {
+        0x08 | 0x09 => 1,
+        0x0B => 2,
+        0x0C | 0x0D => 4,
+        0x0E => 8,
+        _ => return Err(invalid(format!("unknown IDX data type {:#04x}", data_type))),
+    };
+
+    let start = 4 + 4 * rank;
+    if bytes.len() < start {
+        return Err(invalid("truncated IDX header".to_string()));
+    }
+    let dimensions = bytes[4..start]
+        .chunks(4)
+        .map(|b| u32::from_be_bytes(b.try_into().unwrap()) as usize)
+        .collect::<Vec<usize>>();
+    let count = dimensions.iter().product::<usize>();
+    if bytes.len() != start + count * size {
+        return Err(invalid(format!(
+            "IDX file has {} bytes of data, expected {}",
+            bytes.len() - start,
+            count * size
+        )));
+    }
+
+    let values = bytes[start..]
+        .chunks(size)
+        .map(|b| match data_type {
+            0x08 => b[0] as f64,
+            0x09 => b[0] as i8 as f64,
+            0x0B => i16::from_be_bytes(b.try_into().unwrap()) as f64,
+            0x0C => i32::from_be_bytes(b.try_into().unwrap()) as f64,
+            0x0D => f32::from_be_bytes(b.try_into().unwrap()) as f64,
+            _ => f64::from_be_bytes(b.try_into().unwrap()),
+        })
+        .collect();
+
+    Ok(IdxArray {
+        dimensions,
+        values,
+        unsigned_bytes: data_type == 0x08,
+    })
+}
+
+pub fn read_idx(path: &Path) -> Result<IdxArray, Error> {
+    parse_idx(&fs::read(path)?)
+}
+
+/// Labels file of an images file, following the MNIST naming
+fn labels_path(images: &Path) -> Option<PathBuf> {
+    let name = images.file_name()?.to_str()?;
+    let labels = name.replace("images", "labels").replace("idx3", "idx1");
+    if labels == name {
+        None
+    } else {
+        Some(images.with_file_name(labels))
+    }
+}
+
+/// Weights of the source pixels covered by each target pixel, summing to one
+fn area_weights(source: usize, target: usize) -> Vec<Vec<(usize, f64)>> {
+    let scale = source as f64 / target as f64;
+    (0..target)
+        .map(|t| {
+            let (start, end) = (t as f64 * scale, (t + 1) as f64 * scale);
+            (start.floor() as usize..(end.ceil() as usize).min(source))
+                .map(|s| (s, (end.min(s as f64 + 1.0) - start.max(s as f64)) / scale))
+                .filter(|(_, weight)| *weight > 0.0)
+                .collect()
+        })
+        .collect()
+}
+
+/// Remove a border of pixels from each side of an image, then resize it by averaging the area
+/// covered by each pixel
+pub fn resize(pixels: &[f64], shape: ImageShape, crop: usize, target: ImageShape) -> Vec<f64> {
+    let columns = area_weights(shape.width - 2 * crop, target.width);
+    let rows = area_weights(shape.height - 2 * crop, target.height);
+
+    let mut resized = Vec::with_capacity(target.pixels());
+    for row in rows.iter() {
+        for column in columns.iter() {
+            let mut value = 0.0;
+            for (y, row_weight) in row.iter() {
+                for (x, column_weight) in column.iter() {
+                    value +=
+                        row_weight * column_weight * pixels[(y + crop) * shape.width + x + crop];
+                }
+            }
+            resized.push(value);
+        }
+    }
+    resized
+}
+
+/// Images of an IDX file with one label per image in another IDX file. Pixels of unsigned
+/// bytes are scaled to [0, 1]. Arrays of vectors instead of images are read as plain inputs.
+pub fn read_samples(path: &Path, config: &DatasetConfig) -> Result<Samples, Error> {
+    let images = read_idx(path)?;
+    let labels_path = if config.idx_labels.is_empty() {
+        labels_path(path).ok_or_else(|| {
+            invalid("unable to derive IDX labels path, set IDX_LABELS".to_string())
+        })?
+    } else {
+        PathBuf::from(&config.idx_labels)
+    };
+    let labels = read_idx(&labels_path)?;
+
+    let count = *images.dimensions.first().unwrap_or(&0);
+    if labels.dimensions.first() != Some(&count) || labels.dimensions.len() != 1 {
+        return Err(invalid(format!(
+            "IDX labels of dimensions {:?} do not match {} images",
+            labels.dimensions, count
+        )));
+    }
+    let (targets, categorical) = encode_labels(
+        labels
+            .values
+            .iter()
+            .map(|label| label.to_string())
+            .collect(),
+        config.target_type,
+    )?;
+
+    let scale = if images.unsigned_bytes { 255.0 } else { 1.0 };
+    let (inputs, image) = match *images.dimensions.get(1..).unwrap_or(&[]) {
+        [height, width] => {
+            let shape = ImageShape { width, height };
+            let crop = config.image_crop as usize;
+            if 2 * crop >= width.min(height) {
+                return Err(invalid(format!(
+                    "cropping {} pixels from each side of {}x{} images",
+                    crop, width, height
+                )));
+            }
+            let target = ImageShape {
+                width: match config.image_width {
+                    0 => width - 2 * crop,
+                    w => w as usize,
+                },
+                height: match config.image_height {
+                    0 => height - 2 * crop,
+                    h => h as usize,
+                },
+            };
+
+            let inputs = images
+                .values
+                .chunks(shape.pixels())
+                .map(|pixels| {
+                    resize(pixels, shape, crop, target)
+                        .iter()
+                        .map(|value| value / scale)
+                        .collect()
+                })
+                .collect();
+            (inputs, Some(target))
+        }
+        [features] if features > 0 => (
+            images
+                .values
+                .chunks(features)
+                .map(|input| input.iter().map(|value| value / scale).collect())
+                .collect(),
+            None,
+        ),
+        _ => {
+            return Err(invalid(format!(
+                "IDX inputs of dimensions {:?} are not images or vectors",
+                images.dimensions
+            )))
+        }
+    };
+
+    Ok(Samples {
+        inputs,
+        targets,
+        is_classification: categorical,
+        one_hot_output: categorical,
+        image,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idx(data_type: u8, dimensions: &[u32], data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0, 0, data_type, dimensions.len() as u8];
+        for dimension in dimensions {
+            bytes.extend_from_slice(&dimension.to_be_bytes());
+        }
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn test_parse() {
+        let array = parse_idx(&idx(0x08, &[2, 3], &[0, 1, 2, 3, 4, 255])).unwrap();
+        assert_eq!(array.dimensions, vec![2, 3]);
+        assert_eq!(array.values, vec![0.0, 1.0, 2.0, 3.0, 4.0, 255.0]);
+        assert!(array.unsigned_bytes);
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&(-2i16).to_be_bytes());
+        data.extend_from_slice(&300i16.to_be_bytes());
+        let array = parse_idx(&idx(0x0B, &[2], &data)).unwrap();
+        assert_eq!(array.values, vec![-2.0, 300.0]);
+
+        let array = parse_idx(&idx(0x0D, &[1], &1.5f32.to_be_bytes())).unwrap();
+        assert_eq!(array.values, vec![1.5]);
+
+        assert!(parse_idx(&idx(0x08, &[2, 3], &[0, 1, 2])).is_err());
+        assert!(parse_idx(&idx(0x0A, &[1], &[0])).is_err());
+        assert!(parse_idx(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_resize() {
+        let shape = ImageShape {
+            width: 4,
+            height: 4,
+        };
+        let pixels = (0..16).map(|i| i as f64).collect::<Vec<f64>>();
+        let half = ImageShape {
+            width: 2,
+            height: 2,
+        };
+        assert_eq!(resize(&pixels, shape, 0, half), vec![2.5, 4.5, 10.5, 12.5]);
+        assert_eq!(resize(&pixels, shape, 1, half), vec![5.0, 6.0, 9.0, 10.0]);
+
+        // Target pixels covering parts of source pixels
+        let row = ImageShape {
+            width: 3,
+            height: 1,
+        };
+        let resized = resize(
+            &[0.0, 3.0, 6.0],
+            row,
+            0,
+            ImageShape {
+                width: 2,
+                height: 1,
+            },
+        );
+        assert!((resized[0] - 1.0).abs() < 1e-12);
+        assert!((resized[1] - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_labels_path() {
+        assert_eq!(
+            labels_path(Path::new("mnist/train-images.idx3-ubyte")),
+            Some(PathBuf::from("mnist/train-labels.idx1-ubyte"))
+        );
+        assert_eq!(labels_path(Path::new("digits.idx")), None);
+    }
+}
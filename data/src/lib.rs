@@ -2,6 +2,12 @@ pub mod accuracy;
 pub mod conf;
 pub mod dataset;
 pub mod error;
+pub mod format;
+pub mod generate;
+pub mod idx;
+pub mod matrix;
+pub mod metric;
+pub mod preprocess;
 
 #[macro_use]
 extern crate envconfig_derive;
@@ -0,0 +1,116 @@
+use std::ops::{Index, Range};
+
+/// Rows of equal length stored contiguously, row by row
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Matrix {
+    values: Vec<f64>,
+    rows: usize,
+    columns: usize,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, columns: usize) -> Matrix {
+        Matrix {
+            values: vec![0.0; rows * columns],
+            rows,
+            columns,
+        }
+    }
+
+    /// Matrix of the given values, row by row
+    pub fn from_values(values: Vec<f64>, columns: usize) -> Matrix {
+        assert!(
+            values.len().checked_rem(columns) == Some(0),
+            "{} values do not form rows of {} columns",
+            values.len(),
+            columns
+        );
+        Matrix {
+            rows: values.len() / columns,
+            values,
+            columns,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[f64]> {
+        (0..self.rows).map(move |i| &self[i])
+    }
+
+    /// Values of a range of rows, row by row
+    pub fn slice(&self, rows: Range<usize>) -> &[f64] {
+        &self.values[rows.start * self.columns..rows.end * self.columns]
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [f64] {
+        &mut self.values
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<f64>> {
+        self.iter().map(|row| row.to_vec()).collect()
+    }
+}
+
+impl Index<usize> for Matrix {
+    type Output = [f64];
+
+    fn index(&self, row: usize) -> &[f64] {
+        &self.values[row * self.columns..(row + 1) * self.columns]
+    }
+}
+
+/// Rows of the first length, panics if any other row differs
+impl From<Vec<Vec<f64>>> for Matrix {
+    fn from(rows: Vec<Vec<f64>>) -> Matrix {
+        let columns = rows.first().map_or(0, Vec::len);
+        let mut values = Vec::with_capacity(rows.len() * columns);
+        let count = rows.len();
+        for row in rows {
+            assert_eq!(row.len(), columns, "rows of different lengths");
+            values.extend(row);
+        }
+        Matrix {
+            values,
+            rows: count,
+            columns,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix() {
+        let matrix = Matrix::from(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]]);
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(matrix.columns(), 2);
+        assert_eq!(&matrix[1], &[3.0, 4.0]);
+        assert_eq!(matrix.slice(1..3), &[3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(matrix.iter().last(), Some(&[5.0, 6.0][..]));
+        assert_eq!(Matrix::from_values(matrix.as_slice().to_vec(), 2), matrix);
+        assert_eq!(matrix.to_rows()[2], vec![5.0, 6.0]);
+
+        // Rows without columns are still counted
+        let empty = Matrix::from(vec![vec![], vec![]]);
+        assert_eq!(empty.len(), 2);
+        assert_eq!(empty.iter().count(), 2);
+        assert!(Matrix::from(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::accuracy;
+use crate::error;
+use crate::format::invalid;
+use crate::matrix::Matrix;
+use serde::Serialize;
+use std::{fmt, io::Error, str};
+
+/// Measure of predictions, used as FITNESS and reported in METRICS
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum Metric {
+    /// Crossentropy for one-hot classes, otherwise MSE as fitness. Reported metrics of the task.
+    Auto,
+    Mse,
+    Mae,
+    Rmse,
+    /// Huber loss with HUBER_DELTA
+    Huber,
+    /// Coefficient of determination
+    R2,
+    Crossentropy,
+    Accuracy,
+    BalancedAccuracy,
+    MacroF1,
+    Auc,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseMetricError;
+
+impl fmt::Display for ParseMetricError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot parse metric")
+    }
+}
+
+impl str::FromStr for Metric {
+    type Err = ParseMetricError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Metric::Auto),
+            "mse" => Ok(Metric::Mse),
+            "mae" => Ok(Metric::Mae),
+            "rmse" => Ok(Metric::Rmse),
+            "huber" => Ok(Metric::Huber),
+            "r2" => Ok(Metric::R2),
+            "crossentropy" => Ok(Metric::Crossentropy),
+            "accuracy" => Ok(Metric::Accuracy),
+            "balanced_accuracy" => Ok(Metric::BalancedAccuracy),
+            "macro_f1" => Ok(Metric::MacroF1),
+            "auc" => Ok(Metric::Auc),
+            _ => Err(ParseMetricError {}),
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Metric::Auto => "auto",
+            Metric::Mse => "mse",
+            Metric::Mae => "mae",
+            Metric::Rmse => "rmse",
+            Metric::Huber => "huber",
+            Metric::R2 => "r2",
+            Metric::Crossentropy => "crossentropy",
+            Metric::Accuracy => "accuracy",
+            Metric::BalancedAccuracy => "balanced_accuracy",
+            Metric::MacroF1 => "macro_f1",
+            Metric::Auc => "auc",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Kind of targets predicted
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Task {
+    pub is_classification: bool,
+    pub one_hot_output: bool,
+    pub huber_delta: f64,
+}
+
+/// Comma separated metrics, like "rmse,r2", where auto adds the metrics of the task
+pub fn parse_metrics(metrics: &str, task: &Task) -> Result<Vec<Metric>, Error> {
+    let mut parsed = Vec::new();
+    for name in metrics.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+        let metric = name
+            .parse::<Metric>()
+            .map_err(|_| invalid(format!("unknown metric '{}'", name)))?;
+        for metric in metric.resolve(task, false)? {
+            if !parsed.contains(&metric) {
+                parsed.push(metric);
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+impl Metric {
+    /// Metrics measured for the task, the fitness function or the reported metrics for auto
+    pub fn resolve(self, task: &Task, fitness: bool) -> Result<Vec<Metric>, Error> {
+        let metrics = match (self, fitness) {
+            (Metric::Auto, true) if task.is_classification && task.one_hot_output => {
+                vec![Metric::Crossentropy]
+            }
+            (Metric::Auto, true) => vec![Metric::Mse],
+            (Metric::Auto, false) if !task.is_classification => {
+                vec![Metric::Mae, Metric::Rmse, Metric::R2]
+            }
+            (Metric::Auto, false) => vec![Metric::BalancedAccuracy, Metric::MacroF1, Metric::Auc],
+            (metric, _) => vec![metric],
+        };
+
+        for metric in metrics.iter() {
+            let applies = match metric {
+                Metric::Crossentropy => task.is_classification && task.one_hot_output,
+                Metric::Accuracy | Metric::BalancedAccuracy | Metric::MacroF1 | Metric::Auc => {
+                    task.is_classification
+                }
+                _ => true,
+            };
+            if !applies {
+                return Err(invalid(format!(
+                    "metric {} does not apply to the dataset",
+                    metric
+                )));
+            }
+        }
+        Ok(metrics)
+    }
+
+    /// Value of the metric, lower is better for errors and higher for other metrics. Predictions
+    /// of one-hot classes that are not probabilities are normalized for MSE and crossentropy.
+    pub fn measure(
+        self,
+        task: &Task,
+        targets: &Matrix,
+        predictions: &Matrix,
+        probabilities: bool,
+    ) -> f64 {
+        let norm = task.is_classification && task.one_hot_output && !probabilities;
+        let one_hot = task.one_hot_output;
+
+        match self {
+            Metric::Auto => panic!("metric auto is not resolved"),
+            Metric::Mse => error::mse(targets, predictions, norm),
+            Metric::Mae => error::mae(targets, predictions),
+            Metric::Rmse => error::rmse(targets, predictions),
+            Metric::Huber => error::huber(targets, predictions, task.huber_delta),
+            Metric::R2 => error::r_squared(targets, predictions),
+            Metric::Crossentropy => error::crossentropy(targets, predictions, norm),
+            Metric::Accuracy if one_hot => accuracy::one_hot_accuracy(targets, predictions),
+            Metric::Accuracy => accuracy::binary_accuracy(targets, predictions),
+            Metric::BalancedAccuracy => accuracy::balanced_accuracy(targets, predictions, one_hot),
+            Metric::MacroF1 => accuracy::macro_f1(targets, predictions, one_hot),
+            Metric::Auc => accuracy::auc(targets, predictions, one_hot),
+        }
+    }
+
+    /// Fitness to maximize, exp(-crossentropy), one minus other errors or the metric itself,
+    /// and zero if not finite
+    pub fn fitness(
+        self,
+        task: &Task,
+        targets: &Matrix,
+        predictions: &Matrix,
+        probabilities: bool,
+    ) -> f64 {
+        let value = self.measure(task, targets, predictions, probabilities);
+        let fitness = match self {
+            Metric::Crossentropy => (-value).exp(),
+            Metric::Mse | Metric::Mae | Metric::Rmse | Metric::Huber => 1.0 - value,
+            _ => value,
+        };
+        if fitness.is_finite() {
+            fitness
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(is_classification: bool, one_hot_output: bool) -> Task {
+        Task {
+            is_classification,
+            one_hot_output,
+            huber_delta: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let regression = task(false, false);
+        assert_eq!(
+            parse_metrics("auto, mse,rmse", &regression).unwrap(),
+            vec![Metric::Mae, Metric::Rmse, Metric::R2, Metric::Mse]
+        );
+        assert!(parse_metrics("", &regression).unwrap().is_empty());
+        assert!(parse_metrics("auc", &regression).is_err());
+        assert!(parse_metrics("precision", &regression).is_err());
+
+        assert_eq!(
+            Metric::Auto.resolve(&task(true, true), true).unwrap(),
+            vec![Metric::Crossentropy]
+        );
+        assert_eq!(
+            Metric::Auto.resolve(&task(true, false), true).unwrap(),
+            vec![Metric::Mse]
+        );
+        assert!(Metric::Crossentropy
+            .resolve(&task(true, false), true)
+            .is_err());
+    }
+
+    #[test]
+    fn test_fitness() {
+        let regression = task(false, false);
+        let targets = Matrix::from_values(vec![1.0, -1.0], 1);
+        let predictions = Matrix::from_values(vec![0.5, -1.0], 1);
+        assert_eq!(
+            Metric::Mae.fitness(&regression, &targets, &predictions, false),
+            0.75
+        );
+        assert_eq!(
+            Metric::R2.fitness(&regression, &targets, &predictions, false),
+            1.0 - 0.25 / 2.0
+        );
+        assert_eq!(
+            Metric::Mse.fitness(
+                &regression,
+                &targets,
+                &Matrix::from_values(vec![f64::NAN, 0.0], 1),
+                false
+            ),
+            0.0
+        );
+
+        let classification = task(true, true);
+        let targets = Matrix::from_values(vec![1.0, 0.0], 2);
+        assert!(
+            (Metric::Crossentropy.fitness(
+                &classification,
+                &targets,
+                &Matrix::from_values(vec![0.5, 0.5], 2),
+                true
+            ) - 0.5)
+                .abs()
+                < 1e-6
+        );
+        assert_eq!(
+            Metric::Accuracy.fitness(
+                &classification,
+                &targets,
+                &Matrix::from_values(vec![0.6, 0.4], 2),
+                true
+            ),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use crate::format::invalid;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::Error, path::Path};
+
+/// Step of PREPROCESSING, a comma separated list like "impute,zscore,pca:8"
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Replace missing values by the mean of each feature
+    Impute,
+    /// Scale each feature to [-1, 1]
+    MinMax,
+    /// Scale each feature to zero mean and unit variance
+    ZScore,
+    /// Project onto the given number of principal components
+    Pca(usize),
+}
+
+pub fn parse_steps(steps: &str) -> Result<Vec<Step>, Error> {
+    steps
+        .split(',')
+        .map(|step| step.trim())
+        .filter(|step| !step.is_empty())
+        .map(|step| match step.to_lowercase().as_str() {
+            "impute" => Ok(Step::Impute),
+            "minmax" => Ok(Step::MinMax),
+            "zscore" => Ok(Step::ZScore),
+            pca if pca.starts_with("pca:") => pca[4..]
+                .parse::<usize>()
+                .ok()
+                .filter(|k| *k > 0)
+                .map(Step::Pca)
+                .ok_or_else(|| invalid(format!("invalid preprocessing step '{}'", step))),
+            _ => Err(invalid(format!("unknown preprocessing step '{}'", step))),
+        })
+        .collect()
+}
+
+/// Step fitted to the training inputs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Transform {
+    /// Replace missing values by the value of each feature
+    Impute(Vec<f64>),
+    /// Subtract the offset of each feature and multiply by its scale
+    Affine { offset: Vec<f64>, scale: Vec<f64> },
+    /// Subtract the mean of each feature and project onto each component
+    Project {
+        mean: Vec<f64>,
+        components: Vec<Vec<f64>>,
+    },
+}
+
+impl Transform {
+    fn apply(&self, input: &[f64]) -> Vec<f64> {
+        match self {
+            Transform::Impute(values) => input
+                .iter()
+                .zip(values.iter())
+                .map(|(x, value)| if x.is_nan() { *value } else { *x })
+                .collect(),
+            Transform::Affine { offset, scale } => input
+                .iter()
+                .zip(offset.iter().zip(scale.iter()))
+                .map(|(x, (offset, scale))| (x - offset) * scale)
+                .collect(),
+            Transform::Project { mean, components } => components
+                .iter()
+                .map(|component| {
+                    input
+                        .iter()
+                        .zip(mean.iter().zip(component.iter()))
+                        .map(|(x, (mean, c))| (x - mean) * c)
+                        .sum()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Transformation of raw dataset inputs into network inputs, saved alongside champions as JSON
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub transforms: Vec<Transform>,
+    /// Append an input of 1.0
+    pub bias_input: bool,
+}
+
+impl Pipeline {
+    /// Fit each step to the training inputs as transformed by the steps before it
+    pub fn fit(steps: &[Step], inputs: &[Vec<f64>], bias_input: bool) -> Pipeline {
+        let mut inputs = inputs.to_vec();
+        let mut transforms = Vec::new();
+        for step in steps.iter() {
+            let transform = fit_step(*step, &inputs);
+            for input in inputs.iter_mut() {
+                *input = transform.apply(input);
+            }
+            transforms.push(transform);
+        }
+
+        Pipeline {
+            transforms,
+            bias_input,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.transforms.is_empty() && !self.bias_input
+    }
+
+    pub fn apply(&self, input: &[f64]) -> Vec<f64> {
+        let mut input = self
+            .transforms
+            .iter()
+            .fold(input.to_vec(), |input, transform| transform.apply(&input));
+        if self.bias_input {
+            input.push(1.0);
+        }
+        input
+    }
+
+    pub fn apply_all(&self, inputs: &mut [Vec<f64>]) {
+        for input in inputs.iter_mut() {
+            *input = self.apply(input);
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Pipeline, Error> {
+        serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+/// Values of each feature, without missing values
+fn features(inputs: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let count = inputs.first().map_or(0, Vec::len);
+    (0..count)
+        .map(|i| {
+            inputs
+                .iter()
+                .map(|input| input[i])
+                .filter(|x| !x.is_nan())
+                .collect()
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn fit_step(step: Step, inputs: &[Vec<f64>]) -> Transform {
+    let features = features(inputs);
+    let means = features.iter().map(|f| mean(f)).collect::<Vec<f64>>();
+
+    match step {
+        Step::Impute => Transform::Impute(means),
+        Step::MinMax => {
+            let (offset, scale) = features
+                .iter()
+                .map(|f| {
+                    let min = f.iter().cloned().fold(f64::INFINITY, f64::min);
+                    let max = f.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                    if max > min {
+                        ((min + max) / 2.0, 2.0 / (max - min))
+                    } else {
+                        (mean(f), 1.0)
+                    }
+                })
+                .unzip();
+            Transform::Affine { offset, scale }
+        }
+        Step::ZScore => {
+            let scale = features
+                .iter()
+                .zip(means.iter())
+                .map(|(f, m)| {
+                    let deviation =
+                        mean(&f.iter().map(|x| (x - m).powi(2)).collect::<Vec<_>>()).sqrt();
+                    if deviation > 0.0 {
+                        1.0 / deviation
+                    } else {
+                        1.0
+                    }
+                })
+                .collect();
+            Transform::Affine {
+                offset: means,
+                scale,
+            }
+        }
+        Step::Pca(k) => {
+            let components = principal_components(inputs, &means, k);
+            Transform::Project {
+                mean: means,
+                components,
+            }
+        }
+    }
+}
+
+/// Eigenvectors of the covariance matrix with the largest eigenvalues, by power iteration on
+/// the matrix with the previous components projected out. Each component is oriented with its
+/// largest element positive.
+fn principal_components(inputs: &[Vec<f64>], means: &[f64], k: usize) -> Vec<Vec<f64>> {
+    let n = means.len();
+    let mut covariance = vec![vec![0.0; n]; n];
+    for input in inputs.iter() {
+        let centered = input
+            .iter()
+            .zip(means.iter())
+            .map(|(x, m)| if x.is_nan() { 0.0 } else { x - m })
+            .collect::<Vec<f64>>();
+        for (row, a) in covariance.iter_mut().zip(centered.iter()) {
+            for (value, b) in row.iter_mut().zip(centered.iter()) {
+                *value += a * b;
+            }
+        }
+    }
+    for value in covariance.iter_mut().flatten() {
+        *value /= inputs.len().max(1) as f64;
+    }
+
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<f64>();
+    let mut components: Vec<Vec<f64>> = Vec::new();
+    for c in 0..k.min(n) {
+        let orthogonalize = |vector: &mut Vec<f64>, components: &[Vec<f64>]| {
+            for component in components.iter() {
+                let projection = dot(vector, component);
+                for (v, c) in vector.iter_mut().zip(component.iter()) {
+                    *v -= projection * c;
+                }
+            }
+            let norm = dot(vector, vector).sqrt();
+            if norm > 0.0 {
+                for v in vector.iter_mut() {
+                    *v /= norm;
+                }
+            }
+            norm
+        };
+
+        // Deterministic start, the unit vector of this component if all others are covered
+        let mut vector = (0..n)
+            .map(|i| 1.0 + (i + c) as f64 / n as f64)
+            .collect::<Vec<f64>>();
+        if orthogonalize(&mut vector, &components) < 1e-9 {
+            vector = (0..n).map(|i| if i == c { 1.0 } else { 0.0 }).collect();
+            orthogonalize(&mut vector, &components);
+        }
+
+        for _ in 0..1000 {
+            let mut next = covariance.iter().map(|row| dot(row, &vector)).collect();
+            if orthogonalize(&mut next, &components) < 1e-12 {
+                break;
+            }
+            let change = next
+                .iter()
+                .zip(vector.iter())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            vector = next;
+            if change < 1e-12 {
+                break;
+            }
+        }
+
+        let largest =
+            vector.iter().cloned().fold(
+                0.0,
+                |largest: f64, v| if v.abs() > largest.abs() { v } else { largest },
+            );
+        if largest < 0.0 {
+            for v in vector.iter_mut() {
+                *v = -*v;
+            }
+        }
+        components.push(vector);
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs() -> Vec<Vec<f64>> {
+        vec![
+            vec![1.0, 10.0, 5.0],
+            vec![3.0, f64::NAN, 5.0],
+            vec![2.0, 30.0, 5.0],
+        ]
+    }
+
+    #[test]
+    fn test_parse_steps() {
+        assert_eq!(
+            parse_steps("impute, MinMax,pca:2").unwrap(),
+            vec![Step::Impute, Step::MinMax, Step::Pca(2)]
+        );
+        assert!(parse_steps("").unwrap().is_empty());
+        assert!(parse_steps("pca:0").is_err());
+        assert!(parse_steps("log").is_err());
+    }
+
+    #[test]
+    fn test_scaling() {
+        let pipeline = Pipeline::fit(&[Step::Impute, Step::MinMax], &inputs(), true);
+        assert_eq!(pipeline.apply(&inputs()[1]), vec![1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(pipeline.apply(&[4.0, 10.0, 6.0]), vec![2.0, -1.0, 1.0, 1.0]);
+
+        let pipeline = Pipeline::fit(&[Step::Impute, Step::ZScore], &inputs(), false);
+        let mut scaled = inputs();
+        pipeline.apply_all(&mut scaled);
+        for i in 0..3 {
+            let values = scaled.iter().map(|input| input[i]).collect::<Vec<f64>>();
+            assert!(mean(&values).abs() < 1e-12);
+        }
+        assert!((scaled[0][0] + 1.5f64.sqrt()).abs() < 1e-12);
+
+        // Missing values remain without imputation
+        let pipeline = Pipeline::fit(&[Step::MinMax], &inputs(), false);
+        assert!(pipeline.apply(&inputs()[1])[1].is_nan());
+    }
+
+    #[test]
+    fn test_pca() {
+        // Points along the line y = 2x, with small deviations along (2, -1)
+        let inputs = (0..22)
+            .map(|i| {
+                let (t, d) = ((i / 2) as f64 - 5.0, if i % 2 == 0 { 0.1 } else { -0.1 });
+                vec![t + 2.0 * d, 2.0 * t - d]
+            })
+            .collect::<Vec<_>>();
+        let pipeline = Pipeline::fit(&[Step::Pca(2)], &inputs, false);
+        match &pipeline.transforms[0] {
+            Transform::Project { components, .. } => {
+                let s = 5.0f64.sqrt();
+                assert!((components[0][0] - 1.0 / s).abs() < 1e-6);
+                assert!((components[0][1] - 2.0 / s).abs() < 1e-6);
+                assert!((components[1][0] - 2.0 / s).abs() < 1e-6);
+                assert!((components[1][1] + 1.0 / s).abs() < 1e-6);
+            }
+            transform => panic!("unexpected transform {:?}", transform),
+        }
+
+        let projected = pipeline.apply(&inputs[0]);
+        assert!((projected[0] + 5.0 * 5.0f64.sqrt()).abs() < 1e-6);
+        assert!((projected[1] - 0.1 * 5.0f64.sqrt()).abs() < 1e-6);
+        assert_eq!(
+            Pipeline::fit(&[Step::Pca(5)], &inputs, false)
+                .apply(&inputs[0])
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let pipeline = Pipeline::fit(&[Step::Impute, Step::ZScore, Step::Pca(2)], &inputs(), true);
+        let json = serde_json::to_string(&pipeline).unwrap();
+        let loaded = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert_eq!(loaded, pipeline);
+        assert_eq!(loaded.apply(&inputs()[0]), pipeline.apply(&inputs()[0]));
+    }
+}
@@ -34,6 +34,22 @@ pub struct EvolutionConfig {
 
     #[envconfig(from = "DB_LOG", default = "false")]
     pub db_log: bool,
+
+    #[envconfig(from = "SEED_GENOME", default = "")]
+    pub seed_genome: String,
+
+    #[serde(with = "bson::compat::u2f")]
+    #[envconfig(from = "SEED_MUTATIONS", default = "1")]
+    pub seed_mutations: u64,
+
+    #[envconfig(from = "SAVE_CHAMPION", default = "")]
+    pub save_champion: String,
+
+    #[envconfig(from = "SAVE_PHENOTYPE", default = "")]
+    pub save_phenotype: String,
+
+    #[envconfig(from = "DEVELOP_GENOME", default = "")]
+    pub develop_genome: String,
 }
 
 #[derive(Envconfig, Serialize, Clone)]
@@ -1,9 +1,48 @@
 use crate::environment::EnvironmentDescription;
 use crate::stats::Stats;
+use network::{execute::Executor, float::Float};
+use std::marker::PhantomData;
 
 pub trait Develop<G>: From<EnvironmentDescription> {
     type Phenotype;
     type Stats: Stats;
 
     fn develop(&self, genome: G) -> (Self::Phenotype, Self::Stats);
+
+    /// Develop a genome whose phenotype is logged, with stats too costly to gather for every
+    /// evaluation
+    fn develop_analyzed(&self, genome: G) -> (Self::Phenotype, Self::Stats) {
+        self.develop(genome)
+    }
+}
+
+/// Develops the networks of D with values and weights of float type F, converting each
+/// network once when it is developed
+pub struct FloatDeveloper<D, F> {
+    developer: D,
+    float: PhantomData<F>,
+}
+
+impl<D: From<EnvironmentDescription>, F> From<EnvironmentDescription> for FloatDeveloper<D, F> {
+    fn from(description: EnvironmentDescription) -> Self {
+        FloatDeveloper {
+            developer: D::from(description),
+            float: PhantomData,
+        }
+    }
+}
+
+impl<G, D: Develop<G, Phenotype = Executor>, F: Float> Develop<G> for FloatDeveloper<D, F> {
+    type Phenotype = Executor<F>;
+    type Stats = D::Stats;
+
+    fn develop(&self, genome: G) -> (Self::Phenotype, Self::Stats) {
+        let (network, stats) = self.developer.develop(genome);
+        (network.into_float(), stats)
+    }
+
+    fn develop_analyzed(&self, genome: G) -> (Self::Phenotype, Self::Stats) {
+        let (network, stats) = self.developer.develop_analyzed(genome);
+        (network.into_float(), stats)
+    }
 }
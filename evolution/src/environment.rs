@@ -1,17 +1,36 @@
 use crate::stats::Stats;
 use serde::Serialize;
 
-pub trait Environment: Default {
+/// Task evaluating phenotypes. One instance is shared by all evaluator threads.
+pub trait Environment: Send + Sync {
     type Config: Serialize + Default;
     type Stats: Stats;
     type Phenotype;
 
     fn evaluate(&self, phenotype: &mut Self::Phenotype) -> (f64, Self::Stats);
     fn description(&self) -> EnvironmentDescription;
+
+    /// JSON form of the transformation of raw inputs into phenotype inputs, saved alongside
+    /// champions and phenotypes so they can be evaluated on new inputs
+    fn preprocessing(&self) -> Option<String> {
+        None
+    }
+
+    /// Added before the extension of saved files, to keep apart the files of several
+    /// environments of a process, like the folds of cross-validation
+    fn save_suffix(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(new, Copy, Clone, Default, Serialize)]
 pub struct EnvironmentDescription {
     pub inputs: u64,
     pub outputs: u64,
+    /// Width and height of the first inputs if they form an image, row by row
+    #[new(default)]
+    pub input_grid: Option<(u64, u64)>,
+    /// Outputs are one-hot classes, to which a group activation like softmax applies
+    #[new(default)]
+    pub one_hot_output: bool,
 }
@@ -2,7 +2,13 @@ use crate::develop::Develop;
 use crate::environment::Environment;
 use crate::stats::Stats;
 use crossbeam::queue;
-use std::{sync::Arc, thread, time};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread, time,
+};
 
 type Input<G> = (u64, usize, G);
 type Output<P, E> = (u64, usize, f64, P, E);
@@ -20,6 +26,8 @@ pub trait Evaluate<G> {
 pub struct MultiEvaluator<G, D: Develop<G>, E: Environment> {
     input: Arc<queue::ArrayQueue<Input<G>>>,
     output: Arc<queue::ArrayQueue<Output<D::Stats, E::Stats>>>,
+    /// Set when dropped, stopping the threads
+    stopped: Arc<AtomicBool>,
 }
 
 impl<
@@ -28,19 +36,22 @@ impl<
         E: Environment + 'static,
     > MultiEvaluator<G, D, E>
 {
-    pub fn new(task_count: u64, thread_count: u64) -> Self {
+    /// Start threads evaluating organisms in the shared environment
+    pub fn new(task_count: u64, thread_count: u64, environment: Arc<E>) -> Self {
         let input = Arc::new(queue::ArrayQueue::new(task_count as usize));
         let output = Arc::new(queue::ArrayQueue::new(task_count as usize));
+        let stopped = Arc::new(AtomicBool::new(false));
 
         for _ in 0..thread_count {
             let input = input.clone();
             let output = output.clone();
+            let stopped = stopped.clone();
+            let environment = environment.clone();
 
             thread::spawn(move || {
-                let environment = E::default();
                 let developer = D::from(environment.description());
 
-                loop {
+                while !stopped.load(Ordering::Relaxed) {
                     if let Ok((species_index, organism_index, genome)) = input.pop() {
                         let (mut phenotype, phenotype_stats) = developer.develop(genome);
                         let (fitness, evaluation_stats) = environment.evaluate(&mut phenotype);
@@ -53,6 +64,9 @@ impl<
                         );
 
                         while let Err(queue::PushError(ret)) = output.push(result) {
+                            if stopped.load(Ordering::Relaxed) {
+                                return;
+                            }
                             result = ret;
                             thread::sleep(time::Duration::from_nanos(1000));
                         }
@@ -63,7 +77,17 @@ impl<
             });
         }
 
-        MultiEvaluator { input, output }
+        MultiEvaluator {
+            input,
+            output,
+            stopped,
+        }
+    }
+}
+
+impl<G, D: Develop<G>, E: Environment> Drop for MultiEvaluator<G, D, E> {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
     }
 }
 
@@ -9,6 +9,28 @@ pub trait GenericGenome<C, S: Default, I, T>: Clone + Send {
         0.0
     }
     fn get_stats(&self) -> T;
+    /// Check internal invariants, describing the first violation found
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+    /// Whether save and load are implemented
+    const SAVABLE: bool = false;
+    /// Serialize genome, to be restored by load
+    fn save(&self) -> Option<String> {
+        None
+    }
+    /// Restore a serialized genome, registering its structure in the state
+    fn load(
+        _config: &C,
+        _init_config: &I,
+        _serialized: &str,
+        _state: &mut S,
+    ) -> Result<Self, String>
+    where
+        Self: Sized,
+    {
+        Err("genome type cannot be loaded".to_owned())
+    }
 }
 
 pub trait Genome:
@@ -24,3 +46,44 @@ pub trait Genome:
     type State: Default;
     type Stats: Stats;
 }
+
+/// Evolve a few genomes by random mutations and crossovers, panicking at the first invalid
+/// genome. Every mutation is followed by the extra mutation, for operators outside of mutate.
+/// Used by the genome tests of each method, with the test-support feature.
+#[cfg(any(test, feature = "test-support"))]
+pub fn check_random_evolution<G: Genome>(
+    config: &G::Config,
+    init_config: &G::InitConfig,
+    genome_count: usize,
+    iterations: usize,
+    mut extra_mutation: impl FnMut(&mut G, &mut G::State),
+) {
+    use rand::Rng;
+
+    let mut state = G::State::default();
+    let mut rng = rand::thread_rng();
+    let mut genomes = (0..genome_count)
+        .map(|_| G::new(config, init_config, &mut state))
+        .collect::<Vec<_>>();
+    for genome in genomes.iter() {
+        assert_eq!(genome.validate(), Ok(()), "invalid new genome");
+    }
+
+    for _ in 0..iterations {
+        let i = rng.gen_range(0, genomes.len());
+        genomes[i].mutate(config, &mut state);
+        extra_mutation(&mut genomes[i], &mut state);
+        assert_eq!(
+            genomes[i].validate(),
+            Ok(()),
+            "invalid genome after mutation"
+        );
+
+        if rng.gen::<f64>() < 0.2 {
+            let j = rng.gen_range(0, genomes.len());
+            let child = genomes[i].crossover(config, &genomes[j], &rng.gen::<f64>(), &rng.gen());
+            assert_eq!(child.validate(), Ok(()), "invalid genome after crossover");
+            genomes[j] = child;
+        }
+    }
+}
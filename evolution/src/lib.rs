@@ -25,34 +25,73 @@ extern crate num_cpus;
 
 use algorithm::Algorithm;
 use conf::{CombinedConfig, PopulationConfig, EVOLUTION};
+use develop::Develop;
 use envconfig::Envconfig;
-use environment::Environment;
+use environment::{Environment, EnvironmentDescription};
 use evaluate::MultiEvaluator;
+use genome::{GenericGenome, Genome};
 use log::Log;
 use population::Population;
 use serde::Serialize;
 use std::{
+    fs,
+    path::Path,
+    sync::Arc,
     time::{Duration, SystemTime},
     u64,
 };
 
+/// Evolve a population in the environment, shared with the evaluator threads, returning the
+/// phenotype of the best genome of the last generation
 pub fn evolve<
     E: Environment + 'static,
     A: Algorithm<E>,
     L: Log<A::Genome>,
     C: Serialize + Default,
->() {
-    let environment = &E::default();
+>(
+    environment: Arc<E>,
+) -> Option<E::Phenotype>
+where
+    E::Phenotype: Serialize,
+{
+    check_genome_files::<A::Genome>();
     let environment_description = environment.description();
 
     let population_config = PopulationConfig::init().unwrap();
     let genome_config = A::genome_config(&environment_description);
     let init_config = A::genome_init_config(&environment_description);
-    let mut population = Population::<A::Genome>::new(
-        population_config.clone(),
-        genome_config.clone(),
-        &init_config,
-    );
+
+    // Develop a saved genome instead of evolving
+    if !EVOLUTION.develop_genome.is_empty() {
+        let serialized =
+            fs::read_to_string(&EVOLUTION.develop_genome).expect("unable to read genome");
+        let mut state = <A::Genome as Genome>::State::default();
+        let genome = A::Genome::load(&genome_config, &init_config, &serialized, &mut state)
+            .unwrap_or_else(|e| panic!("unable to load genome: {}", e));
+        save_phenotype::<E, A>(&*environment, &environment_description, genome);
+        return None;
+    }
+
+    let mut population = if EVOLUTION.seed_genome.is_empty() {
+        Population::<A::Genome>::new(
+            population_config.clone(),
+            genome_config.clone(),
+            &init_config,
+        )
+    } else {
+        let serialized =
+            fs::read_to_string(&EVOLUTION.seed_genome).expect("unable to read seed genome");
+        let mut state = <A::Genome as Genome>::State::default();
+        let seed = A::Genome::load(&genome_config, &init_config, &serialized, &mut state)
+            .unwrap_or_else(|e| panic!("unable to load seed genome: {}", e));
+        Population::<A::Genome>::from_seed(
+            population_config.clone(),
+            genome_config.clone(),
+            seed,
+            state,
+            EVOLUTION.seed_mutations,
+        )
+    };
 
     let evaluator = MultiEvaluator::<A::Genome, A::Developer, E>::new(
         population.population_config.population_size,
@@ -61,6 +100,7 @@ pub fn evolve<
         } else {
             num_cpus::get() as u64
         },
+        environment.clone(),
     );
     let config = CombinedConfig::new(
         EVOLUTION.clone(),
@@ -78,23 +118,142 @@ pub fn evolve<
         u64::MAX
     };
 
-    for _ in 0..EVOLUTION.initial_mutations {
-        population.mutate();
+    // A seeded population is already varied by its seed mutations
+    if EVOLUTION.seed_genome.is_empty() {
+        for _ in 0..EVOLUTION.initial_mutations {
+            population.mutate();
+        }
     }
 
+    let developer = A::Developer::from(environment_description);
+    let mut champion = None;
     let start_time = SystemTime::now();
     for i in 0..iterations {
-        let population_stats = population.evaluate(&evaluator);
+        let mut population_stats = population.evaluate(&evaluator);
+        // Only the phenotype of the logged best organism is analyzed
+        if logger.is_logged(i) {
+            if let (Some(best), Some(stats)) = (population.best(), population_stats.best_mut()) {
+                stats.phenotype = developer.develop_analyzed(best.genome.clone()).1;
+            }
+        }
         logger.log(i, &population, &population_stats);
 
-        if EVOLUTION.seconds_limit > 0
-            && SystemTime::elapsed(&start_time).unwrap()
-                >= Duration::from_secs(EVOLUTION.seconds_limit + 3)
+        if i + 1 == iterations
+            || (EVOLUTION.seconds_limit > 0
+                && SystemTime::elapsed(&start_time).unwrap()
+                    >= Duration::from_secs(EVOLUTION.seconds_limit + 3))
         {
+            save_champion::<E, A>(&population, &*environment, &environment_description);
+            champion = population
+                .best()
+                .map(|best| developer.develop(best.genome.clone()).0);
             break;
         }
 
         population.evolve();
     }
     logger.close();
+    champion
+}
+
+/// Panic before evolving if DEVELOP_GENOME, SEED_GENOME or SAVE_CHAMPION is set for genomes
+/// that cannot be saved and loaded
+pub fn check_genome_files<G: Genome>() {
+    let options = [
+        ("DEVELOP_GENOME", &EVOLUTION.develop_genome),
+        ("SEED_GENOME", &EVOLUTION.seed_genome),
+        ("SAVE_CHAMPION", &EVOLUTION.save_champion),
+    ];
+    for (option, path) in options.iter() {
+        if !G::SAVABLE && !path.is_empty() {
+            panic!(
+                "{} is not supported by this method, its genomes cannot be saved and loaded",
+                option
+            );
+        }
+    }
+}
+
+/// Write the best evaluated genome to SAVE_CHAMPION, for use as SEED_GENOME in later runs,
+/// and its phenotype to SAVE_PHENOTYPE
+fn save_champion<E: Environment, A: Algorithm<E>>(
+    population: &Population<A::Genome>,
+    environment: &E,
+    description: &EnvironmentDescription,
+) where
+    E::Phenotype: Serialize,
+{
+    if let Some(best) = population.best() {
+        if !EVOLUTION.save_champion.is_empty() {
+            if let Some(serialized) = best.genome.save() {
+                write_champion(environment, &serialized);
+            }
+        }
+        if !EVOLUTION.save_phenotype.is_empty() {
+            save_phenotype::<E, A>(environment, description, best.genome.clone());
+        }
+    }
+}
+
+/// Write the developed phenotype of a genome as JSON to SAVE_PHENOTYPE, or to standard output
+fn save_phenotype<E: Environment, A: Algorithm<E>>(
+    environment: &E,
+    description: &EnvironmentDescription,
+    genome: A::Genome,
+) where
+    E::Phenotype: Serialize,
+{
+    let developer = A::Developer::from(*description);
+    let (phenotype, _) = developer.develop(genome);
+    write_phenotype(environment, &phenotype);
+}
+
+/// Write a serialized genome to SAVE_CHAMPION, along with the preprocessing of the environment
+pub fn write_champion<E: Environment>(environment: &E, serialized: &str) {
+    let path = save_path(environment, &EVOLUTION.save_champion);
+    fs::write(&path, serialized).expect("unable to save champion");
+    save_preprocessing(environment, &path);
+}
+
+/// Write a phenotype as JSON to SAVE_PHENOTYPE, along with the preprocessing of the
+/// environment, or to standard output if SAVE_PHENOTYPE is not set
+pub fn write_phenotype<E: Environment>(environment: &E, phenotype: &E::Phenotype)
+where
+    E::Phenotype: Serialize,
+{
+    let serialized =
+        serde_json::to_string_pretty(phenotype).expect("unable to serialize phenotype");
+    if EVOLUTION.save_phenotype.is_empty() {
+        println!("{}", serialized);
+    } else {
+        let path = save_path(environment, &EVOLUTION.save_phenotype);
+        fs::write(&path, serialized).expect("unable to save phenotype");
+        save_preprocessing(environment, &path);
+    }
+}
+
+/// Path with the save suffix of the environment, if any, before its extension
+fn save_path<E: Environment>(environment: &E, path: &str) -> String {
+    match environment.save_suffix() {
+        Some(suffix) => {
+            let path = Path::new(path);
+            let extension = match path.extension() {
+                Some(extension) => format!("{}.{}", suffix, extension.to_string_lossy()),
+                None => suffix,
+            };
+            path.with_extension(extension)
+                .to_string_lossy()
+                .into_owned()
+        }
+        None => path.to_string(),
+    }
+}
+
+/// Write the preprocessing of the environment, if any, next to a saved file as
+/// <path>.preprocessing.json
+fn save_preprocessing<E: Environment>(environment: &E, path: &str) {
+    if let Some(preprocessing) = environment.preprocessing() {
+        fs::write(format!("{}.preprocessing.json", path), preprocessing)
+            .expect("unable to save preprocessing");
+    }
 }
@@ -11,6 +11,8 @@ pub trait Log<G: Genome> {
     fn new<C: Serialize>(description: &EnvironmentDescription, config: &C) -> Self;
     fn log<S: GetPopulationStats>(&mut self, iteration: u64, population: &Population<G>, stats: &S);
     fn close(&mut self);
+    /// Whether the stats of an iteration will be logged, checked before they are gathered
+    fn is_logged(&self, iteration: u64) -> bool;
 }
 
 pub struct Logger {
@@ -39,12 +41,7 @@ impl<G: Genome> Log<G> for Logger {
                 SystemTime::now() - Duration::from_secs(EVOLUTION.log_sec_interval);
         }
 
-        let log = (self.log_interval > 0 && iteration % self.log_interval == 0)
-            || (self.log_seconds > 0
-                && SystemTime::elapsed(&self.prev_log_time).unwrap()
-                    >= Duration::from_secs(self.log_seconds));
-
-        if log {
+        if self.is_due(iteration) {
             self.prev_log_time += Duration::from_secs(self.log_seconds);
 
             println!("Iter: {}", iteration);
@@ -60,4 +57,19 @@ impl<G: Genome> Log<G> for Logger {
     }
 
     fn close(&mut self) {}
+
+    fn is_logged(&self, iteration: u64) -> bool {
+        self.is_due(iteration)
+    }
+}
+
+impl Logger {
+    /// Every LOG_INTERVAL iterations, and whenever LOG_SEC_INTERVAL seconds have passed
+    fn is_due(&self, iteration: u64) -> bool {
+        (self.log_interval > 0 && iteration % self.log_interval == 0)
+            || (self.log_seconds > 0
+                && (iteration == 0
+                    || SystemTime::elapsed(&self.prev_log_time).unwrap()
+                        >= Duration::from_secs(self.log_seconds)))
+    }
 }
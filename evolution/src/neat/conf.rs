@@ -1,3 +1,4 @@
+use crate::neat::{perturbation::Perturbation, topology::InitialTopology};
 use envconfig::Envconfig;
 use lazy_static::lazy_static;
 use network::activation::Activation;
@@ -34,6 +35,25 @@ pub struct NeatConfig {
 
     #[envconfig(from = "MUTATE_ONLY_ONE_LINK", default = "true")]
     pub mutate_only_one_link: bool,
+
+    #[envconfig(from = "MUTATE_EACH_LINK_PROBABILITY", default = "1.0")]
+    pub mutate_each_link_probability: f64,
+
+    #[envconfig(from = "WEIGHT_PERTURBATION", default = "Uniform")]
+    pub weight_perturbation: Perturbation,
+
+    #[envconfig(from = "MAX_LINK_WEIGHT", default = "0.0")]
+    pub max_link_weight: f64,
+
+    #[envconfig(from = "INITIAL_TOPOLOGY", default = "Empty")]
+    pub initial_topology: InitialTopology,
+
+    #[envconfig(from = "INITIAL_LINK_DENSITY", default = "0.5")]
+    pub initial_link_density: f64,
+
+    #[serde(with = "bson::compat::u2f")]
+    #[envconfig(from = "INITIAL_HIDDEN_NODES", default = "1")]
+    pub initial_hidden_nodes: u64,
 }
 
 impl Default for NeatConfig {
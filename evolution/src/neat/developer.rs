@@ -8,9 +8,10 @@ use crate::neat::{
 };
 use crate::stats::Stats;
 use bson;
-use network::{connection, execute, execute::Executor};
+use network::{analysis::GraphSummary, connection, execute, execute::Executor};
 use serde::Serialize;
 use std::collections::HashMap;
+use std::hash::Hash;
 
 pub struct Developer;
 
@@ -20,12 +21,100 @@ impl From<EnvironmentDescription> for Developer {
     }
 }
 
-#[derive(Serialize, new)]
+#[derive(Serialize)]
 pub struct NetworkStats {
     #[serde(with = "bson::compat::u2f")]
     pub nodes: u64,
     #[serde(with = "bson::compat::u2f")]
     pub edges: u64,
+    /// Linked nodes and links before and after simplification, both counted by
+    /// Executor::size. Zero when the network is not simplified.
+    #[serde(with = "bson::compat::u2f")]
+    pub linked_nodes: u64,
+    #[serde(with = "bson::compat::u2f")]
+    pub linked_edges: u64,
+    #[serde(with = "bson::compat::u2f")]
+    pub simplified_nodes: u64,
+    #[serde(with = "bson::compat::u2f")]
+    pub simplified_edges: u64,
+    /// Structure of the developed network, only analyzed for logged networks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structure: Option<NetworkStructure>,
+}
+
+/// Graph metrics of a network, see network::analysis::GraphSummary
+#[derive(Serialize)]
+pub struct NetworkStructure {
+    #[serde(with = "bson::compat::u2f")]
+    pub depth: u64,
+    #[serde(with = "bson::compat::u2f")]
+    pub width: u64,
+    #[serde(with = "bson::compat::u2f")]
+    pub max_fan_in: u64,
+    #[serde(with = "bson::compat::u2f")]
+    pub max_fan_out: u64,
+    pub mean_fan_in: f64,
+    pub mean_fan_out: f64,
+    pub fan_in_distribution: Vec<i64>,
+    pub fan_out_distribution: Vec<i64>,
+    #[serde(with = "bson::compat::u2f")]
+    pub components: u64,
+    #[serde(with = "bson::compat::u2f")]
+    pub recurrent_components: u64,
+}
+
+impl<N: Hash + Eq + Copy, E: Copy> From<&connection::Connections<N, E>> for NetworkStructure {
+    fn from(connections: &connection::Connections<N, E>) -> Self {
+        let summary: GraphSummary = connections.summary();
+        let counts = |distribution: Vec<usize>| distribution.iter().map(|c| *c as i64).collect();
+        Self {
+            depth: summary.depth as u64,
+            width: summary.width as u64,
+            max_fan_in: summary.max_fan_in as u64,
+            max_fan_out: summary.max_fan_out as u64,
+            mean_fan_in: summary.mean_fan_in,
+            mean_fan_out: summary.mean_fan_out,
+            fan_in_distribution: counts(summary.fan_in_distribution),
+            fan_out_distribution: counts(summary.fan_out_distribution),
+            components: summary.components as u64,
+            recurrent_components: summary.recurrent_components as u64,
+        }
+    }
+}
+
+impl NetworkStats {
+    pub fn new(nodes: u64, edges: u64) -> Self {
+        Self {
+            nodes,
+            edges,
+            linked_nodes: 0,
+            linked_edges: 0,
+            simplified_nodes: 0,
+            simplified_edges: 0,
+            structure: None,
+        }
+    }
+
+    /// Simplify the network and record its size
+    pub fn simplify(&mut self, network: Executor, epsilon: f64) -> Executor {
+        let (nodes, edges) = network.size();
+        self.linked_nodes = nodes as u64;
+        self.linked_edges = edges as u64;
+
+        let network = network.simplify(epsilon);
+        let (nodes, edges) = network.size();
+        self.simplified_nodes = nodes as u64;
+        self.simplified_edges = edges as u64;
+        network
+    }
+
+    /// Record the structure of the graph the network was developed from
+    pub fn analyze<N: Hash + Eq + Copy, E: Copy>(
+        &mut self,
+        connections: &connection::Connections<N, E>,
+    ) {
+        self.structure = Some(NetworkStructure::from(connections));
+    }
 }
 
 impl Stats for NetworkStats {}
@@ -36,7 +125,7 @@ impl Develop<NeatGenome<NeatNode, NeatLink>> for Developer {
 
     fn develop(&self, genome: NeatGenome<NeatNode, NeatLink>) -> (Self::Phenotype, Self::Stats) {
         // Sort genomes netowrk topologically
-        let order = genome.connections.sort_topologically();
+        let order = genome.connections.sort_topologically_stable();
 
         // Create vector of all input node indexes, for insertion of nerual network inputs
         let num_input_nodes = genome.inputs.keys().map(|n| n.id()).max().unwrap() as usize + 1;
@@ -95,11 +184,18 @@ impl Develop<NeatGenome<NeatNode, NeatLink>> for Developer {
 
         // Create neural network executor
         let network = Executor::create(nodes.len(), inputs, outputs, actions);
-        let stats = NetworkStats {
-            nodes: nodes.len() as u64,
-            edges: genome.links.len() as u64,
-        };
+        let stats = NetworkStats::new(nodes.len() as u64, genome.links.len() as u64);
+
+        (network, stats)
+    }
 
+    fn develop_analyzed(
+        &self,
+        genome: NeatGenome<NeatNode, NeatLink>,
+    ) -> (Self::Phenotype, Self::Stats) {
+        let structure = NetworkStructure::from(&genome.connections);
+        let (network, mut stats) = self.develop(genome);
+        stats.structure = Some(structure);
         (network, stats)
     }
 }
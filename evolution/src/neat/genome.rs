@@ -4,13 +4,15 @@ use crate::neat::{
     conf::NeatConfig,
     link::{LinkExtension, NeatLink},
     node::{NeatNode, NodeExtension, NodeRef},
+    perturbation,
     state::{InitConfig, NeatState, StateProvider},
+    topology::InitialTopology,
 };
 use crate::stats::Stats;
 use network::connection;
-use rand::{seq::SliceRandom, Rng};
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::{collections::HashMap, mem};
 
 #[derive(Clone)]
 pub struct NeatGenome<N, L> {
@@ -76,7 +78,7 @@ where
         }
     }
 
-    /// Generate genome with default activation and no connections
+    /// Generate genome with default activation and the configured initial topology
     fn new(config: &C, init_config: &InitConfig, state: &mut S) -> Self {
         let node_config = config.neat_node();
 
@@ -106,13 +108,15 @@ where
             })
             .collect();
 
-        Self {
+        let mut genome = Self {
             inputs,
             outputs,
             hidden_nodes: HashMap::new(),
             links: HashMap::new(),
             connections: connection::Connections::<NodeRef, ()>::new(),
-        }
+        };
+        genome.init_topology(config, init_config, state);
+        genome
     }
 
     fn distance(&self, config: &C, other: &Self) -> f64 {
@@ -126,6 +130,10 @@ where
     fn get_stats(&self) -> NeatGenomeStats {
         Self::get_stats(self)
     }
+
+    fn validate(&self) -> Result<(), String> {
+        Self::validate(self)
+    }
 }
 
 impl<N, L> NeatGenome<N, L>
@@ -294,6 +302,78 @@ where
         return genome;
     }
 
+    /// Check that nodes, links and connections are consistent with each other
+    pub fn validate(&self) -> Result<(), String> {
+        // Nodes are stored under their own reference, in the map of their type
+        let node_maps = [
+            (&self.inputs, NodeRef::Input(0)),
+            (&self.hidden_nodes, NodeRef::Hidden(0)),
+            (&self.outputs, NodeRef::Output(0)),
+        ];
+        for (nodes, kind) in node_maps.iter() {
+            for (node_ref, node) in nodes.iter() {
+                if mem::discriminant(node_ref) != mem::discriminant(kind) {
+                    return Err(format!("node {} is stored with wrong node type", node_ref));
+                }
+                if node.neat().node_ref != *node_ref {
+                    return Err(format!(
+                        "node {} is stored under key {}",
+                        node.neat().node_ref,
+                        node_ref
+                    ));
+                }
+            }
+        }
+
+        // Links connect existing nodes and are present in connections
+        for ((from, to), link) in self.links.iter() {
+            if link.neat().from != *from || link.neat().to != *to {
+                return Err(format!(
+                    "link {}->{} is stored under key {}->{}",
+                    link.neat().from,
+                    link.neat().to,
+                    from,
+                    to
+                ));
+            }
+            if let NodeRef::Output(_) = from {
+                return Err(format!("link {}->{} starts in output node", from, to));
+            }
+            if let NodeRef::Input(_) = to {
+                return Err(format!("link {}->{} ends in input node", from, to));
+            }
+            if self.get_node(from).is_none() || self.get_node(to).is_none() {
+                return Err(format!("link {}->{} refers to missing node", from, to));
+            }
+            if !self.connections.contains(from, *to) {
+                return Err(format!("link {}->{} is missing from connections", from, to));
+            }
+        }
+
+        // Connections contain no additional edges
+        for connection in self.connections.get_all_connections().iter() {
+            if !self.links.contains_key(&(connection.from, connection.to)) {
+                return Err(format!(
+                    "connection {}->{} has no link",
+                    connection.from, connection.to
+                ));
+            }
+        }
+
+        // All nodes are reached by topological sorting if there are no cycles
+        let sorted_nodes = self
+            .connections
+            .sort_topologically()
+            .iter()
+            .filter(|action| matches!(action, connection::OrderedAction::Node(_)))
+            .count();
+        if sorted_nodes != self.connections.get_all_nodes().len() {
+            return Err("connections contain a cycle".to_owned());
+        }
+
+        Ok(())
+    }
+
     pub fn get_stats(&self) -> NeatGenomeStats {
         NeatGenomeStats {
             hidden_nodes: self.hidden_nodes.len() as u64,
@@ -391,6 +471,107 @@ where
         self.insert_link(link2);
     }
 
+    /// Add the links and hidden nodes of the configured initial topology. Nodes and links
+    /// are created in a fixed order, so that all genomes share their innovation numbers.
+    fn init_topology<
+        C: ConfigProvider<N::Config, L::Config>,
+        S: StateProvider<N::State, L::State>,
+    >(
+        &mut self,
+        config: &C,
+        init_config: &InitConfig,
+        state: &mut S,
+    ) {
+        let mut rng = rand::thread_rng();
+        let pairs = (0..init_config.inputs)
+            .flat_map(|i| {
+                (0..init_config.outputs).map(move |o| (NodeRef::Input(i), NodeRef::Output(o)))
+            })
+            .collect::<Vec<(NodeRef, NodeRef)>>();
+
+        // Register every input-output link in order before sampling, so that genomes built
+        // from fresh states still share the innovation numbers of their initial links
+        if config.neat().initial_topology != InitialTopology::Empty {
+            for (from, to) in pairs.iter() {
+                state.neat_mut().get_connect_innovation(*from, *to);
+            }
+        }
+
+        match config.neat().initial_topology {
+            InitialTopology::Empty => {}
+            InitialTopology::Full => {
+                for (from, to) in pairs {
+                    self.add_initial_link(config, state, from, to);
+                }
+            }
+            InitialTopology::Sparse => {
+                for (from, to) in pairs {
+                    if rng.gen::<f64>() < config.neat().initial_link_density {
+                        self.add_initial_link(config, state, from, to);
+                    }
+                }
+            }
+            InitialTopology::HiddenLayer => {
+                // Hidden node k gets the id of a split of the k-th input-output link
+                assert!(
+                    config.neat().initial_hidden_nodes as usize <= pairs.len(),
+                    "INITIAL_HIDDEN_NODES of {} exceeds the {} input-output links",
+                    config.neat().initial_hidden_nodes,
+                    pairs.len()
+                );
+                let hidden_nodes = pairs
+                    .iter()
+                    .take(config.neat().initial_hidden_nodes as usize)
+                    .map(|(from, to)| {
+                        let innovation = state.neat_mut().get_connect_innovation(*from, *to);
+                        NodeRef::Hidden(
+                            state
+                                .neat_mut()
+                                .get_split_innovation(innovation)
+                                .node_number,
+                        )
+                    })
+                    .collect::<Vec<NodeRef>>();
+
+                for hidden in hidden_nodes {
+                    self.hidden_nodes.insert(
+                        hidden,
+                        N::new(config.neat_node(), NeatNode::new(hidden), state.node_mut()),
+                    );
+                    for i in 0..init_config.inputs {
+                        self.add_initial_link(config, state, NodeRef::Input(i), hidden);
+                    }
+                    for o in 0..init_config.outputs {
+                        self.add_initial_link(config, state, hidden, NodeRef::Output(o));
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_initial_link<
+        C: ConfigProvider<N::Config, L::Config>,
+        S: StateProvider<N::State, L::State>,
+    >(
+        &mut self,
+        config: &C,
+        state: &mut S,
+        from: NodeRef,
+        to: NodeRef,
+    ) {
+        let innovation = state.neat_mut().get_connect_innovation(from, to);
+        let weight = perturbation::clamp(
+            (rand::thread_rng().gen::<f64>() - 0.5) * 2.0 * config.neat().initial_link_weight_size,
+            config.neat().max_link_weight,
+        );
+
+        self.insert_link(L::new(
+            config.neat_link(),
+            NeatLink::new(from, to, weight, innovation),
+            state.link_mut(),
+        ));
+    }
+
     pub fn insert_link(&mut self, link: L) {
         if !self
             .connections
@@ -409,18 +590,29 @@ where
         let neat_config = config.neat();
         let mut rng = rand::thread_rng();
 
+        let perturb = |link: &mut L, rng: &mut ThreadRng| {
+            let weight = link.neat().weight
+                + neat_config
+                    .weight_perturbation
+                    .sample(rng, neat_config.mutate_link_weight_size);
+            link.neat_mut().weight = perturbation::clamp(weight, neat_config.max_link_weight);
+        };
+
         if neat_config.mutate_only_one_link {
             if !self.links.is_empty() {
                 let link_index = rng.gen_range(0, self.links.len());
-                if let Some(link) = self.links.values_mut().skip(link_index).next() {
-                    link.neat_mut().weight +=
-                        (rng.gen::<f64>() - 0.5) * 2.0 * neat_config.mutate_link_weight_size;
+                if let Some(link) = self.links.values_mut().nth(link_index) {
+                    perturb(link, &mut rng);
                 }
             }
         } else {
+            // Mutate each link with a given probability, all links if the probability is 1
             for link in self.links.values_mut() {
-                link.neat_mut().weight +=
-                    (rng.gen::<f64>() - 0.5) * 2.0 * neat_config.mutate_link_weight_size;
+                if neat_config.mutate_each_link_probability >= 1.0
+                    || rng.gen::<f64>() < neat_config.mutate_each_link_probability
+                {
+                    perturb(link, &mut rng);
+                }
             }
         }
     }
@@ -530,7 +722,10 @@ where
                     NeatLink::new(
                         source,
                         target,
-                        (rng.gen::<f64>() - 0.5) * 2.0 * config.neat().initial_link_weight_size,
+                        perturbation::clamp(
+                            (rng.gen::<f64>() - 0.5) * 2.0 * config.neat().initial_link_weight_size,
+                            config.neat().max_link_weight,
+                        ),
                         innovation,
                     ),
                     state.link_mut(),
@@ -570,3 +765,102 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::check_random_evolution;
+
+    fn fuzz_config() -> NeatConfig {
+        NeatConfig {
+            add_node_probability: 0.3,
+            add_link_probability: 0.6,
+            remove_node_probability: 0.1,
+            remove_link_probability: 0.3,
+            ..NeatConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let config = NeatConfig::default();
+        let mut state = NeatState::default();
+        let mut genome = DefaultNeatGenome::new(&config, &InitConfig::new(2, 1), &mut state);
+        let link = NeatLink::new(NodeRef::Input(0), NodeRef::Output(0), 1.0, 0);
+        genome.insert_link(link.clone());
+        assert_eq!(genome.validate(), Ok(()));
+
+        let mut missing_connection = genome.clone();
+        missing_connection.connections.remove(&link.from, link.to);
+        assert!(missing_connection.validate().is_err());
+
+        let mut missing_link = genome.clone();
+        missing_link.links.remove(&(link.from, link.to));
+        assert!(missing_link.validate().is_err());
+
+        let mut missing_node = genome.clone();
+        missing_node.outputs.remove(&link.to);
+        assert!(missing_node.validate().is_err());
+
+        let mut wrong_key = genome;
+        let node = wrong_key.inputs.remove(&NodeRef::Input(1)).unwrap();
+        wrong_key.hidden_nodes.insert(NodeRef::Input(1), node);
+        assert!(wrong_key.validate().is_err());
+    }
+
+    #[test]
+    fn test_initial_topology() {
+        let mut state = NeatState::default();
+        let init_config = InitConfig::new(3, 2);
+        let topology_config = |initial_topology, initial_link_density| NeatConfig {
+            initial_topology,
+            initial_link_density,
+            initial_hidden_nodes: 2,
+            ..NeatConfig::default()
+        };
+
+        let full = topology_config(InitialTopology::Full, 0.0);
+        let genome = DefaultNeatGenome::new(&full, &init_config, &mut state);
+        assert_eq!(genome.validate(), Ok(()));
+        assert_eq!(genome.links.len(), 6);
+
+        let empty = topology_config(InitialTopology::Sparse, 0.0);
+        let genome = DefaultNeatGenome::new(&empty, &init_config, &mut state);
+        assert_eq!(genome.links.len(), 0);
+
+        let dense = topology_config(InitialTopology::Sparse, 1.0);
+        let genome = DefaultNeatGenome::new(&dense, &init_config, &mut state);
+        assert_eq!(genome.links.len(), 6);
+
+        // Sampled links share innovation numbers even when built from fresh states
+        let sparse = topology_config(InitialTopology::Sparse, 0.5);
+        let genome = DefaultNeatGenome::new(&dense, &init_config, &mut NeatState::default());
+        for _ in 0..10 {
+            let other = DefaultNeatGenome::new(&sparse, &init_config, &mut NeatState::default());
+            for (link_ref, link) in other.links.iter() {
+                assert_eq!(link.innovation, genome.links[link_ref].innovation);
+            }
+        }
+
+        let hidden = topology_config(InitialTopology::HiddenLayer, 0.0);
+        let genome = DefaultNeatGenome::new(&hidden, &init_config, &mut state);
+        let other = DefaultNeatGenome::new(&hidden, &init_config, &mut state);
+        assert_eq!(genome.validate(), Ok(()));
+        assert_eq!(genome.hidden_nodes.len(), 2);
+        assert_eq!(genome.links.len(), 10);
+        for (link_ref, link) in genome.links.iter() {
+            assert_eq!(link.innovation, other.links[link_ref].innovation);
+        }
+    }
+
+    #[test]
+    fn test_random_mutations() {
+        check_random_evolution::<DefaultNeatGenome>(
+            &fuzz_config(),
+            &InitConfig::new(3, 2),
+            10,
+            2000,
+            |_, _| {},
+        );
+    }
+}
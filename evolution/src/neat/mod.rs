@@ -1,25 +1,29 @@
 use crate::algorithm::Algorithm;
+use crate::develop::FloatDeveloper;
 use crate::environment::EnvironmentDescription;
 use crate::log::Logger;
 use crate::neat::{conf::MethodConfig, genome::DefaultNeatGenome};
 use crate::{environment::Environment, evolve};
 use developer::Developer;
-use network::execute::Executor;
+use network::{execute::Executor, float::Float};
 use serde::Serialize;
+use std::sync::Arc;
 
 pub mod conf;
 pub mod developer;
 pub mod genome;
 pub mod link;
 pub mod node;
+pub mod perturbation;
 pub mod state;
+pub mod topology;
 
 pub struct Neat;
 
-impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Neat {
+impl<F: Float, E: Environment<Phenotype = Executor<F>>> Algorithm<E> for Neat {
     type Config = MethodConfig;
     type Genome = DefaultNeatGenome;
-    type Developer = Developer;
+    type Developer = FloatDeveloper<Developer, F>;
 
     fn genome_config(_: &EnvironmentDescription) -> conf::NeatConfig {
         conf::NeatConfig::default()
@@ -30,6 +34,8 @@ impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Neat {
     }
 }
 
-pub fn neat<E: Environment<Phenotype = Executor> + Default + 'static, N: Serialize + Default>() {
-    evolve::<E, Neat, Logger, N>();
+pub fn neat<F: Float, E: Environment<Phenotype = Executor<F>> + 'static, N: Serialize + Default>(
+    environment: Arc<E>,
+) -> Option<Executor<F>> {
+    evolve::<E, Neat, Logger, N>(environment)
 }
@@ -87,6 +87,25 @@ impl fmt::Display for NodeRef {
     }
 }
 
+impl std::str::FromStr for NodeRef {
+    type Err = String;
+
+    /// Parse the format written by Display, such as I0, H3 or O1
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("cannot parse node reference {}", s);
+        if s.is_empty() || !s.is_char_boundary(1) {
+            return Err(error());
+        }
+        let id = s[1..].parse::<u64>().map_err(|_| error())?;
+        match &s[..1] {
+            "I" => Ok(NodeRef::Input(id)),
+            "H" => Ok(NodeRef::Hidden(id)),
+            "O" => Ok(NodeRef::Output(id)),
+            _ => Err(error()),
+        }
+    }
+}
+
 impl std::cmp::Ord for NodeRef {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         match (self, other) {
@@ -0,0 +1,86 @@
+use rand::{
+    distributions::{Cauchy, Distribution, Normal},
+    Rng,
+};
+use serde::Serialize;
+use std::{fmt, str};
+
+/// Distribution of the noise added to weights and biases during mutation
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum Perturbation {
+    Uniform,
+    Gaussian,
+    Cauchy,
+}
+
+impl Perturbation {
+    /// Sample noise of the given size. The size is the half-width of the uniform
+    /// distribution, the standard deviation of the gaussian and the scale of the cauchy.
+    pub fn sample<R: Rng>(&self, rng: &mut R, size: f64) -> f64 {
+        match self {
+            Perturbation::Uniform => (rng.gen::<f64>() - 0.5) * 2.0 * size,
+            _ if size <= 0.0 => 0.0,
+            Perturbation::Gaussian => Normal::new(0.0, size).sample(rng),
+            Perturbation::Cauchy => Cauchy::new(0.0, size).sample(rng),
+        }
+    }
+}
+
+/// Clamp value to [-limit, limit]. A limit of zero leaves the value unbounded.
+pub fn clamp(value: f64, limit: f64) -> f64 {
+    if limit > 0.0 {
+        value.min(limit).max(-limit)
+    } else {
+        value
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsePerturbationError;
+
+impl fmt::Display for ParsePerturbationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot parse perturbation distribution")
+    }
+}
+
+impl str::FromStr for Perturbation {
+    type Err = ParsePerturbationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Uniform" => Ok(Perturbation::Uniform),
+            "Gaussian" => Ok(Perturbation::Gaussian),
+            "Cauchy" => Ok(Perturbation::Cauchy),
+            _ => Err(ParsePerturbationError {}),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniform_bounds() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            assert!(Perturbation::Uniform.sample(&mut rng, 0.5).abs() <= 0.5);
+        }
+    }
+
+    #[test]
+    fn test_zero_size() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(Perturbation::Gaussian.sample(&mut rng, 0.0), 0.0);
+        assert_eq!(Perturbation::Cauchy.sample(&mut rng, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(clamp(5.0, 2.0), 2.0);
+        assert_eq!(clamp(-5.0, 2.0), -2.0);
+        assert_eq!(clamp(1.5, 2.0), 1.5);
+        assert_eq!(clamp(-50.0, 0.0), -50.0);
+    }
+}
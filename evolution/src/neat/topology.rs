@@ -0,0 +1,39 @@
+use serde::Serialize;
+use std::{fmt, str};
+
+/// Links and hidden nodes of newly created genomes
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum InitialTopology {
+    /// No links, all structure comes from mutation
+    Empty,
+    /// Every input linked to every output
+    Full,
+    /// Each input-output link present with probability INITIAL_LINK_DENSITY
+    Sparse,
+    /// A single fully connected layer of INITIAL_HIDDEN_NODES hidden nodes, at most one per
+    /// input-output pair
+    HiddenLayer,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseInitialTopologyError;
+
+impl fmt::Display for ParseInitialTopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot parse initial topology")
+    }
+}
+
+impl str::FromStr for InitialTopology {
+    type Err = ParseInitialTopologyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Empty" => Ok(InitialTopology::Empty),
+            "Full" => Ok(InitialTopology::Full),
+            "Sparse" => Ok(InitialTopology::Sparse),
+            "HiddenLayer" => Ok(InitialTopology::HiddenLayer),
+            _ => Err(ParseInitialTopologyError {}),
+        }
+    }
+}
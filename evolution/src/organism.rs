@@ -19,15 +19,27 @@ impl<G: Genome> Organism<G> {
         }
     }
 
+    pub fn from_genome(genome: G) -> Self {
+        Self {
+            genome,
+            fitness: None,
+            adjusted_fitness: None,
+            generation: 0,
+        }
+    }
+
     /// Breed organism with other organism
     pub fn crossover(&self, config: &G::Config, other: &Self) -> Self {
+        let genome = self.genome.crossover(
+            config,
+            &other.genome,
+            &self.fitness.unwrap(),
+            &other.fitness.unwrap(),
+        );
+        debug_assert_eq!(genome.validate(), Ok(()), "invalid genome after crossover");
+
         Organism {
-            genome: self.genome.crossover(
-                config,
-                &other.genome,
-                &self.fitness.unwrap(),
-                &other.fitness.unwrap(),
-            ),
+            genome,
             fitness: None,
             adjusted_fitness: None,
             generation: self.generation + 1,
@@ -42,6 +54,11 @@ impl<G: Genome> Organism<G> {
     /// Mutate organism
     pub fn mutate(&mut self, config: &G::Config, state: &mut G::State) {
         self.genome.mutate(config, state);
+        debug_assert_eq!(
+            self.genome.validate(),
+            Ok(()),
+            "invalid genome after mutation"
+        );
     }
 
     /// Genetic distance to other organism
@@ -44,6 +44,39 @@ impl<G: Genome> Population<G> {
         return population;
     }
 
+    /// Create population of a seed genome and mutated copies of it
+    pub fn from_seed(
+        population_config: PopulationConfig,
+        genome_config: G::Config,
+        seed: G,
+        state: G::State,
+        mutations: u64,
+    ) -> Self {
+        let genome_config_clone = genome_config.clone();
+        let mut population = Population {
+            population_config,
+            genome_config,
+            species: HashMap::new(),
+            extinct_species: HashMap::new(),
+            next_id: 0,
+            state: G::State::default(), // temp value, replaced after inserting all organisms
+        };
+
+        let mut state = state;
+        // The seed itself is kept unmodified
+        population.push(Organism::<G>::from_genome(seed.clone()), false);
+        for _ in 1..population.population_config.population_size {
+            let mut organism = Organism::<G>::from_genome(seed.clone());
+            for _ in 0..mutations {
+                organism.mutate(&genome_config_clone, &mut state);
+            }
+            population.push(organism, false);
+        }
+        population.state = state;
+
+        population
+    }
+
     /// Add organism to population
     pub fn push(&mut self, organism: Organism<G>, lock_new: bool) {
         if let Some(species) = self.compatible_species(&organism) {
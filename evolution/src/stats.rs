@@ -18,6 +18,15 @@ pub struct OrganismStats<G: Serialize, P: Serialize, E: Serialize> {
     pub evaluation: E,
 }
 
+impl<G: Serialize, P: Serialize, E: Serialize> PopulationStats<G, P, E> {
+    /// Best organism, whose stats may be completed before they are logged
+    pub fn best_mut(&mut self) -> Option<&mut OrganismStats<G, P, E>> {
+        self.organisms
+            .iter_mut()
+            .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+    }
+}
+
 pub trait GetPopulationStats {
     type G: Serialize;
     type P: Serialize;
@@ -0,0 +1,104 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use network::{
+    activation::Activation,
+    connection::{Connections, OrderedAction},
+    execute::{Action, Executor},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Layered network of the size assembled by DES-HyperNEAT for image datasets
+fn layered_executor(layers: usize, width: usize, fan_in: usize) -> Executor {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut connections = Connections::<usize, f64>::new();
+    for layer in 1..layers {
+        for node in 0..width {
+            let to = layer * width + node;
+            for _ in 0..fan_in {
+                let from = (layer - 1) * width + rng.gen_range(0, width);
+                if !connections.contains(&from, to) {
+                    connections.add(from, to, rng.gen::<f64>() - 0.5);
+                }
+            }
+        }
+    }
+
+    let actions = connections
+        .sort_topologically_stable()
+        .iter()
+        .filter_map(|action| match action {
+            OrderedAction::Edge(from, to, weight) => Some(Action::Link(*from, *to, *weight)),
+            OrderedAction::Node(node) if *node >= width => {
+                Some(Action::Activation(*node, 0.1, Activation::Sigmoid))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let length = layers * width;
+    Executor::create(
+        length,
+        (0..width).collect(),
+        (length - width..length).collect(),
+        actions,
+    )
+}
+
+fn bench_execute(c: &mut Criterion) {
+    let mut group = c.benchmark_group("execute");
+    for (layers, width, fan_in) in [(4, 32, 8), (6, 256, 32)].iter() {
+        let mut executor = layered_executor(*layers, *width, *fan_in);
+        let mut compiled = executor.compile();
+        let mut layered = executor.layered();
+        let mut single = executor.cast::<f32>();
+        let mut compiled_single = single.compile();
+        let inputs = (0..*width)
+            .map(|i| i as f64 / *width as f64)
+            .collect::<Vec<f64>>();
+        let size = format!("{} actions", executor.actions.len());
+
+        group.bench_with_input(BenchmarkId::new("actions", &size), &inputs, |b, inputs| {
+            b.iter(|| executor.execute(inputs))
+        });
+        group.bench_with_input(BenchmarkId::new("compiled", &size), &inputs, |b, inputs| {
+            b.iter(|| compiled.execute(inputs))
+        });
+        group.bench_with_input(BenchmarkId::new("layered", &size), &inputs, |b, inputs| {
+            b.iter(|| layered.execute(inputs))
+        });
+        group.bench_with_input(BenchmarkId::new("f32", &size), &inputs, |b, inputs| {
+            b.iter(|| single.execute(inputs))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("compiled_f32", &size),
+            &inputs,
+            |b, inputs| b.iter(|| compiled_single.execute(inputs)),
+        );
+    }
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+    let mut executor = layered_executor(6, 256, 32);
+    let inputs = (0..128)
+        .map(|s| (0..256).map(|i| ((s + i) % 256) as f64 / 256.0).collect())
+        .collect::<Vec<Vec<f64>>>();
+    let mut outputs = vec![0.0; inputs.len() * executor.outputs.len()];
+
+    group.bench_function("single", |b| {
+        b.iter(|| {
+            inputs
+                .iter()
+                .map(|input| executor.execute(input))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("batch", |b| b.iter(|| executor.execute_batch(&inputs)));
+    group.bench_function("batch_into", |b| {
+        b.iter(|| executor.execute_batch_into(&inputs, &mut outputs))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_execute, bench_batch);
+criterion_main!(benches);
@@ -1,11 +1,14 @@
+use crate::float::Float;
 use rand::Rng;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{self, Display},
-    str,
+    mem, str,
 };
 
-#[derive(Copy, Clone, Debug, Display, PartialEq, Serialize)]
+/// Node activation function. Variants with a parameter have it evolved per node, see
+/// Activation::parameter.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Activation {
     None,
     Linear,
@@ -21,30 +24,205 @@ pub enum Activation {
     Square,
     Abs,
     Exp,
+    Sawtooth,
+    Triangle,
+    Inverse,
+    Log,
+    Softplus,
+    ELU,
+    SELU,
+    Hat,
+    BipolarSigmoid,
+    ScaledGaussian(f64),
+    ScaledSine(f64),
+    ScaledSigmoid(f64),
+    LeakyReLU(f64),
+    BoundedExp(f64),
 }
 
+/// Activation applied to all output nodes together, after the node activations
+#[derive(Copy, Clone, Debug, Display, PartialEq, Serialize, Deserialize)]
+pub enum GroupActivation {
+    None,
+    Softmax,
+    LogSoftmax,
+}
+
+/// Activation functions to sample from, each with a relative weight
 #[derive(Clone, new, Serialize)]
 pub struct Activations {
     activations: Vec<Activation>,
+    weights: Vec<f64>,
 }
 
+// Constants of the scaled exponential linear unit
+pub(crate) const SELU_ALPHA: f64 = 1.673_263_242_354_377_3;
+pub(crate) const SELU_SCALE: f64 = 1.050_700_987_355_480_5;
+
 impl Activation {
-    pub fn activate(&self, x: f64) -> f64 {
+    pub fn activate<F: Float>(&self, x: F) -> F {
+        let c = F::from_f64;
         match self {
             Activation::None => x,
-            Activation::Linear => x.min(1.0).max(-1.0),
-            Activation::Step => ((x > 0.0) as u8) as f64,
-            Activation::ReLU => x.max(0.0),
-            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Linear => x.min(c(1.0)).max(c(-1.0)),
+            Activation::Step => {
+                if x > c(0.0) {
+                    c(1.0)
+                } else {
+                    c(0.0)
+                }
+            }
+            Activation::ReLU => x.max(c(0.0)),
+            Activation::Sigmoid => c(1.0) / (c(1.0) + (-x).exp()),
             Activation::Tanh => x.tanh(),
             Activation::Softmax => x.exp(),
-            Activation::Gaussian => (-(2.5 * x).powi(2)).exp(),
-            Activation::OffsetGaussian => 2.0 * (-(2.5 * x).powi(2)).exp() - 1.0,
-            Activation::Sine => (2.0 * x).sin(),
-            Activation::Cos => (2.0 * x).cos(),
+            Activation::Gaussian => (-(c(2.5) * x).powi(2)).exp(),
+            Activation::OffsetGaussian => c(2.0) * (-(c(2.5) * x).powi(2)).exp() - c(1.0),
+            Activation::Sine => (c(2.0) * x).sin(),
+            Activation::Cos => (c(2.0) * x).cos(),
             Activation::Square => x * x,
             Activation::Abs => x.abs(),
-            Activation::Exp => x.min(1.0).exp(),
+            Activation::Exp => x.min(c(1.0)).exp(),
+            Activation::Sawtooth => x - x.floor(),
+            Activation::Triangle => {
+                c(1.0) - c(2.0) * (x - c(2.0) * ((x + c(1.0)) * c(0.5)).floor()).abs()
+            }
+            Activation::Inverse => {
+                if x == c(0.0) {
+                    c(0.0)
+                } else {
+                    c(1.0) / x
+                }
+            }
+            Activation::Log => x.max(c(1e-7)).ln(),
+            Activation::Softplus => x.max(c(0.0)) + (c(1.0) + (-x.abs()).exp()).ln(),
+            Activation::ELU => {
+                if x > c(0.0) {
+                    x
+                } else {
+                    x.exp() - c(1.0)
+                }
+            }
+            Activation::SELU => {
+                c(SELU_SCALE)
+                    * if x > c(0.0) {
+                        x
+                    } else {
+                        c(SELU_ALPHA) * (x.exp() - c(1.0))
+                    }
+            }
+            Activation::Hat => (c(1.0) - x.abs()).max(c(0.0)),
+            Activation::BipolarSigmoid => c(2.0) * (c(1.0) / (c(1.0) + (-x).exp())) - c(1.0),
+            Activation::ScaledGaussian(p) => (-(c(*p) * x).powi(2)).exp(),
+            Activation::ScaledSine(p) => (c(*p) * x).sin(),
+            Activation::ScaledSigmoid(p) => c(1.0) / (c(1.0) + (-(c(*p) * x)).exp()),
+            Activation::LeakyReLU(p) => {
+                if x > c(0.0) {
+                    x
+                } else {
+                    c(*p) * x
+                }
+            }
+            Activation::BoundedExp(p) => x.min(c(*p)).exp(),
+        }
+    }
+
+    /// All activation functions, parameterized ones with their default parameter
+    pub const ALL: [Activation; 28] = [
+        Activation::None,
+        Activation::Linear,
+        Activation::Step,
+        Activation::ReLU,
+        Activation::Sigmoid,
+        Activation::Tanh,
+        Activation::Softmax,
+        Activation::Gaussian,
+        Activation::OffsetGaussian,
+        Activation::Sine,
+        Activation::Cos,
+        Activation::Square,
+        Activation::Abs,
+        Activation::Exp,
+        Activation::Sawtooth,
+        Activation::Triangle,
+        Activation::Inverse,
+        Activation::Log,
+        Activation::Softplus,
+        Activation::ELU,
+        Activation::SELU,
+        Activation::Hat,
+        Activation::BipolarSigmoid,
+        Activation::ScaledGaussian(2.5),
+        Activation::ScaledSine(2.0),
+        Activation::ScaledSigmoid(1.0),
+        Activation::LeakyReLU(0.01),
+        Activation::BoundedExp(1.0),
+    ];
+
+    /// Evolvable parameter of the function, if any
+    pub fn parameter(&self) -> Option<f64> {
+        match self {
+            Activation::ScaledGaussian(p)
+            | Activation::ScaledSine(p)
+            | Activation::ScaledSigmoid(p)
+            | Activation::LeakyReLU(p)
+            | Activation::BoundedExp(p) => Some(*p),
+            _ => None,
+        }
+    }
+
+    /// Same function with another parameter, unchanged if it has none
+    pub fn with_parameter(self, parameter: f64) -> Self {
+        match self {
+            Activation::ScaledGaussian(_) => Activation::ScaledGaussian(parameter),
+            Activation::ScaledSine(_) => Activation::ScaledSine(parameter),
+            Activation::ScaledSigmoid(_) => Activation::ScaledSigmoid(parameter),
+            Activation::LeakyReLU(_) => Activation::LeakyReLU(parameter),
+            Activation::BoundedExp(_) => Activation::BoundedExp(parameter),
+            activation => activation,
+        }
+    }
+
+    /// Whether both are the same function, regardless of parameter
+    pub fn same_function(&self, other: &Activation) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+/// Written as the name, followed by the parameter in parentheses if any, e.g. ScaledSine(2.0)
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl GroupActivation {
+    /// Activate values in place. The maximum is subtracted before exponentiating, so large
+    /// values do not overflow.
+    pub fn activate(&self, values: &mut [f64]) {
+        if values.is_empty() {
+            return;
+        }
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        match self {
+            GroupActivation::None => {}
+            GroupActivation::Softmax => {
+                let mut sum = 0.0;
+                for value in values.iter_mut() {
+                    *value = (*value - max).exp();
+                    sum += *value;
+                }
+                for value in values.iter_mut() {
+                    *value /= sum;
+                }
+            }
+            GroupActivation::LogSoftmax => {
+                let sum = values.iter().map(|value| (value - max).exp()).sum::<f64>();
+                let log_sum = max + sum.ln();
+                for value in values.iter_mut() {
+                    *value -= log_sum;
+                }
+            }
         }
     }
 }
@@ -55,12 +233,19 @@ impl Activations {
         self.activations.iter()
     }
 
+    /// Sample an activation function with probability proportional to its weight
     pub fn random(&self) -> Activation {
+        let total = self.weights.iter().sum::<f64>();
+        let mut sample = rand::thread_rng().gen::<f64>() * total;
+        for (activation, weight) in self.activations.iter().zip(self.weights.iter()) {
+            if sample < *weight {
+                return *activation;
+            }
+            sample -= weight;
+        }
         *self
             .activations
-            .iter()
-            .skip(rand::thread_rng().gen_range(0, self.activations.len()))
-            .next()
+            .last()
             .expect("list of activation functions cannot be empty")
     }
 }
@@ -74,58 +259,229 @@ impl fmt::Display for ParseActivationError {
     }
 }
 
+/// Parses a name, e.g. Tanh, optionally followed by a parameter, e.g. ScaledSine(3.0).
+/// Parameterized functions without a parameter get their default, see Activation::ALL.
 impl str::FromStr for Activation {
     type Err = ParseActivationError;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, parameter) = match s.find('(') {
+            Some(open) if s.ends_with(')') => {
+                let parameter = s[open + 1..s.len() - 1]
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| ParseActivationError {})?;
+                (&s[..open], Some(parameter))
+            }
+            Some(_) => return Err(ParseActivationError {}),
+            None => (s, None),
+        };
+        // Linear is already clamped to [-1, 1]
+        let name = if name == "ClampedLinear" {
+            "Linear"
+        } else {
+            name
+        };
+
+        let activation = Activation::ALL
+            .iter()
+            .find(|activation| {
+                let debug = format!("{:?}", activation);
+                debug == name || debug.starts_with(&format!("{}(", name))
+            })
+            .cloned()
+            .ok_or(ParseActivationError {})?;
+        match parameter {
+            Some(parameter) if activation.parameter().is_some() => {
+                Ok(activation.with_parameter(parameter))
+            }
+            Some(_) => Err(ParseActivationError {}),
+            None => Ok(activation),
+        }
+    }
+}
+
+impl str::FromStr for GroupActivation {
+    type Err = ParseActivationError;
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "None" => Ok(Activation::None),
-            "Linear" => Ok(Activation::Linear),
-            "Step" => Ok(Activation::Step),
-            "ReLU" => Ok(Activation::ReLU),
-            "Sigmoid" => Ok(Activation::Sigmoid),
-            "Tanh" => Ok(Activation::Tanh),
-            "Softmax" => Ok(Activation::Softmax),
-            "Gaussian" => Ok(Activation::Gaussian),
-            "OffsetGaussian" => Ok(Activation::OffsetGaussian),
-            "Sine" => Ok(Activation::Sine),
-            "Cos" => Ok(Activation::Cos),
-            "Square" => Ok(Activation::Square),
-            "Abs" => Ok(Activation::Abs),
-            "Exp" => Ok(Activation::Exp),
+            "None" => Ok(GroupActivation::None),
+            "Softmax" => Ok(GroupActivation::Softmax),
+            "LogSoftmax" => Ok(GroupActivation::LogSoftmax),
             _ => Err(ParseActivationError {}),
         }
     }
 }
 
+/// Parses a whitespace separated list of activation functions, or All for every function.
+/// Each function can be followed by a sampling weight, e.g. "Tanh:2 Sine Gaussian:0.5",
+/// otherwise its weight is 1.
 impl str::FromStr for Activations {
     type Err = ParseActivationError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s == "All" {
-            Ok(Activations::new(vec![
-                Activation::None,
-                Activation::Linear,
-                Activation::Step,
-                Activation::ReLU,
-                Activation::Sigmoid,
-                Activation::Tanh,
-                Activation::Softmax,
-                Activation::Gaussian,
-                Activation::OffsetGaussian,
-                Activation::Sine,
-                Activation::Cos,
-                Activation::Square,
-                Activation::Abs,
-                Activation::Exp,
-            ]))
-        } else {
-            Ok(Activations::new(
-                s.trim()
-                    .split_whitespace()
-                    .map(|word| Activation::from_str(word).unwrap())
-                    .collect::<Vec<Activation>>(),
-            ))
+            return Ok(Activations::new(
+                Activation::ALL.to_vec(),
+                vec![1.0; Activation::ALL.len()],
+            ));
+        }
+
+        let mut activations = Vec::new();
+        let mut weights = Vec::new();
+        for word in s.trim().split_whitespace() {
+            let (activation, weight) = match word.rfind(':') {
+                Some(colon) => (
+                    &word[..colon],
+                    word[colon + 1..]
+                        .parse::<f64>()
+                        .map_err(|_| ParseActivationError {})?,
+                ),
+                None => (word, 1.0),
+            };
+            if !(weight >= 0.0 && weight.is_finite()) {
+                return Err(ParseActivationError {});
+            }
+            activations.push(Activation::from_str(activation)?);
+            weights.push(weight);
+        }
+        // Random choice needs some weight, otherwise it would always pick the last function
+        if weights.iter().sum::<f64>() <= 0.0 {
+            return Err(ParseActivationError {});
+        }
+        Ok(Activations::new(activations, weights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_softmax() {
+        let mut values = vec![1.0, 2.0, 3.0];
+        GroupActivation::Softmax.activate(&mut values);
+        let sum = 1.0f64.exp() + 2.0f64.exp() + 3.0f64.exp();
+        for (value, x) in values.iter().zip([1.0f64, 2.0, 3.0].iter()) {
+            assert!((value - x.exp() / sum).abs() < 1e-12);
         }
+
+        // Large and negative values neither overflow nor break normalization
+        let mut values = vec![1000.0, 1000.0, -1000.0];
+        GroupActivation::Softmax.activate(&mut values);
+        assert_eq!(values, vec![0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_log_softmax() {
+        let mut softmax = vec![-3.0, 0.5, 800.0, 799.0];
+        let mut log_softmax = softmax.clone();
+        GroupActivation::Softmax.activate(&mut softmax);
+        GroupActivation::LogSoftmax.activate(&mut log_softmax);
+        for (p, log_p) in softmax.iter().zip(log_softmax.iter()) {
+            assert!(log_p.is_finite());
+            assert!((p - log_p.exp()).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_functions() {
+        let inputs = [-3.7, -1.0, -0.2, 0.0, 0.3, 1.0, 2.5];
+        for x in inputs.iter() {
+            // Defaults of the parameterized functions match the fixed ones
+            assert_eq!(
+                Activation::ScaledGaussian(2.5).activate(*x),
+                Activation::Gaussian.activate(*x)
+            );
+            assert_eq!(
+                Activation::ScaledSine(2.0).activate(*x),
+                Activation::Sine.activate(*x)
+            );
+            assert_eq!(
+                Activation::ScaledSigmoid(1.0).activate(*x),
+                Activation::Sigmoid.activate(*x)
+            );
+            assert_eq!(
+                Activation::BoundedExp(1.0).activate(*x),
+                Activation::Exp.activate(*x)
+            );
+            assert!((Activation::BipolarSigmoid.activate(*x) - (x / 2.0).tanh()).abs() < 1e-12);
+            assert!((Activation::Softplus.activate(*x) - (1.0 + x.exp()).ln()).abs() < 1e-12);
+            for activation in Activation::ALL.iter() {
+                assert!(activation.activate(*x).is_finite(), "{}", activation);
+            }
+        }
+
+        assert_eq!(Activation::Sawtooth.activate(-0.25), 0.75);
+        assert_eq!(Activation::Triangle.activate(0.0), 1.0);
+        assert_eq!(Activation::Triangle.activate(1.0), -1.0);
+        assert_eq!(Activation::Triangle.activate(2.5), 0.0);
+        assert_eq!(Activation::Inverse.activate(0.0), 0.0);
+        assert_eq!(Activation::Inverse.activate(-4.0), -0.25);
+        assert_eq!(Activation::Log.activate(1.0), 0.0);
+        assert_eq!(Activation::Hat.activate(0.5), 0.5);
+        assert_eq!(Activation::Hat.activate(-2.0), 0.0);
+        assert_eq!(Activation::ELU.activate(2.0), 2.0);
+        assert_eq!(Activation::LeakyReLU(0.1).activate(-2.0), -0.2);
+        assert!((Activation::SELU.activate(-100.0) + SELU_ALPHA * SELU_SCALE).abs() < 1e-12);
+        assert!((Activation::Softplus.activate(1000.0) - 1000.0).abs() < 1e-12);
+        assert_eq!(Activation::Softplus.activate(-1000.0), 0.0);
+    }
+
+    #[test]
+    fn test_parse() {
+        for activation in Activation::ALL.iter() {
+            assert_eq!(
+                activation.to_string().parse::<Activation>().unwrap(),
+                *activation
+            );
+        }
+        assert_eq!(
+            "ScaledSine(3)".parse::<Activation>().unwrap(),
+            Activation::ScaledSine(3.0)
+        );
+        assert_eq!(
+            "LeakyReLU".parse::<Activation>().unwrap(),
+            Activation::LeakyReLU(0.01)
+        );
+        assert_eq!(
+            "ClampedLinear".parse::<Activation>().unwrap(),
+            Activation::Linear
+        );
+        assert!("Tanh(2)".parse::<Activation>().is_err());
+        assert!("ScaledSine(".parse::<Activation>().is_err());
+        assert!("Scaled".parse::<Activation>().is_err());
+
+        let activations = "Tanh:2 ScaledGaussian(1.5):0.5 Sine"
+            .parse::<Activations>()
+            .unwrap();
+        assert_eq!(
+            activations.activations,
+            vec![
+                Activation::Tanh,
+                Activation::ScaledGaussian(1.5),
+                Activation::Sine
+            ]
+        );
+        assert_eq!(activations.weights, vec![2.0, 0.5, 1.0]);
+        assert!("Tanh:-1".parse::<Activations>().is_err());
+        assert!("Tanh Unknown".parse::<Activations>().is_err());
+        assert!("Tanh:0 Sine:0".parse::<Activations>().is_err());
+        assert_eq!(
+            "All".parse::<Activations>().unwrap().activations,
+            Activation::ALL.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_weighted_sampling() {
+        let activations = "Tanh:3 Sine ReLU:0".parse::<Activations>().unwrap();
+        let tanh = (0..4000)
+            .map(|_| activations.random())
+            .filter(|activation| *activation == Activation::Tanh)
+            .count();
+        assert!(tanh > 2700 && tanh < 3300);
+        assert!((0..1000).all(|_| activations.random() != Activation::ReLU));
     }
 }
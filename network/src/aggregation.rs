@@ -0,0 +1,156 @@
+use crate::float::Float;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    str,
+};
+
+/// How a node combines the weighted values of its incoming links, before its bias and
+/// activation are applied
+#[derive(Copy, Clone, Debug, Display, PartialEq, Serialize, Deserialize)]
+pub enum Aggregation {
+    Sum,
+    Product,
+    Max,
+    Min,
+    Mean,
+    Median,
+}
+
+#[derive(Clone, new, Serialize)]
+pub struct Aggregations {
+    aggregations: Vec<Aggregation>,
+}
+
+impl Aggregation {
+    pub const ALL: [Aggregation; 6] = [
+        Aggregation::Sum,
+        Aggregation::Product,
+        Aggregation::Max,
+        Aggregation::Min,
+        Aggregation::Mean,
+        Aggregation::Median,
+    ];
+
+    /// Aggregate values left to right, the median sorts them in place. Zero if empty.
+    pub fn aggregate<F: Float>(&self, values: &mut [F]) -> F {
+        let (first, rest) = match values.split_first() {
+            Some((first, rest)) => (*first, rest),
+            None => return F::zero(),
+        };
+        match self {
+            Aggregation::Sum => rest.iter().fold(first, |a, b| a + *b),
+            Aggregation::Product => rest.iter().fold(first, |a, b| a * *b),
+            Aggregation::Max => rest.iter().fold(first, |a, b| a.max(*b)),
+            Aggregation::Min => rest.iter().fold(first, |a, b| a.min(*b)),
+            Aggregation::Mean => {
+                rest.iter().fold(first, |a, b| a + *b) / F::from_f64(values.len() as f64)
+            }
+            Aggregation::Median => {
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                let middle = values.len() / 2;
+                if values.len() % 2 == 1 {
+                    values[middle]
+                } else {
+                    (values[middle - 1] + values[middle]) * F::from_f64(0.5)
+                }
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Aggregations {
+    pub fn iter(&self) -> impl Iterator<Item = &Aggregation> {
+        self.aggregations.iter()
+    }
+
+    pub fn random(&self) -> Aggregation {
+        self.aggregations[rand::thread_rng().gen_range(0, self.aggregations.len())]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseAggregationError;
+
+impl fmt::Display for ParseAggregationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot parse aggregation function")
+    }
+}
+
+impl str::FromStr for Aggregation {
+    type Err = ParseAggregationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Aggregation::ALL
+            .iter()
+            .find(|aggregation| aggregation.to_string() == s)
+            .cloned()
+            .ok_or(ParseAggregationError {})
+    }
+}
+
+/// Parses a whitespace separated list of aggregation functions, or All for every function
+impl str::FromStr for Aggregations {
+    type Err = ParseAggregationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "All" {
+            return Ok(Aggregations::new(Aggregation::ALL.to_vec()));
+        }
+        let aggregations = s
+            .split_whitespace()
+            .map(|word| word.parse::<Aggregation>())
+            .collect::<Result<Vec<_>, _>>()?;
+        if aggregations.is_empty() {
+            return Err(ParseAggregationError {});
+        }
+        Ok(Aggregations::new(aggregations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate() {
+        let values = [2.0, -1.0, 4.0, 0.5];
+        let aggregate = |aggregation: Aggregation| aggregation.aggregate(&mut values.clone());
+        assert_eq!(aggregate(Aggregation::Sum), 5.5);
+        assert_eq!(aggregate(Aggregation::Product), -4.0);
+        assert_eq!(aggregate(Aggregation::Max), 4.0);
+        assert_eq!(aggregate(Aggregation::Min), -1.0);
+        assert_eq!(aggregate(Aggregation::Mean), 1.375);
+        assert_eq!(aggregate(Aggregation::Median), 1.25);
+        assert_eq!(Aggregation::Median.aggregate(&mut [3.0, -2.0, 1.0]), 1.0);
+
+        for aggregation in Aggregation::ALL.iter() {
+            assert_eq!(aggregation.aggregate::<f64>(&mut []), 0.0);
+            assert_eq!(aggregation.aggregate(&mut [-3.0f32]), -3.0);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        for aggregation in Aggregation::ALL.iter() {
+            assert_eq!(
+                aggregation.to_string().parse::<Aggregation>().unwrap(),
+                *aggregation
+            );
+        }
+        assert!("Average".parse::<Aggregation>().is_err());
+        assert_eq!(
+            "Sum Product".parse::<Aggregations>().unwrap().aggregations,
+            vec![Aggregation::Sum, Aggregation::Product]
+        );
+        assert_eq!(
+            "All".parse::<Aggregations>().unwrap().aggregations,
+            Aggregation::ALL.to_vec()
+        );
+        assert!("".parse::<Aggregations>().is_err());
+    }
+}
@@ -0,0 +1,330 @@
+use crate::connection::{Connections, OrderedAction};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+/// Structural metrics of a graph. Depth is the number of links on the longest path, width
+/// the largest number of nodes at the same depth.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphSummary {
+    pub nodes: usize,
+    pub edges: usize,
+    pub depth: usize,
+    pub width: usize,
+    pub max_fan_in: usize,
+    pub max_fan_out: usize,
+    /// Mean over nodes with at least one incoming connection
+    pub mean_fan_in: f64,
+    /// Mean over nodes with at least one outgoing connection
+    pub mean_fan_out: f64,
+    /// Number of nodes with each fan-in and fan-out, indexed by fan-in and fan-out
+    pub fan_in_distribution: Vec<usize>,
+    pub fan_out_distribution: Vec<usize>,
+    /// Strongly connected components
+    pub components: usize,
+    /// Strongly connected components of more than one node
+    pub recurrent_components: usize,
+}
+
+impl<N: Hash + Eq + Copy, E: Copy> Connections<N, E> {
+    /// Targets of each node with outgoing connections
+    fn adjacency(&self) -> HashMap<N, Vec<N>> {
+        self.get_sources()
+            .map(|source| (*source, self.get_targets(source).cloned().collect()))
+            .collect()
+    }
+
+    /// Number of incoming connections of each node with incoming connections
+    pub fn fan_ins(&self) -> HashMap<N, usize> {
+        let mut fan_ins = HashMap::new();
+        for source in self.get_sources() {
+            for target in self.get_targets(source) {
+                *fan_ins.entry(*target).or_insert(0) += 1;
+            }
+        }
+        fan_ins
+    }
+
+    /// Number of nodes with each fan-in, indexed by fan-in
+    pub fn fan_in_distribution(&self) -> Vec<usize> {
+        let fan_ins = self.fan_ins();
+        distribution(
+            self.get_all_nodes()
+                .iter()
+                .map(|node| *fan_ins.get(node).unwrap_or(&0)),
+        )
+    }
+
+    /// Number of nodes with each fan-out, indexed by fan-out
+    pub fn fan_out_distribution(&self) -> Vec<usize> {
+        distribution(
+            self.get_all_nodes()
+                .iter()
+                .map(|node| self.edge_count(node)),
+        )
+    }
+
+    /// Length of the longest path reaching each node, zero for nodes without incoming
+    /// connections
+    pub fn depths(&self) -> HashMap<N, usize> {
+        let mut depths = HashMap::<N, usize>::new();
+        for action in self.sort_topologically() {
+            if let OrderedAction::Node(node) = action {
+                let depth = *depths.entry(node).or_insert(0);
+                for target in self.get_targets(&node) {
+                    let target_depth = depths.entry(*target).or_insert(0);
+                    *target_depth = (*target_depth).max(depth + 1);
+                }
+            }
+        }
+        depths
+    }
+
+    /// All nodes reachable from a node, including itself
+    pub fn reachable(&self, from: N) -> HashSet<N> {
+        self.shortest_paths(from).keys().cloned().collect()
+    }
+
+    /// Number of links on the shortest path from a node to each node reachable from it
+    pub fn shortest_paths(&self, from: N) -> HashMap<N, usize> {
+        let mut lengths: HashMap<N, usize> = [(from, 0)].iter().cloned().collect();
+        let mut queue: VecDeque<N> = [from].iter().cloned().collect();
+        while let Some(node) = queue.pop_front() {
+            let length = lengths[&node] + 1;
+            for target in self.get_targets(&node) {
+                if !lengths.contains_key(target) {
+                    lengths.insert(*target, length);
+                    queue.push_back(*target);
+                }
+            }
+        }
+        lengths
+    }
+
+    /// Number of links on the longest path from a node to each node reachable from it
+    pub fn longest_paths(&self, from: N) -> HashMap<N, usize> {
+        let mut lengths: HashMap<N, usize> = [(from, 0)].iter().cloned().collect();
+        for action in self.sort_topologically() {
+            if let OrderedAction::Node(node) = action {
+                if let Some(length) = lengths.get(&node).cloned() {
+                    for target in self.get_targets(&node) {
+                        let target_length = lengths.entry(*target).or_insert(0);
+                        *target_length = (*target_length).max(length + 1);
+                    }
+                }
+            }
+        }
+        lengths
+    }
+
+    /// Strongly connected components, in reverse topological order. Every node is its own
+    /// component as long as connections cannot form cycles.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<N>> {
+        strongly_connected_components(&self.adjacency())
+    }
+
+    pub fn summary(&self) -> GraphSummary {
+        let nodes = self.get_all_nodes();
+        let fan_ins = self.fan_ins();
+        let edges = fan_ins.values().sum::<usize>();
+
+        let mut widths = HashMap::<usize, usize>::new();
+        for depth in self.depths().values() {
+            *widths.entry(*depth).or_insert(0) += 1;
+        }
+        let components = self.strongly_connected_components();
+
+        GraphSummary {
+            nodes: nodes.len(),
+            edges,
+            depth: widths.keys().max().cloned().unwrap_or(0),
+            width: widths.values().max().cloned().unwrap_or(0),
+            max_fan_in: fan_ins.values().max().cloned().unwrap_or(0),
+            max_fan_out: self
+                .get_sources()
+                .map(|source| self.edge_count(source))
+                .max()
+                .unwrap_or(0),
+            mean_fan_in: mean(edges, fan_ins.len()),
+            mean_fan_out: mean(edges, self.get_sources().count()),
+            fan_in_distribution: distribution(
+                nodes.iter().map(|node| *fan_ins.get(node).unwrap_or(&0)),
+            ),
+            fan_out_distribution: distribution(nodes.iter().map(|node| self.edge_count(node))),
+            components: components.len(),
+            recurrent_components: components
+                .iter()
+                .filter(|component| component.len() > 1)
+                .count(),
+        }
+    }
+}
+
+fn mean(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+fn distribution(values: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut counts = Vec::new();
+    for value in values {
+        if counts.len() <= value {
+            counts.resize(value + 1, 0);
+        }
+        counts[value] += 1;
+    }
+    counts
+}
+
+/// Tarjan's algorithm, with an explicit stack instead of recursion
+fn strongly_connected_components<N: Hash + Eq + Copy>(
+    adjacency: &HashMap<N, Vec<N>>,
+) -> Vec<Vec<N>> {
+    let nodes = adjacency
+        .iter()
+        .flat_map(|(source, targets)| std::iter::once(source).chain(targets.iter()))
+        .cloned()
+        .collect::<HashSet<N>>();
+    let no_targets = Vec::new();
+
+    let mut index = HashMap::<N, usize>::new();
+    let mut lowlink = HashMap::<N, usize>::new();
+    let mut on_stack = HashSet::<N>::new();
+    let mut stack = Vec::<N>::new();
+    let mut components = Vec::new();
+
+    for root in nodes {
+        if index.contains_key(&root) {
+            continue;
+        }
+
+        // Nodes being visited, with the position of the next target to visit
+        let mut visiting = vec![(root, 0)];
+        while let Some((node, position)) = visiting.pop() {
+            if position == 0 {
+                index.insert(node, index.len());
+                lowlink.insert(node, index[&node]);
+                stack.push(node);
+                on_stack.insert(node);
+            }
+
+            let targets = adjacency.get(&node).unwrap_or(&no_targets);
+            if let Some(target) = targets.get(position) {
+                visiting.push((node, position + 1));
+                if !index.contains_key(target) {
+                    visiting.push((*target, 0));
+                } else if on_stack.contains(target) {
+                    let low = lowlink[&node].min(index[target]);
+                    lowlink.insert(node, low);
+                }
+                continue;
+            }
+
+            // All targets visited, node is the root of a component if nothing above it
+            // reaches an earlier node
+            if lowlink[&node] == index[&node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(&member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+            if let Some((parent, _)) = visiting.last() {
+                let low = lowlink[parent].min(lowlink[&node]);
+                lowlink.insert(*parent, low);
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connections() -> Connections<u8, ()> {
+        let mut connections = Connections::<u8, ()>::new();
+        connections.add(0, 1, ());
+        connections.add(1, 3, ());
+        connections.add(0, 2, ());
+        connections.add(2, 3, ());
+        connections.add(2, 1, ());
+        connections.add(4, 3, ());
+        connections.add(4, 5, ());
+        connections
+    }
+
+    #[test]
+    fn test_metrics() {
+        let connections = connections();
+        assert_eq!(connections.fan_in_distribution(), vec![2, 2, 1, 1]);
+        assert_eq!(connections.fan_out_distribution(), vec![2, 1, 3]);
+        assert_eq!(connections.depths()[&3], 3);
+        assert_eq!(connections.depths()[&5], 1);
+
+        assert_eq!(
+            connections.reachable(2),
+            [1, 2, 3].iter().cloned().collect::<HashSet<u8>>()
+        );
+        assert_eq!(connections.shortest_paths(0)[&3], 2);
+        assert_eq!(connections.longest_paths(0)[&3], 3);
+        assert!(!connections.longest_paths(0).contains_key(&4));
+
+        assert_eq!(
+            connections.summary(),
+            GraphSummary {
+                nodes: 6,
+                edges: 7,
+                depth: 3,
+                width: 2,
+                max_fan_in: 3,
+                max_fan_out: 2,
+                mean_fan_in: 7.0 / 4.0,
+                mean_fan_out: 7.0 / 4.0,
+                fan_in_distribution: vec![2, 2, 1, 1],
+                fan_out_distribution: vec![2, 1, 3],
+                components: 6,
+                recurrent_components: 0,
+            }
+        );
+        assert_eq!(
+            Connections::<u8, ()>::new().summary(),
+            GraphSummary::default()
+        );
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        assert_eq!(connections().strongly_connected_components().len(), 6);
+
+        // Cycles 0-1-2 and 3-4, with 5 reached from both
+        let adjacency = [
+            (0, vec![1]),
+            (1, vec![2, 3]),
+            (2, vec![0, 5]),
+            (3, vec![4]),
+            (4, vec![3, 5]),
+        ]
+        .iter()
+        .cloned()
+        .collect::<HashMap<u8, Vec<u8>>>();
+        let mut components = strongly_connected_components(&adjacency)
+            .into_iter()
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect::<Vec<_>>();
+        components.sort();
+        assert_eq!(components, vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+    }
+}
@@ -0,0 +1,294 @@
+/// Generated from an evolved network
+pub fn network(inputs: &[f64]) -> [f64; 5] {
+    let mut v = [0.0; 42];
+    v[0] = inputs[2];
+    v[1] = inputs[0];
+    v[2] = inputs[1];
+    v[3] += v[0] * 0.0;
+    v[3] += v[1] * 0.542423147947443;
+    v[3] += v[2] * 1.0114318674422176;
+    {
+        let x = v[3] + -0.5;
+        v[3] = x;
+    }
+    v[4] += v[0] * 1.3435480285200714;
+    v[4] += v[1] * 1.49382126680646;
+    v[4] += v[2] * 1.44191280446295;
+    {
+        let x = v[4] + -0.4;
+        v[4] = x.min(1.0).max(-1.0);
+    }
+    v[5] += v[0] * 1.19484820835413;
+    v[5] += v[1] * 0.7860665125309141;
+    v[5] += v[2] * 0.2708944018413494;
+    {
+        let x = v[5] + -0.3;
+        v[5] = if x > 0.0 { 1.0 } else { 0.0 };
+    }
+    v[6] += v[0] * -0.28094199531435476;
+    v[6] += v[1] * -0.7947542113627402;
+    v[6] += v[2] * -1.2010001732637265;
+    {
+        let x = v[6] + -0.19999999999999996;
+        v[6] = x.max(0.0);
+    }
+    v[7] += v[0] * -1.4446963958599746;
+    v[7] += v[1] * -1.492859738653744;
+    v[7] += v[2] * -1.338971519142352;
+    {
+        let x = v[7] + -0.09999999999999998;
+        v[7] = 1.0 / (1.0 + (-x).exp());
+    }
+    v[8] += v[0] * -1.003859785914393;
+    v[8] += v[1] * -0.532880339982064;
+    v[8] += v[2] * 0.010221960112155263;
+    {
+        let x = v[8] + 0.0;
+        v[8] = x.tanh();
+    }
+    v[9] += v[0] * 0.551940765858577;
+    v[9] += v[1] * 1.0189569782172223;
+    v[9] += v[2] * 1.3480621437174403;
+    {
+        let x = v[9] + 0.10000000000000009;
+        v[9] = x.exp();
+    }
+    v[10] += v[0] * 1.4947134221109009;
+    v[10] += v[1] * 1.4390622508392963;
+    v[10] += v[2] * 1.1886407548627926;
+    {
+        let x = v[10] + 0.20000000000000007;
+        v[10] = (-(2.5 * x).powi(2)).exp();
+    }
+    v[11] += v[0] * 0.777342308881465;
+    v[11] += v[1] * 0.2608342280706503;
+    v[11] += v[2] * -0.2909765418808459;
+    {
+        let x = v[11] + 0.30000000000000004;
+        v[11] = 2.0 * (-(2.5 * x).powi(2)).exp() - 1.0;
+    }
+    v[12] += v[0] * -0.8034050019214369;
+    v[12] += v[1] * -1.2070963638952006;
+    v[12] += v[2] * -1.4474128957607808;
+    {
+        let x = v[12] + 0.4;
+        v[12] = (2.0 * x).sin();
+    }
+    v[13] += v[0] * -1.4918288823059838;
+    v[13] += v[1] * -1.3343328281167288;
+    v[13] += v[2] * -0.9962410852801427;
+    {
+        let x = v[13] + 0.5;
+        v[13] = (2.0 * x).cos();
+    }
+    v[14] += v[0] * -0.5233127851290926;
+    v[14] += v[1] * 0.020443445517929183;
+    v[14] += v[2] * 0.561432751718635;
+    {
+        let x = v[14] + 0.6000000000000001;
+        v[14] = x * x;
+    }
+    v[15] += v[0] * 1.0264347687743267;
+    v[15] += v[1] * 1.3525136550995864;
+    v[15] += v[2] * 1.4955361631355002;
+    {
+        let x = v[15] + 0.7000000000000002;
+        v[15] = x.abs();
+    }
+    v[16] += v[0] * 1.4361448673683217;
+    v[16] += v[1] * 1.1823781010629744;
+    v[16] += v[2] * 0.7685820055651726;
+    {
+        let x = v[16] + 0.8;
+        v[16] = x.min(1.0).exp();
+    }
+    v[17] += v[0] * 0.2507619411953054;
+    v[17] += v[1] * -0.3009975755379038;
+    v[17] += v[2] * -0.8120184824655166;
+    {
+        let x = v[17] + 0.9000000000000001;
+        v[17] = x - x.floor();
+    }
+    v[18] += v[0] * -1.2131364971423229;
+    v[18] += v[1] * -1.4500621780114962;
+    v[18] += v[2] * -1.4907287456360023;
+    {
+        let x = v[18] + 1.0;
+        v[18] = 1.0 - 2.0 * (x - 2.0 * ((x + 1.0) * 0.5).floor()).abs();
+    }
+    v[19] += v[0] * -1.32963217086336;
+    v[19] += v[1] * -0.9885761193508473;
+    v[19] += v[2] * -0.5137209277044188;
+    {
+        let x = v[19] + 1.1;
+        v[19] = if x == 0.0 { 0.0 } else { 1.0 / x };
+    }
+    v[20] += v[0] * 0.03066398153298705;
+    v[20] += v[1] * 0.5708986647211385;
+    v[20] += v[2] * 1.0338648918459947;
+    {
+        let x = v[20] + 1.2000000000000002;
+        v[20] = x.max(1e-7).ln();
+    }
+    v[21] += v[0] * 1.356902355938953;
+    v[21] += v[1] * 1.4962894516722813;
+    v[21] += v[2] * 1.4331607895329013;
+    {
+        let x = v[21] + 1.3;
+        v[21] = x.max(0.0) + (1.0 + (-x.abs()).exp()).ln();
+    }
+    v[22] += v[0] * 1.1760605377914348;
+    v[22] += v[1] * 0.7597860094092853;
+    v[22] += v[2] * 0.24067800897089076;
+    {
+        let x = v[22] + 1.4000000000000001;
+        v[22] = if x > 0.0 { x } else { x.exp() - 1.0 };
+    }
+    v[23] += v[0] * -0.3110046309101382;
+    v[23] += v[1] * -0.8205942529861576;
+    v[23] += v[2] * -1.2191202925021631;
+    {
+        let x = v[23] + 1.5;
+        v[23] = 1.0507009873554805 * if x > 0.0 { x } else { 1.6732632423543774 * (x.exp() - 1.0) };
+    }
+    v[24] += v[0] * -1.4526441195798285;
+    v[24] += v[1] * -1.4895593797339917;
+    v[24] += v[2] * -1.3248697656800972;
+    {
+        let x = v[24] + 1.6;
+        v[24] = (1.0 - x.abs()).max(0.0);
+    }
+    v[25] += v[0] * -0.9808652440864392;
+    v[25] += v[1] * -0.5041052131525494;
+    v[25] += v[2] * 0.04088309351707839;
+    {
+        let x = v[25] + 1.7000000000000002;
+        v[25] = 2.0 * (1.0 / (1.0 + (-x).exp())) - 1.0;
+    }
+    v[26] += v[0] * 0.5803380652704313;
+    v[26] += v[1] * 1.041247002378363;
+    v[26] += v[2] * 1.3612280424248915;
+    {
+        let x = v[26] + 1.8000000000000003;
+        v[26] = (-(2.5 * x).powi(2)).exp();
+    }
+    v[27] += v[0] * 1.496973252738631;
+    v[27] += v[1] * 1.4301101559131875;
+    v[27] += v[2] * 1.1696883584349165;
+    {
+        let x = v[27] + 1.9000000000000004;
+        v[27] = (2.0 * x).sin();
+    }
+    v[28] += v[0] * 0.7509547288986226;
+    v[28] += v[1] * 0.23058289969378687;
+    v[28] += v[2] * -0.3209972432713159;
+    {
+        let x = v[28] + 2.0;
+        v[28] = 1.0 / (1.0 + (-(1.0 * x)).exp());
+    }
+    v[29] += v[0] * -0.8291319152257928;
+    v[29] += v[1] * -1.2250474720881055;
+    v[29] += v[2] * -1.455158600560778;
+    {
+        let x = v[29] + 2.1;
+        v[29] = if x > 0.0 { x } else { 0.01 * x };
+    }
+    v[30] += v[0] * -1.488320838905138;
+    v[30] += v[1] * -1.3200458337323697;
+    v[30] += v[2] * -0.9731088175788707;
+    {
+        let x = v[30] + 2.2;
+        v[30] = x.min(1.0).exp();
+    }
+    v[31] += v[3] * 0.8;
+    v[32] += v[4] * 0.6900000000000001;
+    v[31] += v[5] * 0.5800000000000001;
+    v[32] += v[6] * 0.47000000000000003;
+    v[31] += v[7] * 0.36000000000000004;
+    v[32] += v[8] * 0.25;
+    v[31] += v[9] * 0.14;
+    v[32] += v[10] * 0.030000000000000027;
+    v[31] += v[11] * -0.07999999999999996;
+    v[32] += v[12] * -0.18999999999999995;
+    v[31] += v[13] * -0.30000000000000004;
+    v[32] += v[14] * -0.4099999999999999;
+    v[31] += v[15] * -0.52;
+    v[32] += v[16] * -0.6299999999999999;
+    v[31] += v[17] * -0.74;
+    v[32] += v[18] * -0.8499999999999999;
+    v[31] += v[19] * -0.96;
+    v[32] += v[20] * -1.07;
+    v[31] += v[21] * -1.18;
+    v[32] += v[22] * -1.2899999999999998;
+    v[31] += v[23] * -1.4000000000000001;
+    v[32] += v[24] * -1.51;
+    v[31] += v[25] * -1.6199999999999999;
+    v[32] += v[26] * -1.7299999999999998;
+    v[31] += v[27] * -1.84;
+    v[32] += v[28] * -1.95;
+    v[31] += v[29] * -2.0599999999999996;
+    v[32] += v[30] * -2.17;
+    {
+        let x = v[31] + 0.25;
+        v[31] = x.tanh();
+    }
+    {
+        let x = v[32] + -0.25;
+        v[32] = 1.0 / (1.0 + (-x).exp());
+    }
+    v[33] += v[31] * 0.3333333333333333;
+    v[35] += v[5] * f64::INFINITY;
+    v[36] += v[3] * 1.1;
+    v[36] += v[7] * 0.8;
+    {
+        let x = v[36] + 0.05;
+        v[36] = x.tanh();
+    }
+    v[33] += v[36] * 0.5;
+    {
+        v[37] = (v[4] * 1.1) * (v[8] * 0.8) * (v[12] * 0.5000000000000001);
+        let x = v[37] + 0.05;
+        v[37] = x.tanh();
+    }
+    v[33] += v[37] * 0.3;
+    {
+        v[38] = (v[5] * 1.1).max(v[9] * 0.8).max(v[13] * 0.5000000000000001).max(v[17] * 0.20000000000000018);
+        let x = v[38] + 0.05;
+        v[38] = x.tanh();
+    }
+    v[33] += v[38] * 0.09999999999999998;
+    {
+        v[39] = (v[6] * 1.1).min(v[10] * 0.8);
+        let x = v[39] + 0.05;
+        v[39] = x.tanh();
+    }
+    v[33] += v[39] * -0.10000000000000009;
+    {
+        v[40] = (v[7] * 1.1 + v[11] * 0.8 + v[15] * 0.5000000000000001) / 3.0;
+        let x = v[40] + 0.05;
+        v[40] = x.tanh();
+    }
+    v[33] += v[40] * -0.30000000000000004;
+    {
+        v[41] = { let mut m = [v[8] * 1.1, v[12] * 0.8, v[16] * 0.5000000000000001, v[20] * 0.20000000000000018]; m.sort_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal)); (m[1] + m[2]) * 0.5 };
+        let x = v[41] + 0.05;
+        v[41] = x.tanh();
+    }
+    v[33] += v[41] * -0.5;
+    let mut outputs: [f64; 5] = [v[31], v[32], v[33], v[34], v[35]];
+    for output in outputs.iter_mut() {
+        if !output.is_finite() {
+            *output = 0.0;
+        }
+    }
+    let max = outputs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mut sum = 0.0;
+    for output in outputs.iter_mut() {
+        *output = (*output - max).exp();
+        sum += *output;
+    }
+    for output in outputs.iter_mut() {
+        *output /= sum;
+    }
+    outputs
+}
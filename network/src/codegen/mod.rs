@@ -0,0 +1,641 @@
+#[cfg(test)]
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod fixture;
+
+use crate::activation::{Activation, GroupActivation, SELU_ALPHA, SELU_SCALE};
+use crate::aggregation::Aggregation;
+use crate::execute::{Action, Executor};
+use crate::float::Float;
+use std::{fmt::Write, mem};
+
+impl<F: Float> Executor<F> {
+    /// Generate a dependency-free Rust function `fn name(inputs: &[T]) -> [T; outputs]`
+    /// computing the same outputs as execute, with T the float type of the executor
+    pub fn to_rust(&self, name: &str) -> String {
+        rust(self, name)
+    }
+
+    /// Generate a C function `void name(const T *inputs, T *outputs)` depending only on
+    /// math.h, computing the same outputs as execute, with T the float type of the executor
+    pub fn to_c(&self, name: &str) -> String {
+        c(self, name)
+    }
+}
+
+fn is_single<F: Float>() -> bool {
+    mem::size_of::<F>() == mem::size_of::<f32>()
+}
+
+/// Float literal that parses to the same value in Rust and C
+fn literal<F: Float>(value: F, rust: bool) -> String {
+    let single = is_single::<F>();
+    if value.is_finite() {
+        let mut literal = format!("{:?}", value);
+        if !rust && single {
+            literal.push('f');
+        }
+        literal
+    } else {
+        let value = value.to_f64();
+        let constant = if value.is_nan() { "NAN" } else { "INFINITY" };
+        let sign = if value < 0.0 { "-" } else { "" };
+        if rust {
+            format!(
+                "{}{}::{}",
+                sign,
+                if single { "f32" } else { "f64" },
+                constant
+            )
+        } else {
+            format!("{}{}", sign, constant)
+        }
+    }
+}
+
+fn rust<F: Float>(executor: &Executor<F>, name: &str) -> String {
+    let float = if is_single::<F>() { "f32" } else { "f64" };
+    let l = |value: f64| literal(F::from_f64(value), true);
+    let mut code = String::new();
+
+    writeln!(code, "/// Generated from an evolved network").unwrap();
+    writeln!(
+        code,
+        "pub fn {}(inputs: &[{}]) -> [{}; {}] {{",
+        name,
+        float,
+        float,
+        executor.outputs.len()
+    )
+    .unwrap();
+    writeln!(
+        code,
+        "    let mut v = [{}; {}];",
+        l(0.0),
+        executor.values.len()
+    )
+    .unwrap();
+    for (i, index) in executor.inputs.iter().enumerate() {
+        writeln!(code, "    v[{}] = inputs[{}];", i, index).unwrap();
+    }
+
+    let mut terms = Vec::new();
+    for action in executor.actions.iter() {
+        match action {
+            Action::Link(from, to, weight) if executor.aggregation(*to) != Aggregation::Sum => {
+                terms.push(format!("v[{}] * {}", from, literal(*weight, true)))
+            }
+            Action::Link(from, to, weight) => writeln!(
+                code,
+                "    v[{}] += v[{}] * {};",
+                to,
+                from,
+                literal(*weight, true)
+            )
+            .unwrap(),
+            Action::Activation(node, bias, activation) => {
+                writeln!(code, "    {{").unwrap();
+                if !terms.is_empty() {
+                    writeln!(
+                        code,
+                        "        v[{}] = {};",
+                        node,
+                        rust_aggregation(executor.aggregation(*node), &terms, &l)
+                    )
+                    .unwrap();
+                    terms.clear();
+                }
+                writeln!(
+                    code,
+                    "        let x = v[{}] + {};",
+                    node,
+                    literal(*bias, true)
+                )
+                .unwrap();
+                writeln!(
+                    code,
+                    "        v[{}] = {};",
+                    node,
+                    rust_activation(*activation, &l)
+                )
+                .unwrap();
+                writeln!(code, "    }}").unwrap();
+            }
+        }
+    }
+
+    let outputs = executor
+        .outputs
+        .iter()
+        .map(|o| format!("v[{}]", o))
+        .collect::<Vec<String>>();
+    writeln!(
+        code,
+        "    let mut outputs: [{}; {}] = [{}];",
+        float,
+        outputs.len(),
+        outputs.join(", ")
+    )
+    .unwrap();
+    writeln!(code, "    for output in outputs.iter_mut() {{").unwrap();
+    writeln!(code, "        if !output.is_finite() {{").unwrap();
+    writeln!(code, "            *output = {};", l(0.0)).unwrap();
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+
+    if executor.output_activation != GroupActivation::None {
+        writeln!(
+            code,
+            "    let max = outputs.iter().cloned().fold({}::NEG_INFINITY, {}::max);",
+            float, float
+        )
+        .unwrap();
+    }
+    match executor.output_activation {
+        GroupActivation::None => {}
+        GroupActivation::Softmax => {
+            writeln!(code, "    let mut sum = {};", l(0.0)).unwrap();
+            writeln!(code, "    for output in outputs.iter_mut() {{").unwrap();
+            writeln!(code, "        *output = (*output - max).exp();").unwrap();
+            writeln!(code, "        sum += *output;").unwrap();
+            writeln!(code, "    }}").unwrap();
+            writeln!(code, "    for output in outputs.iter_mut() {{").unwrap();
+            writeln!(code, "        *output /= sum;").unwrap();
+            writeln!(code, "    }}").unwrap();
+        }
+        GroupActivation::LogSoftmax => {
+            writeln!(
+                code,
+                "    let sum = outputs.iter().map(|output| (output - max).exp()).sum::<{}>();",
+                float
+            )
+            .unwrap();
+            writeln!(code, "    let log_sum = max + sum.ln();").unwrap();
+            writeln!(code, "    for output in outputs.iter_mut() {{").unwrap();
+            writeln!(code, "        *output -= log_sum;").unwrap();
+            writeln!(code, "    }}").unwrap();
+        }
+    }
+
+    writeln!(code, "    outputs").unwrap();
+    writeln!(code, "}}").unwrap();
+    code
+}
+
+/// Aggregation of the weighted links, in the same order of operations as
+/// Aggregation::aggregate
+fn rust_aggregation(
+    aggregation: Aggregation,
+    terms: &[String],
+    l: &dyn Fn(f64) -> String,
+) -> String {
+    let count = terms.len();
+    if count == 1 {
+        return terms[0].clone();
+    }
+    match aggregation {
+        Aggregation::Sum => terms.join(" + "),
+        Aggregation::Product => format!("({})", terms.join(") * (")),
+        Aggregation::Max => format!("({}).max({})", terms[0], terms[1..].join(").max(")),
+        Aggregation::Min => format!("({}).min({})", terms[0], terms[1..].join(").min(")),
+        Aggregation::Mean => format!("({}) / {}", terms.join(" + "), l(count as f64)),
+        Aggregation::Median => {
+            let middle = if count % 2 == 1 {
+                format!("m[{}]", count / 2)
+            } else {
+                format!("(m[{}] + m[{}]) * {}", count / 2 - 1, count / 2, l(0.5))
+            };
+            format!(
+                "{{ let mut m = [{}]; m.sort_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal)); {} }}",
+                terms.join(", "),
+                middle
+            )
+        }
+    }
+}
+
+/// Activation of x, in the same order of operations as Activation::activate
+fn rust_activation(activation: Activation, l: &dyn Fn(f64) -> String) -> String {
+    match activation {
+        Activation::None => "x".to_string(),
+        Activation::Linear => format!("x.min({}).max({})", l(1.0), l(-1.0)),
+        Activation::Step => format!("if x > {} {{ {} }} else {{ {} }}", l(0.0), l(1.0), l(0.0)),
+        Activation::ReLU => format!("x.max({})", l(0.0)),
+        Activation::Sigmoid => format!("{} / ({} + (-x).exp())", l(1.0), l(1.0)),
+        Activation::Tanh => "x.tanh()".to_string(),
+        Activation::Softmax => "x.exp()".to_string(),
+        Activation::Gaussian => format!("(-({} * x).powi(2)).exp()", l(2.5)),
+        Activation::OffsetGaussian => format!(
+            "{} * (-({} * x).powi(2)).exp() - {}",
+            l(2.0),
+            l(2.5),
+            l(1.0)
+        ),
+        Activation::Sine => format!("({} * x).sin()", l(2.0)),
+        Activation::Cos => format!("({} * x).cos()", l(2.0)),
+        Activation::Square => "x * x".to_string(),
+        Activation::Abs => "x.abs()".to_string(),
+        Activation::Exp => format!("x.min({}).exp()", l(1.0)),
+        Activation::Sawtooth => "x - x.floor()".to_string(),
+        Activation::Triangle => format!(
+            "{} - {} * (x - {} * ((x + {}) * {}).floor()).abs()",
+            l(1.0),
+            l(2.0),
+            l(2.0),
+            l(1.0),
+            l(0.5)
+        ),
+        Activation::Inverse => format!(
+            "if x == {} {{ {} }} else {{ {} / x }}",
+            l(0.0),
+            l(0.0),
+            l(1.0)
+        ),
+        Activation::Log => format!("x.max({}).ln()", l(1e-7)),
+        Activation::Softplus => format!("x.max({}) + ({} + (-x.abs()).exp()).ln()", l(0.0), l(1.0)),
+        Activation::ELU => format!("if x > {} {{ x }} else {{ x.exp() - {} }}", l(0.0), l(1.0)),
+        Activation::SELU => format!(
+            "{} * if x > {} {{ x }} else {{ {} * (x.exp() - {}) }}",
+            l(SELU_SCALE),
+            l(0.0),
+            l(SELU_ALPHA),
+            l(1.0)
+        ),
+        Activation::Hat => format!("({} - x.abs()).max({})", l(1.0), l(0.0)),
+        Activation::BipolarSigmoid => format!(
+            "{} * ({} / ({} + (-x).exp())) - {}",
+            l(2.0),
+            l(1.0),
+            l(1.0),
+            l(1.0)
+        ),
+        Activation::ScaledGaussian(p) => format!("(-({} * x).powi(2)).exp()", l(p)),
+        Activation::ScaledSine(p) => format!("({} * x).sin()", l(p)),
+        Activation::ScaledSigmoid(p) => {
+            format!("{} / ({} + (-({} * x)).exp())", l(1.0), l(1.0), l(p))
+        }
+        Activation::LeakyReLU(p) => format!("if x > {} {{ x }} else {{ {} * x }}", l(0.0), l(p)),
+        Activation::BoundedExp(p) => format!("x.min({}).exp()", l(p)),
+    }
+}
+
+fn c<F: Float>(executor: &Executor<F>, name: &str) -> String {
+    let single = is_single::<F>();
+    let float = if single { "float" } else { "double" };
+    let l = |value: f64| literal(F::from_f64(value), false);
+    let num_outputs = executor.outputs.len();
+    let mut code = String::new();
+
+    writeln!(code, "#include <math.h>").unwrap();
+    writeln!(code).unwrap();
+    if executor.aggregations.contains(&Aggregation::Median) {
+        writeln!(
+            code,
+            "static {} {}_median({} *m, int n) {{",
+            float, name, float
+        )
+        .unwrap();
+        writeln!(code, "    int i, j;").unwrap();
+        writeln!(code, "    for (i = 1; i < n; i++) {{").unwrap();
+        writeln!(code, "        {} x = m[i];", float).unwrap();
+        writeln!(code, "        for (j = i; j > 0 && m[j - 1] > x; j--) {{").unwrap();
+        writeln!(code, "            m[j] = m[j - 1];").unwrap();
+        writeln!(code, "        }}").unwrap();
+        writeln!(code, "        m[j] = x;").unwrap();
+        writeln!(code, "    }}").unwrap();
+        writeln!(
+            code,
+            "    return n % 2 == 1 ? m[n / 2] : (m[n / 2 - 1] + m[n / 2]) * {};",
+            l(0.5)
+        )
+        .unwrap();
+        writeln!(code, "}}").unwrap();
+        writeln!(code).unwrap();
+    }
+    writeln!(
+        code,
+        "/* Generated from an evolved network. Reads {} inputs and writes {} outputs. */",
+        executor.inputs.iter().max().map_or(0, |index| index + 1),
+        num_outputs
+    )
+    .unwrap();
+    writeln!(
+        code,
+        "void {}(const {} *inputs, {} *outputs) {{",
+        name, float, float
+    )
+    .unwrap();
+    writeln!(
+        code,
+        "    {} v[{}] = {{0}};",
+        float,
+        executor.values.len().max(1)
+    )
+    .unwrap();
+    writeln!(code, "    {} x;", float).unwrap();
+    writeln!(code, "    int i;").unwrap();
+    for (i, index) in executor.inputs.iter().enumerate() {
+        writeln!(code, "    v[{}] = inputs[{}];", i, index).unwrap();
+    }
+
+    let mut terms = Vec::new();
+    for action in executor.actions.iter() {
+        match action {
+            Action::Link(from, to, weight) if executor.aggregation(*to) != Aggregation::Sum => {
+                terms.push(format!("v[{}] * {}", from, literal(*weight, false)))
+            }
+            Action::Link(from, to, weight) => writeln!(
+                code,
+                "    v[{}] += v[{}] * {};",
+                to,
+                from,
+                literal(*weight, false)
+            )
+            .unwrap(),
+            Action::Activation(node, bias, activation) => {
+                if terms.len() == 1 {
+                    writeln!(code, "    v[{}] = {};", node, terms[0]).unwrap();
+                } else if executor.aggregation(*node) == Aggregation::Median {
+                    writeln!(code, "    {{").unwrap();
+                    writeln!(
+                        code,
+                        "        {} m[{}] = {{{}}};",
+                        float,
+                        terms.len(),
+                        terms.join(", ")
+                    )
+                    .unwrap();
+                    writeln!(
+                        code,
+                        "        v[{}] = {}_median(m, {});",
+                        node,
+                        name,
+                        terms.len()
+                    )
+                    .unwrap();
+                    writeln!(code, "    }}").unwrap();
+                } else if !terms.is_empty() {
+                    writeln!(
+                        code,
+                        "    v[{}] = {};",
+                        node,
+                        c_aggregation(executor.aggregation(*node), &terms, single, &l)
+                    )
+                    .unwrap();
+                }
+                terms.clear();
+                writeln!(code, "    x = v[{}] + {};", node, literal(*bias, false)).unwrap();
+                writeln!(
+                    code,
+                    "    v[{}] = {};",
+                    node,
+                    c_activation(*activation, single, &l)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    for (i, output) in executor.outputs.iter().enumerate() {
+        writeln!(code, "    outputs[{}] = v[{}];", i, output).unwrap();
+    }
+    writeln!(code, "    for (i = 0; i < {}; i++) {{", num_outputs).unwrap();
+    writeln!(code, "        if (!isfinite(outputs[i])) {{").unwrap();
+    writeln!(code, "            outputs[i] = {};", l(0.0)).unwrap();
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+
+    let f = if single { "f" } else { "" };
+    if executor.output_activation != GroupActivation::None {
+        writeln!(code, "    x = -INFINITY;").unwrap();
+        writeln!(code, "    for (i = 0; i < {}; i++) {{", num_outputs).unwrap();
+        writeln!(code, "        x = fmax{}(x, outputs[i]);", f).unwrap();
+        writeln!(code, "    }}").unwrap();
+    }
+    match executor.output_activation {
+        GroupActivation::None => {}
+        GroupActivation::Softmax => {
+            writeln!(code, "    {{").unwrap();
+            writeln!(code, "        {} sum = {};", float, l(0.0)).unwrap();
+            writeln!(code, "        for (i = 0; i < {}; i++) {{", num_outputs).unwrap();
+            writeln!(code, "            outputs[i] = exp{}(outputs[i] - x);", f).unwrap();
+            writeln!(code, "            sum += outputs[i];").unwrap();
+            writeln!(code, "        }}").unwrap();
+            writeln!(code, "        for (i = 0; i < {}; i++) {{", num_outputs).unwrap();
+            writeln!(code, "            outputs[i] /= sum;").unwrap();
+            writeln!(code, "        }}").unwrap();
+            writeln!(code, "    }}").unwrap();
+        }
+        GroupActivation::LogSoftmax => {
+            writeln!(code, "    {{").unwrap();
+            writeln!(code, "        {} sum = {};", float, l(0.0)).unwrap();
+            writeln!(code, "        for (i = 0; i < {}; i++) {{", num_outputs).unwrap();
+            writeln!(code, "            sum += exp{}(outputs[i] - x);", f).unwrap();
+            writeln!(code, "        }}").unwrap();
+            writeln!(code, "        x += log{}(sum);", f).unwrap();
+            writeln!(code, "        for (i = 0; i < {}; i++) {{", num_outputs).unwrap();
+            writeln!(code, "            outputs[i] -= x;").unwrap();
+            writeln!(code, "        }}").unwrap();
+            writeln!(code, "    }}").unwrap();
+        }
+    }
+    writeln!(code, "}}").unwrap();
+    code
+}
+
+/// Aggregation of two or more weighted links, in the same order of operations as
+/// Aggregation::aggregate, except for the median which needs a helper function
+fn c_aggregation(
+    aggregation: Aggregation,
+    terms: &[String],
+    single: bool,
+    l: &dyn Fn(f64) -> String,
+) -> String {
+    let f = if single { "f" } else { "" };
+    let fold = |function: &str| {
+        terms[1..].iter().fold(terms[0].clone(), |a, b| {
+            format!("{}{}({}, {})", function, f, a, b)
+        })
+    };
+    match aggregation {
+        Aggregation::Sum => terms.join(" + "),
+        Aggregation::Product => format!("({})", terms.join(") * (")),
+        Aggregation::Max => fold("fmax"),
+        Aggregation::Min => fold("fmin"),
+        Aggregation::Mean => format!("({}) / {}", terms.join(" + "), l(terms.len() as f64)),
+        Aggregation::Median => unreachable!("the median is generated separately"),
+    }
+}
+
+/// Activation of x, in the same order of operations as Activation::activate
+fn c_activation(activation: Activation, single: bool, l: &dyn Fn(f64) -> String) -> String {
+    let f = if single { "f" } else { "" };
+    match activation {
+        Activation::None => "x".to_string(),
+        Activation::Linear => format!("fmax{}(fmin{}(x, {}), {})", f, f, l(1.0), l(-1.0)),
+        Activation::Step => format!("x > {} ? {} : {}", l(0.0), l(1.0), l(0.0)),
+        Activation::ReLU => format!("fmax{}(x, {})", f, l(0.0)),
+        Activation::Sigmoid => format!("{} / ({} + exp{}(-x))", l(1.0), l(1.0), f),
+        Activation::Tanh => format!("tanh{}(x)", f),
+        Activation::Softmax => format!("exp{}(x)", f),
+        Activation::Gaussian => format!("exp{}(-(({} * x) * ({} * x)))", f, l(2.5), l(2.5)),
+        Activation::OffsetGaussian => format!(
+            "{} * exp{}(-(({} * x) * ({} * x))) - {}",
+            l(2.0),
+            f,
+            l(2.5),
+            l(2.5),
+            l(1.0)
+        ),
+        Activation::Sine => format!("sin{}({} * x)", f, l(2.0)),
+        Activation::Cos => format!("cos{}({} * x)", f, l(2.0)),
+        Activation::Square => "x * x".to_string(),
+        Activation::Abs => format!("fabs{}(x)", f),
+        Activation::Exp => format!("exp{}(fmin{}(x, {}))", f, f, l(1.0)),
+        Activation::Sawtooth => format!("x - floor{}(x)", f),
+        Activation::Triangle => format!(
+            "{} - {} * fabs{}(x - {} * floor{}((x + {}) * {}))",
+            l(1.0),
+            l(2.0),
+            f,
+            l(2.0),
+            f,
+            l(1.0),
+            l(0.5)
+        ),
+        Activation::Inverse => format!("x == {} ? {} : {} / x", l(0.0), l(0.0), l(1.0)),
+        Activation::Log => format!("log{}(fmax{}(x, {}))", f, f, l(1e-7)),
+        Activation::Softplus => format!(
+            "fmax{}(x, {}) + log{}({} + exp{}(-fabs{}(x)))",
+            f,
+            l(0.0),
+            f,
+            l(1.0),
+            f,
+            f
+        ),
+        Activation::ELU => format!("x > {} ? x : exp{}(x) - {}", l(0.0), f, l(1.0)),
+        Activation::SELU => format!(
+            "{} * (x > {} ? x : {} * (exp{}(x) - {}))",
+            l(SELU_SCALE),
+            l(0.0),
+            l(SELU_ALPHA),
+            f,
+            l(1.0)
+        ),
+        Activation::Hat => format!("fmax{}({} - fabs{}(x), {})", f, l(1.0), f, l(0.0)),
+        Activation::BipolarSigmoid => format!(
+            "{} * ({} / ({} + exp{}(-x))) - {}",
+            l(2.0),
+            l(1.0),
+            l(1.0),
+            f,
+            l(1.0)
+        ),
+        Activation::ScaledGaussian(p) => {
+            format!("exp{}(-(({} * x) * ({} * x)))", f, l(p), l(p))
+        }
+        Activation::ScaledSine(p) => format!("sin{}({} * x)", f, l(p)),
+        Activation::ScaledSigmoid(p) => {
+            format!("{} / ({} + exp{}(-({} * x)))", l(1.0), l(1.0), f, l(p))
+        }
+        Activation::LeakyReLU(p) => format!("x > {} ? x : {} * x", l(0.0), l(p)),
+        Activation::BoundedExp(p) => format!("exp{}(fmin{}(x, {}))", f, f, l(p)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// Network of the fixture, with a hidden node for each activation function and each
+    /// aggregation, an output summed without activation, an output never written and an
+    /// infinite weight
+    fn fixture_executor() -> Executor {
+        let mut actions = Vec::new();
+        let n = Activation::ALL.len();
+        for (i, activation) in Activation::ALL.iter().enumerate() {
+            let node = 3 + i;
+            for input in 0..3 {
+                let weight = ((i * 3 + input) as f64 * 0.37).sin() * 1.5;
+                actions.push(Action::Link(input, node, weight));
+            }
+            actions.push(Action::Activation(node, 0.1 * i as f64 - 0.5, *activation));
+        }
+        let outputs = (3 + n..8 + n).collect::<Vec<usize>>();
+        for i in 0..n {
+            actions.push(Action::Link(3 + i, outputs[i % 2], 0.8 - 0.11 * i as f64));
+        }
+        actions.push(Action::Activation(outputs[0], 0.25, Activation::Tanh));
+        actions.push(Action::Activation(outputs[1], -0.25, Activation::Sigmoid));
+        actions.push(Action::Link(outputs[0], outputs[2], 1.0 / 3.0));
+        actions.push(Action::Link(5, outputs[4], f64::INFINITY));
+
+        let mut aggregations = vec![Aggregation::Sum; 8 + n];
+        for (i, aggregation) in Aggregation::ALL.iter().enumerate() {
+            let node = aggregations.len();
+            for j in 0..2 + i % 3 {
+                actions.push(Action::Link(3 + i + 4 * j, node, 1.1 - 0.3 * j as f64));
+            }
+            actions.push(Action::Activation(node, 0.05, Activation::Tanh));
+            actions.push(Action::Link(node, outputs[2], 0.5 - 0.2 * i as f64));
+            aggregations.push(*aggregation);
+        }
+
+        Executor::create(aggregations.len(), vec![2, 0, 1], outputs, actions)
+            .with_output_activation(GroupActivation::Softmax)
+            .with_aggregations(aggregations)
+    }
+
+    #[test]
+    fn test_fixture() {
+        let mut executor = fixture_executor();
+        assert_eq!(
+            executor.to_rust("network"),
+            include_str!("fixture.rs"),
+            "generated Rust differs from fixture.rs, regenerate it with to_rust(\"network\")"
+        );
+        assert_eq!(
+            executor.to_c("network"),
+            include_str!("fixture.c"),
+            "generated C differs from fixture.c, regenerate it with to_c(\"network\")"
+        );
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let inputs = (0..3)
+                .map(|_| rng.gen::<f64>() * 4.0 - 2.0)
+                .collect::<Vec<f64>>();
+            assert_eq!(
+                fixture::network(&inputs).to_vec(),
+                executor.execute(&inputs)
+            );
+        }
+    }
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(literal(0.1f64, true), "0.1");
+        assert_eq!(literal(0.1f32, false), "0.1f");
+        assert_eq!(literal(1e-7f64, false), "1e-7");
+        assert_eq!(literal(f64::NEG_INFINITY, true), "-f64::INFINITY");
+        assert_eq!(literal(f32::NAN, false), "NAN");
+        assert_eq!("0.1".parse::<f32>().unwrap(), 0.1f32);
+    }
+
+    #[test]
+    fn test_single_precision() {
+        let executor = fixture_executor().cast::<f32>();
+        let rust = executor.to_rust("network");
+        assert!(rust.starts_with(
+            "/// Generated from an evolved network\npub fn network(inputs: &[f32]) -> [f32; 5] {"
+        ));
+        assert!(!rust.contains("f64"));
+
+        let c = executor.to_c("network");
+        assert!(c.contains("void network(const float *inputs, float *outputs)"));
+        assert!(c.contains("tanhf(x)"));
+        assert!(!c.contains("double"));
+    }
+}
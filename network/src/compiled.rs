@@ -0,0 +1,290 @@
+use crate::activation::{Activation, GroupActivation};
+use crate::aggregation::Aggregation;
+use crate::execute::{Action, Executor};
+use crate::float::Float;
+use std::collections::HashMap;
+
+/// Flat form of an Executor, with the links into each node stored contiguously in
+/// struct-of-arrays layout. Each step sums, or otherwise aggregates, the weighted links into a
+/// node, then activates it.
+///
+/// Links are evaluated when their target is activated or read, instead of in action order.
+/// This gives identical results for actions in topologically sorted order, where no node is
+/// activated or linked to after being read.
+#[derive(Clone, Debug)]
+pub struct CompiledExecutor<F: Float = f64> {
+    values: Vec<F>,
+    /// Weighted links of the step being aggregated
+    link_values: Vec<F>,
+    pub(crate) inputs: Vec<usize>,
+    pub(crate) outputs: Vec<usize>,
+    pub(crate) output_activation: GroupActivation,
+
+    // Per step: target node, optional activation and the range of its links
+    pub(crate) step_nodes: Vec<usize>,
+    pub(crate) step_biases: Vec<F>,
+    pub(crate) step_activations: Vec<Option<Activation>>,
+    pub(crate) step_aggregations: Vec<Aggregation>,
+    pub(crate) step_link_ends: Vec<usize>,
+
+    // Per link, grouped by step
+    pub(crate) link_sources: Vec<usize>,
+    pub(crate) link_weights: Vec<F>,
+}
+
+impl<F: Float> From<&Executor<F>> for CompiledExecutor<F> {
+    fn from(executor: &Executor<F>) -> Self {
+        let length = executor.values.len();
+        assert!(
+            executor.inputs.len() <= length && executor.outputs.iter().all(|o| *o < length),
+            "input or output node out of bounds"
+        );
+
+        let mut compiled = Self {
+            values: vec![F::zero(); length],
+            link_values: Vec::new(),
+            inputs: executor.inputs.clone(),
+            outputs: executor.outputs.clone(),
+            output_activation: executor.output_activation,
+            step_nodes: Vec::new(),
+            step_biases: Vec::new(),
+            step_activations: Vec::new(),
+            step_aggregations: Vec::new(),
+            step_link_ends: Vec::new(),
+            link_sources: Vec::new(),
+            link_weights: Vec::new(),
+        };
+
+        // Links waiting for the activation of their target, in action order
+        let mut pending = HashMap::<usize, Vec<(usize, F)>>::new();
+        // Targets in order of their first pending link, for deterministic output
+        let mut pending_order = Vec::<usize>::new();
+
+        for action in executor.actions.iter() {
+            match action {
+                Action::Link(from, to, weight) => {
+                    assert!(*from < length && *to < length, "link out of bounds");
+                    // A node read without being activated gets the sum of its links so far
+                    if let Some(links) = pending.remove(from) {
+                        compiled.push_step(*from, F::zero(), None, Aggregation::Sum, links);
+                    }
+                    let links = pending.entry(*to).or_insert_with(|| {
+                        pending_order.push(*to);
+                        Vec::new()
+                    });
+                    links.push((*from, *weight));
+                }
+                Action::Activation(node, bias, activation) => {
+                    assert!(*node < length, "activation out of bounds");
+                    let links = pending.remove(node).unwrap_or_default();
+                    compiled.push_step(
+                        *node,
+                        *bias,
+                        Some(*activation),
+                        executor.aggregation(*node),
+                        links,
+                    );
+                }
+            }
+        }
+
+        // Links into nodes without activation are summed without activating
+        for node in pending_order {
+            if let Some(links) = pending.remove(&node) {
+                compiled.push_step(node, F::zero(), None, Aggregation::Sum, links);
+            }
+        }
+
+        compiled
+    }
+}
+
+impl<F: Float> CompiledExecutor<F> {
+    fn push_step(
+        &mut self,
+        node: usize,
+        bias: F,
+        activation: Option<Activation>,
+        aggregation: Aggregation,
+        links: Vec<(usize, F)>,
+    ) {
+        for (source, weight) in links {
+            self.link_sources.push(source);
+            self.link_weights.push(weight);
+        }
+        self.step_nodes.push(node);
+        self.step_biases.push(bias);
+        self.step_activations.push(activation);
+        self.step_aggregations.push(aggregation);
+        self.step_link_ends.push(self.link_sources.len());
+    }
+
+    /// Evaluate network, takes input node values, returns output node values
+    pub fn execute(&mut self, inputs: &[f64]) -> Vec<f64> {
+        // Clear network
+        for value in self.values.iter_mut() {
+            *value = F::zero();
+        }
+
+        // Copy inputs into values
+        for (i, index) in self.inputs.iter().enumerate() {
+            self.values[i] = F::from_f64(inputs[*index]);
+        }
+
+        // Do forward pass. All indices are checked when compiling.
+        let mut link = 0;
+        for step in 0..self.step_nodes.len() {
+            unsafe {
+                let node = *self.step_nodes.get_unchecked(step);
+                let end = *self.step_link_ends.get_unchecked(step);
+
+                let aggregation = *self.step_aggregations.get_unchecked(step);
+                let mut sum = *self.values.get_unchecked(node);
+                if aggregation == Aggregation::Sum || link == end {
+                    while link < end {
+                        sum += *self
+                            .values
+                            .get_unchecked(*self.link_sources.get_unchecked(link))
+                            * *self.link_weights.get_unchecked(link);
+                        link += 1;
+                    }
+                } else {
+                    self.link_values.clear();
+                    while link < end {
+                        self.link_values.push(
+                            *self
+                                .values
+                                .get_unchecked(*self.link_sources.get_unchecked(link))
+                                * *self.link_weights.get_unchecked(link),
+                        );
+                        link += 1;
+                    }
+                    sum = aggregation.aggregate(&mut self.link_values);
+                }
+
+                *self.values.get_unchecked_mut(node) =
+                    match self.step_activations.get_unchecked(step) {
+                        Some(activation) => {
+                            activation.activate(sum + *self.step_biases.get_unchecked(step))
+                        }
+                        None => sum,
+                    };
+            }
+        }
+
+        // Collect output
+        let mut outputs = self
+            .outputs
+            .iter()
+            .map(|o| {
+                if self.values[*o].is_finite() {
+                    self.values[*o].to_f64()
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<f64>>();
+        self.output_activation.activate(&mut outputs);
+        outputs
+    }
+}
+
+impl<F: Float> Executor<F> {
+    /// Compile to flat form for faster repeated execution
+    pub fn compile(&self) -> CompiledExecutor<F> {
+        CompiledExecutor::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{Connections, OrderedAction};
+    use rand::Rng;
+
+    /// Random network with topologically sorted actions, like the developers create
+    fn random_executor(nodes: usize, links: usize) -> Executor {
+        let mut rng = rand::thread_rng();
+        let mut connections = Connections::<usize, f64>::new();
+        for _ in 0..links {
+            let from = rng.gen_range(0, nodes - 1);
+            let to = rng.gen_range(from + 1, nodes);
+            if !connections.contains(&from, to) {
+                connections.add(from, to, rng.gen::<f64>() - 0.5);
+            }
+        }
+
+        let activations = [Activation::Sigmoid, Activation::Tanh, Activation::Gaussian];
+        let actions = connections
+            .sort_topologically()
+            .iter()
+            .filter_map(|action| match action {
+                OrderedAction::Edge(from, to, weight) => Some(Action::Link(*from, *to, *weight)),
+                OrderedAction::Node(node) if *node >= 3 => Some(Action::Activation(
+                    *node,
+                    rng.gen::<f64>(),
+                    activations[node % activations.len()],
+                )),
+                _ => None,
+            })
+            .collect();
+
+        Executor::create(nodes, vec![0, 1, 2], (nodes - 3..nodes).collect(), actions)
+    }
+
+    #[test]
+    fn test_identical_results() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let mut executor = random_executor(40, 200);
+            let mut compiled = executor.compile();
+            for _ in 0..5 {
+                let inputs = (0..3).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+                assert_eq!(executor.execute(&inputs), compiled.execute(&inputs));
+            }
+        }
+    }
+
+    #[test]
+    fn test_aggregations() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            // Input nodes may have links, but are never activated
+            let aggregations = (0..40)
+                .map(|node| match node {
+                    0..=2 => Aggregation::Sum,
+                    _ => Aggregation::ALL[rng.gen_range(0, Aggregation::ALL.len())],
+                })
+                .collect();
+            let mut executor = random_executor(40, 200).with_aggregations(aggregations);
+            assert_eq!(executor.validate_aggregations(), Ok(()));
+            let mut compiled = executor.compile();
+
+            let inputs = (0..40)
+                .map(|_| (0..3).map(|_| rng.gen::<f64>()).collect())
+                .collect::<Vec<Vec<f64>>>();
+            let batch = executor.execute_batch(&inputs);
+            for (input, outputs) in inputs.iter().zip(batch.iter()) {
+                assert_eq!(&executor.execute(input), outputs);
+                assert_eq!(&compiled.execute(input), outputs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_links_without_activation() {
+        let executor = Executor::create(
+            4,
+            vec![1, 0],
+            vec![3, 2],
+            vec![
+                Action::Link(0, 2, 0.5),
+                Action::Activation(2, 0.5, Activation::None),
+                Action::Link(2, 3, 2.0),
+                Action::Link(1, 3, -1.0),
+            ],
+        );
+        let mut compiled = executor.compile();
+        assert_eq!(compiled.execute(&[1.0, 2.0]), vec![2.0, 1.5]);
+    }
+}
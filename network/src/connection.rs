@@ -32,10 +32,19 @@ impl<N: fmt::Display, E: fmt::Display> fmt::Display for OrderedAction<N, E> {
     }
 }
 
-/// Fast non-cyclic graph structure
+/// Fast non-cyclic graph structure. A topological order of the connected nodes is maintained
+/// incrementally with the Pearce-Kelly algorithm, so adding a connection only searches the
+/// nodes between its ends in that order.
 #[derive(Clone)]
 pub struct Connections<N: Hash, E> {
     connections: HashMap<N, Vec<Target<N, E>>>,
+    /// Sources of the incoming connections of each node
+    sources: HashMap<N, Vec<N>>,
+    /// Position of each connected node in topological order. Positions are not contiguous,
+    /// new sources are placed before all nodes and new targets after all nodes.
+    order: HashMap<N, i64>,
+    first: i64,
+    last: i64,
 }
 
 #[allow(dead_code)]
@@ -43,6 +52,10 @@ impl<N: Hash + Eq + Copy, E: Copy> Connections<N, E> {
     pub fn new() -> Self {
         Self {
             connections: HashMap::<N, Vec<Target<N, E>>>::new(),
+            sources: HashMap::new(),
+            order: HashMap::new(),
+            first: 0,
+            last: 0,
         }
     }
 
@@ -53,6 +66,8 @@ impl<N: Hash + Eq + Copy, E: Copy> Connections<N, E> {
         );
         assert!(!self.contains(&from, to), "cannot add existing connection");
 
+        self.place(from, to);
+        self.sources.entry(to).or_default().push(from);
         if let Some(vec) = self.connections.get_mut(&from) {
             vec.push(Target::<N, E>::new(to, edge));
         } else {
@@ -96,15 +111,7 @@ impl<N: Hash + Eq + Copy, E: Copy> Connections<N, E> {
     }
 
     pub fn get_all_nodes(&self) -> Vec<N> {
-        self.get_all_connections()
-            .iter()
-            .flat_map(|connection| {
-                std::iter::once(connection.from).chain(std::iter::once(connection.to))
-            })
-            .collect::<HashSet<N>>()
-            .iter()
-            .cloned()
-            .collect()
+        self.order.keys().cloned().collect()
     }
 
     pub fn get_edges<'a>(&'a self, from: &'a N) -> impl Iterator<Item = &'a Target<N, E>> {
@@ -132,110 +139,169 @@ impl<N: Hash + Eq + Copy, E: Copy> Connections<N, E> {
     }
 
     pub fn contains_node(&self, node: &N) -> bool {
-        self.get_all_nodes().contains(node)
+        self.order.contains_key(node)
     }
 
     pub fn remove(&mut self, from: &N, to: N) -> E {
         let error = "cannot remove non-existent connection";
         let vec = self.connections.get_mut(from).expect(error);
-        if vec.len() == 1 {
-            let edge = vec[0].edge;
+        let index = vec.iter().position(|t| t.node == to).expect(error);
+        let edge = vec.swap_remove(index).edge;
+        if vec.is_empty() {
             self.connections.remove(from);
-            edge
-        } else {
-            let index = vec.iter().position(|t| t.node == to).expect(error);
-            vec.swap_remove(index).edge
         }
+
+        let sources = self.sources.get_mut(&to).unwrap();
+        let index = sources.iter().position(|source| source == from).unwrap();
+        sources.swap_remove(index);
+        if sources.is_empty() {
+            self.sources.remove(&to);
+        }
+
+        // Nodes without connections are no longer part of the graph
+        for node in [*from, to].iter() {
+            if !self.connections.contains_key(node) && !self.sources.contains_key(node) {
+                self.order.remove(node);
+            }
+        }
+
+        edge
     }
 
     pub fn remove_node(&mut self, node: N) -> Vec<Connection<N, E>> {
         // Remove outgoing
-        let mut removed_connections = self
-            .connections
-            .remove(&node)
+        let targets = self.get_targets(&node).cloned().collect::<Vec<N>>();
+        let mut removed_connections = targets
             .into_iter()
-            .flatten()
-            .map(|target| Connection::new(node, target.node, target.edge))
+            .map(|target| Connection::new(node, target, self.remove(&node, target)))
             .collect::<Vec<Connection<N, E>>>();
 
         // Remove inbound
-        let mut delete_keys = Vec::<N>::new();
-        for (source, targets) in self.connections.iter_mut() {
-            if let Some(index) = targets.iter().position(|t| t.node == node) {
-                let target = targets.swap_remove(index);
-                removed_connections.push(Connection::new(*source, node, target.edge));
-                if targets.len() == 0 {
-                    delete_keys.push(*source);
-                }
-            }
-        }
-        for source in delete_keys {
-            self.connections.remove(&source);
-        }
+        let sources = self.sources.get(&node).cloned().unwrap_or_default();
+        removed_connections.extend(
+            sources
+                .into_iter()
+                .map(|source| Connection::new(source, node, self.remove(&source, node))),
+        );
 
         removed_connections
     }
 
-    /// DFS search to check for cycles.
+    /// Check if 'from' is reachable from 'to', then addition will cause cycle.
     ///
-    /// If 'from' is reachable from 'to', then addition will cause cycle
+    /// Only nodes before 'from' in the topological order can be on such a path, so the DFS
+    /// is skipped when 'to' is after 'from', and limited to nodes before 'from' otherwise.
     pub fn creates_cycle(&self, from: N, to: N) -> bool {
+        if from == to {
+            return true;
+        }
+        // Nodes without connections can not be part of a path
+        let bound = match (self.order.get(&from), self.order.get(&to)) {
+            (Some(from_position), Some(to_position)) if to_position < from_position => {
+                *from_position
+            }
+            _ => return false,
+        };
+
         let mut visited: HashSet<N> = [to].iter().cloned().collect();
         let mut stack: Vec<N> = vec![to];
-
         while let Some(node) = stack.pop() {
-            if node == from {
-                return true; // Started at to and reached from, addition will cause cycle
-            } else {
-                // Add all connecting nodes to both stack and visited
-                // Avoid extra storage and double filtering by adding to stack and copying from stack into visited
-                let l = stack.len();
-                stack.extend(self.get_targets(&node).filter(|n| !visited.contains(n)));
-                visited.extend(stack.iter().skip(l));
+            for target in self.get_targets(&node) {
+                if *target == from {
+                    return true; // Started at to and reached from, addition will cause cycle
+                }
+                if self.order[target] < bound && visited.insert(*target) {
+                    stack.push(*target);
+                }
             }
         }
 
-        return false; // Enable to reach from when starting at to, addition will not cause cycle
+        false // Enable to reach from when starting at to, addition will not cause cycle
     }
 
-    /// Determine order of nodes and links to actiave in forward pass
-    pub fn sort_topologically(&self) -> Vec<OrderedAction<N, E>> {
-        // Store number of incoming connections for all nodes
-        let mut backward_count: HashMap<N, u64> = HashMap::new();
-        for (_, targets) in self.connections.iter() {
-            for target in targets.iter() {
-                backward_count.insert(
-                    target.node,
-                    *backward_count.get(&target.node).unwrap_or(&0) + 1,
-                );
-            }
+    /// Give new nodes a position, and reorder the nodes so that 'from' precedes 'to'. Assumes
+    /// that the connection does not create a cycle.
+    fn place(&mut self, from: N, to: N) {
+        if !self.order.contains_key(&to) {
+            self.last += 1;
+            self.order.insert(to, self.last);
+        }
+        if !self.order.contains_key(&from) {
+            self.first -= 1;
+            self.order.insert(from, self.first);
         }
 
-        // Start search from all nodes without incoming connections
-        let mut stack: Vec<N> = self
-            .connections
-            .keys()
-            .filter(|node| *backward_count.get(node).unwrap_or(&0) == 0)
-            .cloned()
-            .collect();
+        let lower = self.order[&to];
+        let upper = self.order[&from];
+        if upper < lower {
+            return;
+        }
 
-        let mut actions = Vec::<OrderedAction<N, E>>::new();
+        // Only the nodes reachable from 'to' and the nodes reaching 'from' within the affected
+        // range change position. Those reaching 'from' are moved before those reachable from
+        // 'to', using the positions they occupied and keeping their relative order.
+        let mut forward = self.search(to, true, |position| position < upper);
+        let mut backward = self.search(from, false, |position| position > lower);
+        forward.sort_by_key(|node| self.order[node]);
+        backward.sort_by_key(|node| self.order[node]);
 
-        // Create topological order
-        while let Some(node) = stack.pop() {
-            actions.push(OrderedAction::Node(node));
+        let mut positions = forward
+            .iter()
+            .chain(backward.iter())
+            .map(|node| self.order[node])
+            .collect::<Vec<i64>>();
+        positions.sort();
+        for (node, position) in backward.iter().chain(forward.iter()).zip(positions) {
+            self.order.insert(*node, position);
+        }
+    }
 
-            // Process all outgoing connections from the current node
-            for target in self.get_edges(&node) {
-                actions.push(OrderedAction::Edge(node, target.node, target.edge));
+    /// Nodes reached from start, through outgoing or incoming connections, with positions in
+    /// range
+    fn search(&self, start: N, forward: bool, in_range: impl Fn(i64) -> bool) -> Vec<N> {
+        let mut visited: HashSet<N> = [start].iter().cloned().collect();
+        let mut stack: Vec<N> = vec![start];
+        while let Some(node) = stack.pop() {
+            let mut visit = |neighbour: &N| {
+                if in_range(self.order[neighbour]) && visited.insert(*neighbour) {
+                    stack.push(*neighbour);
+                }
+            };
+            if forward {
+                self.get_targets(&node).for_each(&mut visit);
+            } else {
+                self.sources
+                    .get(&node)
+                    .into_iter()
+                    .flatten()
+                    .for_each(visit);
+            }
+        }
+        visited.into_iter().collect()
+    }
 
-                // Reduce backward count by 1
-                backward_count.insert(target.node, *backward_count.get(&target.node).unwrap() - 1);
+    /// Determine order of nodes and links to actiave in forward pass. The links into each
+    /// node directly precede it, so nodes can aggregate their links by other means than a sum.
+    ///
+    /// Nodes follow the maintained topological order, which depends on the order connections
+    /// were added in. See sort_topologically_stable for an order given by the graph alone.
+    pub fn sort_topologically(&self) -> Vec<OrderedAction<N, E>> {
+        let mut nodes = self.order.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|(_, position)| **position);
 
-                // Add nodes with no incoming connections to the stack
-                if *backward_count.get(&target.node).unwrap() == 0 {
-                    stack.push(target.node);
-                }
+        let mut actions = Vec::<OrderedAction<N, E>>::new();
+        // Incoming connections of each node, in the order their sources are sorted
+        let mut incoming: HashMap<N, Vec<OrderedAction<N, E>>> = HashMap::new();
+        for (node, _) in nodes {
+            if let Some(mut edges) = incoming.remove(node) {
+                actions.append(&mut edges);
+            }
+            actions.push(OrderedAction::Node(*node));
+            for target in self.get_edges(node) {
+                incoming
+                    .entry(target.node)
+                    .or_default()
+                    .push(OrderedAction::Edge(*node, target.node, target.edge));
             }
         }
 
@@ -249,35 +315,22 @@ impl<N: Hash + Eq + Copy, E: Copy> Connections<N, E> {
     }
 
     pub fn prune_dangling_inputs(&mut self, inputs: &Vec<N>, collect: bool) -> Vec<N> {
-        let mut backward_count: HashMap<N, u64> = HashMap::new();
-        for (_, targets) in self.connections.iter() {
-            for target in targets.iter() {
-                backward_count.insert(
-                    target.node,
-                    *backward_count.get(&target.node).unwrap_or(&0) + 1,
-                );
-            }
-        }
-
         let mut pruned = Vec::new();
 
         loop {
             let mut dangling_inputs = self
                 .connections
                 .keys()
-                .filter(|n| !inputs.contains(n) && *backward_count.get(n).unwrap_or(&0) == 0)
+                .filter(|n| !inputs.contains(n) && !self.sources.contains_key(n))
                 .cloned()
                 .collect::<Vec<N>>();
             if dangling_inputs.len() == 0 {
                 break;
             }
             for node in dangling_inputs.iter() {
-                backward_count.remove(node);
-                for target in self.connections.get(node).unwrap().iter() {
-                    backward_count
-                        .insert(target.node, *backward_count.get(&target.node).unwrap() - 1);
+                for target in self.get_targets(node).cloned().collect::<Vec<N>>() {
+                    self.remove(node, target);
                 }
-                self.connections.remove(node);
             }
             if collect {
                 pruned.append(&mut dangling_inputs);
@@ -288,51 +341,103 @@ impl<N: Hash + Eq + Copy, E: Copy> Connections<N, E> {
     }
 
     pub fn prune_dangling_outputs(&mut self, outputs: &Vec<N>, collect: bool) -> Vec<N> {
-        let mut pruned = HashSet::new();
+        let mut pruned = Vec::new();
 
         loop {
-            let mut deleted_node = false;
-            for source in self.get_sources().cloned().collect::<Vec<N>>().iter() {
-                let targets = self.connections.get(source).unwrap();
-                let delete_indexes = (0..targets.len())
-                    .rev()
-                    .filter(|i| {
-                        !outputs.contains(&targets[*i].node)
-                            && !self.connections.contains_key(&targets[*i].node)
-                    })
-                    .collect::<Vec<usize>>();
-                if delete_indexes.len() > 0 {
-                    deleted_node = true;
-                    if delete_indexes.len() == targets.len() {
-                        if collect {
-                            pruned.extend(
-                                self.connections
-                                    .remove(source)
-                                    .unwrap()
-                                    .iter()
-                                    .map(|target| target.node),
-                            );
-                        } else {
-                            self.connections.remove(source);
-                        }
-                    } else {
-                        let targets = self.connections.get_mut(source).unwrap();
-                        for delete_index in delete_indexes.iter() {
-                            if collect {
-                                pruned.insert(targets.swap_remove(*delete_index).node);
-                            } else {
-                                targets.swap_remove(*delete_index);
-                            }
-                        }
-                    }
+            let mut dangling_outputs = self
+                .sources
+                .keys()
+                .filter(|n| !outputs.contains(n) && !self.connections.contains_key(n))
+                .cloned()
+                .collect::<Vec<N>>();
+            if dangling_outputs.is_empty() {
+                break;
+            }
+            for node in dangling_outputs.iter() {
+                for source in self.sources[node].clone() {
+                    self.remove(&source, *node);
                 }
             }
-            if !deleted_node {
-                break;
+            if collect {
+                pruned.append(&mut dangling_outputs);
             }
         }
 
-        pruned.into_iter().collect::<Vec<_>>()
+        pruned
+    }
+}
+
+impl<N: Hash + Eq + Copy + Ord, E: Copy> Connections<N, E> {
+    /// Topological sort like sort_topologically, but identical for identical graphs
+    /// regardless of insertion order, by visiting nodes in order of their keys
+    pub fn sort_topologically_stable(&self) -> Vec<OrderedAction<N, E>> {
+        // Store number of incoming connections for all nodes
+        let mut backward_count: HashMap<N, usize> = self
+            .sources
+            .iter()
+            .map(|(node, sources)| (*node, sources.len()))
+            .collect();
+
+        // Start search from all nodes without incoming connections, the first on top
+        let mut stack: Vec<N> = self
+            .connections
+            .keys()
+            .filter(|node| !backward_count.contains_key(node))
+            .cloned()
+            .collect();
+        stack.sort_by(|a, b| b.cmp(a));
+
+        let mut actions = Vec::<OrderedAction<N, E>>::new();
+        // Incoming connections of each node, in the order their sources are sorted
+        let mut incoming: HashMap<N, Vec<OrderedAction<N, E>>> = HashMap::new();
+
+        // Create topological order
+        while let Some(node) = stack.pop() {
+            if let Some(mut edges) = incoming.remove(&node) {
+                actions.append(&mut edges);
+            }
+            actions.push(OrderedAction::Node(node));
+
+            let mut targets = self.get_edges(&node).collect::<Vec<_>>();
+            targets.sort_by_key(|target| target.node);
+
+            // Process all outgoing connections from the current node
+            let ready = stack.len();
+            for target in targets {
+                incoming
+                    .entry(target.node)
+                    .or_default()
+                    .push(OrderedAction::Edge(node, target.node, target.edge));
+
+                // Reduce backward count by 1, and add nodes with no incoming connections left
+                let count = backward_count.get_mut(&target.node).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    stack.push(target.node);
+                }
+            }
+            // Visit the first ready target first
+            stack[ready..].reverse();
+        }
+
+        actions
+    }
+
+    /// Nodes grouped by the length of the longest path reaching them, each layer sorted.
+    /// Nodes without incoming connections are in the first layer, and nodes in a layer only
+    /// have incoming connections from earlier layers.
+    pub fn layers(&self) -> Vec<Vec<N>> {
+        let mut layers = Vec::<Vec<N>>::new();
+        for (node, depth) in self.depths() {
+            if layers.len() <= depth {
+                layers.resize_with(depth + 1, Vec::new);
+            }
+            layers[depth].push(node);
+        }
+        for layer in layers.iter_mut() {
+            layer.sort();
+        }
+        layers
     }
 }
 
@@ -349,6 +454,7 @@ impl<N: Hash + Eq + Copy, E: Copy> From<Vec<Connection<N, E>>> for Connections<N
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::Rng;
 
     #[test]
     fn test_add() {
@@ -492,6 +598,162 @@ mod tests {
         assert!(pos(0) < pos(2));
         assert!(pos(2) < pos(3));
         assert!(pos(2) < pos(1));
+
+        // Incoming edges directly precede their target
+        assert_eq!(order.len(), 9);
+        for node in 1..4 {
+            let incoming = order
+                .iter()
+                .filter(|action| matches!(action, OrderedAction::Edge(_, to, _) if *to == node))
+                .count();
+            for action in order[pos(node) - incoming..pos(node)].iter() {
+                assert!(matches!(action, OrderedAction::Edge(_, to, _) if *to == node));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_stable() {
+        let links = [
+            (0, 1, 5),
+            (1, 3, 6),
+            (0, 2, 7),
+            (2, 3, 8),
+            (2, 1, 9),
+            (4, 3, 1),
+        ];
+        let mut connections = Connections::<u8, u8>::new();
+        let mut reversed = Connections::<u8, u8>::new();
+        for (from, to, edge) in links.iter() {
+            connections.add(*from, *to, *edge);
+        }
+        for (from, to, edge) in links.iter().rev() {
+            reversed.add(*from, *to, *edge);
+        }
+
+        let order = connections.sort_topologically_stable();
+        assert_eq!(order, reversed.sort_topologically_stable());
+        assert_eq!(
+            order
+                .iter()
+                .map(|action| action.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "0", "0-(7)>2", "2", "0-(5)>1", "2-(9)>1", "1", "4", "2-(8)>3", "1-(6)>3",
+                "4-(1)>3", "3"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layers() {
+        let mut connections = Connections::<u8, ()>::new();
+        connections.add(0, 1, ());
+        connections.add(1, 3, ());
+        connections.add(0, 2, ());
+        connections.add(2, 3, ());
+        connections.add(2, 1, ());
+        connections.add(4, 3, ());
+        connections.add(4, 5, ());
+
+        assert_eq!(
+            connections.layers(),
+            vec![vec![0, 4], vec![2, 5], vec![1], vec![3]]
+        );
+        assert!(Connections::<u8, ()>::new().layers().is_empty());
+    }
+
+    /// Cycle check without the topological order, a DFS from 'to' through all nodes
+    fn creates_cycle_dfs(connections: &Connections<u8, u8>, from: u8, to: u8) -> bool {
+        let mut visited: HashSet<u8> = [to].iter().cloned().collect();
+        let mut stack = vec![to];
+        while let Some(node) = stack.pop() {
+            if node == from {
+                return true;
+            }
+            for target in connections.get_targets(&node) {
+                if visited.insert(*target) {
+                    stack.push(*target);
+                }
+            }
+        }
+        false
+    }
+
+    /// Check the maintained order and sources against the connections
+    fn assert_consistent(connections: &Connections<u8, u8>) {
+        let all_connections = connections.get_all_connections();
+        let nodes = all_connections
+            .iter()
+            .flat_map(|c| vec![c.from, c.to])
+            .collect::<HashSet<u8>>();
+        assert_eq!(
+            connections.order.keys().cloned().collect::<HashSet<u8>>(),
+            nodes
+        );
+        assert_eq!(
+            connections.sources.values().map(Vec::len).sum::<usize>(),
+            all_connections.len()
+        );
+        for c in all_connections.iter() {
+            assert!(connections.order[&c.from] < connections.order[&c.to]);
+            assert!(connections.sources[&c.to].contains(&c.from));
+        }
+
+        let order = connections.sort_topologically();
+        let positions = order
+            .iter()
+            .enumerate()
+            .filter_map(|(i, action)| match action {
+                OrderedAction::Node(node) => Some((*node, i)),
+                _ => None,
+            })
+            .collect::<HashMap<u8, usize>>();
+        assert_eq!(positions.len(), nodes.len());
+        assert_eq!(order.len(), nodes.len() + all_connections.len());
+        for (i, action) in order.iter().enumerate() {
+            if let OrderedAction::Edge(from, to, _) = action {
+                assert!(positions[from] < i && i < positions[to]);
+                assert!(order[i + 1..positions[to]]
+                    .iter()
+                    .all(|a| matches!(a, OrderedAction::Edge(_, t, _) if t == to)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_order() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let mut connections = Connections::<u8, u8>::new();
+            for step in 0..300 {
+                match rng.gen_range(0, 20) {
+                    0..=13 => {
+                        let from = rng.gen_range(0, 30);
+                        let to = rng.gen_range(0, 30);
+                        let cycle = connections.creates_cycle(from, to);
+                        assert_eq!(cycle, creates_cycle_dfs(&connections, from, to));
+                        if !cycle && !connections.contains(&from, to) {
+                            connections.add(from, to, step as u8);
+                        }
+                    }
+                    14..=17 => {
+                        let all_connections = connections.get_all_connections();
+                        if !all_connections.is_empty() {
+                            let c = &all_connections[rng.gen_range(0, all_connections.len())];
+                            assert_eq!(connections.remove(&c.from, c.to), c.edge);
+                        }
+                    }
+                    18 => {
+                        connections.remove_node(rng.gen_range(0, 30));
+                    }
+                    _ => {
+                        connections.prune(&(0..5).collect(), &(25..30).collect(), true);
+                    }
+                }
+                assert_consistent(&connections);
+            }
+        }
     }
 
     #[test]
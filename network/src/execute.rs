@@ -1,33 +1,166 @@
 extern crate libc;
-use crate::activation::Activation;
-use std::mem;
+use crate::activation::{Activation, GroupActivation};
+use crate::aggregation::Aggregation;
+use crate::float::Float;
+use crate::serialize::SerializedExecutor;
+use serde::{Deserialize, Serialize};
+use std::{any::Any, mem};
 
-#[derive(Clone, Debug)]
-pub struct Executor {
-    pub values: Vec<f64>,
+/// Network ready for evaluation, with values and weights of float type F. Inputs and outputs
+/// are always f64, and are converted at the boundary.
+///
+/// Nodes with a non-sum aggregation are set to the aggregation of their weighted links when
+/// activated. Their links must directly precede their activation, as in the order of
+/// Connections::sort_topologically, see validate_aggregations.
+///
+/// Serialized in the versioned format of SerializedExecutor.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(
+    into = "SerializedExecutor<F>",
+    try_from = "SerializedExecutor<F>",
+    bound = ""
+)]
+pub struct Executor<F: Float = f64> {
+    pub values: Vec<F>,
+    /// Index of the sample value read by each input node, where the input nodes are the
+    /// first inputs.len() nodes
     pub inputs: Vec<usize>,
     pub outputs: Vec<usize>,
-    pub actions: Vec<Action>,
+    pub actions: Vec<Action<F>>,
+    /// Applied to all output values together
+    pub output_activation: GroupActivation,
+    /// Aggregation of each node, all nodes sum their links if empty
+    pub aggregations: Vec<Aggregation>,
+    /// Node values of a chunk of samples during batched execution, node-major
+    batch_values: Vec<F>,
+    /// Weighted links into the node being aggregated, link-major during batched execution
+    link_values: Vec<F>,
+    /// Weighted links of a single sample during batched aggregation
+    lane_values: Vec<F>,
 }
 
-#[derive(Clone, Debug)]
-pub enum Action {
-    Link(usize, usize, f64),            // from, to, weight
-    Activation(usize, f64, Activation), // node, bias, activation
+/// Number of samples evaluated together in batched execution
+pub const BATCH_LANES: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Action<F: Float = f64> {
+    Link(usize, usize, F),            // from, to, weight
+    Activation(usize, F, Activation), // node, bias, activation
+}
+
+impl<F: Float> Action<F> {
+    /// Convert weight or bias to another float type
+    pub fn cast<G: Float>(&self) -> Action<G> {
+        match self {
+            Action::Link(from, to, weight) => {
+                Action::Link(*from, *to, G::from_f64(weight.to_f64()))
+            }
+            Action::Activation(node, bias, activation) => {
+                Action::Activation(*node, G::from_f64(bias.to_f64()), *activation)
+            }
+        }
+    }
 }
 
-impl Executor {
+impl<F: Float> Executor<F> {
     pub fn create(
         length: usize,
         inputs: Vec<usize>,
         outputs: Vec<usize>,
-        actions: Vec<Action>,
-    ) -> Executor {
+        actions: Vec<Action<F>>,
+    ) -> Executor<F> {
         Executor {
-            values: vec![0.0; length],
+            values: vec![F::zero(); length],
             inputs,
             outputs,
             actions,
+            output_activation: GroupActivation::None,
+            aggregations: Vec::new(),
+            batch_values: Vec::new(),
+            link_values: Vec::new(),
+            lane_values: Vec::new(),
+        }
+    }
+
+    pub fn with_output_activation(mut self, output_activation: GroupActivation) -> Self {
+        self.output_activation = output_activation;
+        self
+    }
+
+    /// Set the aggregation of each node, all nodes sum their links if empty
+    pub fn with_aggregations(mut self, aggregations: Vec<Aggregation>) -> Self {
+        assert!(
+            aggregations.is_empty() || aggregations.len() == self.values.len(),
+            "aggregations do not match number of nodes"
+        );
+        self.aggregations = aggregations;
+        self
+    }
+
+    pub fn aggregation(&self, node: usize) -> Aggregation {
+        self.aggregations
+            .get(node)
+            .cloned()
+            .unwrap_or(Aggregation::Sum)
+    }
+
+    /// Check that the links into each node with a non-sum aggregation are contiguous and
+    /// directly followed by its activation
+    pub fn validate_aggregations(&self) -> Result<(), String> {
+        let mut aggregating = None;
+        let mut aggregated = vec![false; self.values.len()];
+        for action in self.actions.iter() {
+            match action {
+                Action::Link(_, to, _) if self.aggregation(*to) != Aggregation::Sum => {
+                    if aggregating != Some(*to) {
+                        if let Some(node) = aggregating {
+                            return Err(format!("links into node {} are interrupted", node));
+                        }
+                        if aggregated[*to] {
+                            return Err(format!("links into node {} are not contiguous", to));
+                        }
+                        aggregating = Some(*to);
+                        aggregated[*to] = true;
+                    }
+                }
+                Action::Activation(node, _, _) if aggregating == Some(*node) => {
+                    aggregating = None;
+                }
+                _ => {
+                    if let Some(node) = aggregating {
+                        return Err(format!("links into node {} are interrupted", node));
+                    }
+                }
+            }
+        }
+        match aggregating {
+            Some(node) => Err(format!("node {} is aggregated but not activated", node)),
+            None => Ok(()),
+        }
+    }
+
+    /// Convert network to another float type, e.g. f32 to halve the memory used by large networks
+    pub fn cast<G: Float>(&self) -> Executor<G> {
+        Executor::create(
+            self.values.len(),
+            self.inputs.clone(),
+            self.outputs.clone(),
+            self.actions.iter().map(Action::cast).collect(),
+        )
+        .with_output_activation(self.output_activation)
+        .with_aggregations(self.aggregations.clone())
+    }
+
+    /// Convert network to another float type, without copying it if the type is the same
+    pub fn into_float<G: Float>(self) -> Executor<G> {
+        let network: Box<dyn Any> = Box::new(self);
+        match network.downcast::<Executor<G>>() {
+            Ok(network) => *network,
+            Err(network) => network
+                .downcast::<Executor<F>>()
+                .expect("network of its own float type")
+                .cast(),
         }
     }
 
@@ -43,44 +176,189 @@ impl Executor {
             libc::memset(
                 self.values.as_mut_ptr() as _,
                 0,
-                self.values.len() * mem::size_of::<f64>(),
+                self.values.len() * mem::size_of::<F>(),
             );
         }
 
         // Copy inputs into values
         for (i, index) in self.inputs.iter().enumerate() {
-            self.values[i] = inputs[*index];
+            self.values[i] = F::from_f64(inputs[*index]);
         }
 
         // Do forward pass
         for action in self.actions.iter() {
             match action {
                 Action::Link(from, to, weight) => {
-                    self.values[*to] += self.values[*from] * weight;
+                    let value = self.values[*from] * *weight;
+                    match self.aggregations.get(*to) {
+                        None | Some(Aggregation::Sum) => self.values[*to] += value,
+                        Some(_) => self.link_values.push(value),
+                    }
                 }
                 Action::Activation(node, bias, activation) => {
-                    self.values[*node] = activation.activate(self.values[*node] + bias)
+                    match self.aggregations.get(*node) {
+                        Some(aggregation)
+                            if *aggregation != Aggregation::Sum && !self.link_values.is_empty() =>
+                        {
+                            self.values[*node] = aggregation.aggregate(&mut self.link_values);
+                            self.link_values.clear();
+                        }
+                        _ => {}
+                    }
+                    self.values[*node] = activation.activate(self.values[*node] + *bias)
                 }
             }
         }
 
         // Collect output
-        self.outputs
+        let mut outputs = self
+            .outputs
             .iter()
             .map(|o| {
                 if self.values[*o].is_finite() {
-                    self.values[*o]
+                    self.values[*o].to_f64()
                 } else {
                     0.0
                 }
             })
+            .collect::<Vec<f64>>();
+        self.output_activation.activate(&mut outputs);
+        outputs
+    }
+
+    /// Evaluate network for many samples, returns output node values of each sample
+    pub fn execute_batch(&mut self, inputs: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut outputs = vec![0.0; inputs.len() * self.outputs.len()];
+        self.execute_batch_into(inputs, &mut outputs);
+        let num_outputs = self.outputs.len();
+        (0..inputs.len())
+            .map(|i| outputs[i * num_outputs..(i + 1) * num_outputs].to_vec())
             .collect()
     }
+
+    /// Evaluate network for many samples, writing the output node values of each sample to
+    /// consecutive rows of the output buffer. Samples are processed in chunks, so that each
+    /// action is applied to a chunk of samples at once. Does not allocate after the first call.
+    pub fn execute_batch_into(&mut self, inputs: &[Vec<f64>], outputs: &mut [f64]) {
+        self.execute_samples_into(inputs.len(), |i| &inputs[i], outputs);
+    }
+
+    /// Evaluate network for the given number of samples in consecutive rows of the given number
+    /// of columns, like execute_batch_into
+    pub fn execute_rows_into(
+        &mut self,
+        inputs: &[f64],
+        rows: usize,
+        columns: usize,
+        outputs: &mut [f64],
+    ) {
+        assert_eq!(
+            inputs.len(),
+            rows * columns,
+            "input buffer does not match batch size"
+        );
+        self.execute_samples_into(rows, |i| &inputs[i * columns..(i + 1) * columns], outputs);
+    }
+
+    fn execute_samples_into<'a>(
+        &mut self,
+        count: usize,
+        sample: impl Fn(usize) -> &'a [f64],
+        outputs: &mut [f64],
+    ) {
+        let num_outputs = self.outputs.len();
+        assert_eq!(
+            outputs.len(),
+            count * num_outputs,
+            "output buffer does not match batch size"
+        );
+        self.batch_values
+            .resize(self.values.len() * BATCH_LANES, F::zero());
+
+        for (start, chunk_outputs) in (0..count)
+            .step_by(BATCH_LANES)
+            .zip(outputs.chunks_mut(BATCH_LANES * num_outputs.max(1)))
+        {
+            let lanes = (count - start).min(BATCH_LANES);
+            let values = &mut self.batch_values;
+
+            // Clear network
+            for value in values.iter_mut() {
+                *value = F::zero();
+            }
+
+            // Copy inputs into values
+            for lane in 0..lanes {
+                let sample = sample(start + lane);
+                for (i, index) in self.inputs.iter().enumerate() {
+                    values[i * BATCH_LANES + lane] = F::from_f64(sample[*index]);
+                }
+            }
+
+            // Do forward pass, each action for all samples of the chunk
+            for action in self.actions.iter() {
+                match action {
+                    Action::Link(from, to, weight) => {
+                        let aggregated = match self.aggregations.get(*to) {
+                            None | Some(Aggregation::Sum) => false,
+                            Some(_) => true,
+                        };
+                        let (from, to) = (*from * BATCH_LANES, *to * BATCH_LANES);
+                        for lane in 0..lanes {
+                            let value = values[from + lane] * *weight;
+                            if aggregated {
+                                self.link_values.push(value);
+                            } else {
+                                values[to + lane] += value;
+                            }
+                        }
+                    }
+                    Action::Activation(node, bias, activation) => {
+                        let node = *node;
+                        let start = node * BATCH_LANES;
+                        match self.aggregations.get(node) {
+                            Some(aggregation)
+                                if *aggregation != Aggregation::Sum
+                                    && !self.link_values.is_empty() =>
+                            {
+                                for lane in 0..lanes {
+                                    self.lane_values.clear();
+                                    self.lane_values
+                                        .extend(self.link_values.iter().skip(lane).step_by(lanes));
+                                    values[start + lane] =
+                                        aggregation.aggregate(&mut self.lane_values);
+                                }
+                                self.link_values.clear();
+                            }
+                            _ => {}
+                        }
+                        for value in values[start..start + lanes].iter_mut() {
+                            *value = activation.activate(*value + *bias);
+                        }
+                    }
+                }
+            }
+
+            // Collect output
+            for (lane, sample_outputs) in chunk_outputs.chunks_mut(num_outputs.max(1)).enumerate() {
+                for (output, o) in sample_outputs.iter_mut().zip(self.outputs.iter()) {
+                    let value = values[*o * BATCH_LANES + lane];
+                    *output = if value.is_finite() {
+                        value.to_f64()
+                    } else {
+                        0.0
+                    };
+                }
+                self.output_activation.activate(sample_outputs);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::Rng;
 
     #[test]
     fn test_evaluator() {
@@ -108,4 +386,175 @@ mod tests {
             vec![15.0, 0.0, 0.0, 0.0]
         );
     }
+
+    #[test]
+    fn test_batch() {
+        let mut executor = Executor::create(
+            6,
+            vec![1, 0],
+            vec![5, 4],
+            vec![
+                Action::Link(0, 2, 0.5),
+                Action::Link(1, 2, -1.0),
+                Action::Activation(2, 0.1, Activation::Tanh),
+                Action::Link(2, 5, 2.0),
+                Action::Link(0, 5, 1.5),
+                Action::Activation(5, 0.0, Activation::Sigmoid),
+                Action::Link(2, 4, 1.0),
+                Action::Activation(4, 0.0, Activation::Exp),
+            ],
+        );
+        let inputs = (0..(2 * BATCH_LANES + 3))
+            .map(|i| vec![i as f64 / 10.0, 1.0 - i as f64 / 20.0])
+            .collect::<Vec<_>>();
+        let single = inputs
+            .iter()
+            .map(|input| executor.execute(input))
+            .collect::<Vec<_>>();
+
+        assert_eq!(executor.execute_batch(&inputs), single);
+
+        let mut outputs = vec![0.0; inputs.len() * 2];
+        executor.execute_batch_into(&inputs[..5], &mut outputs[..10]);
+        assert_eq!(
+            outputs[..10].chunks(2).collect::<Vec<_>>(),
+            single[..5].iter().map(|o| &o[..]).collect::<Vec<_>>()
+        );
+
+        let rows = inputs.iter().flatten().cloned().collect::<Vec<f64>>();
+        let mut row_outputs = vec![0.0; inputs.len() * 2];
+        executor.execute_rows_into(&rows, inputs.len(), 2, &mut row_outputs);
+        assert_eq!(row_outputs, single.concat());
+    }
+
+    #[test]
+    fn test_single_precision() {
+        let mut rng = rand::thread_rng();
+        let activations = [
+            Activation::Sigmoid,
+            Activation::Tanh,
+            Activation::Gaussian,
+            Activation::ReLU,
+        ];
+
+        for _ in 0..20 {
+            // Random layered network of 4 inputs, 2 hidden layers of 16 nodes and 3 outputs
+            let layers = [0..4, 4..20, 20..36, 36..39];
+            let mut actions = Vec::new();
+            for layer in 1..layers.len() {
+                for to in layers[layer].clone() {
+                    for from in layers[layer - 1].clone() {
+                        actions.push(Action::Link(from, to, rng.gen::<f64>() * 2.0 - 1.0));
+                    }
+                    let activation = activations[rng.gen_range(0, activations.len())];
+                    actions.push(Action::Activation(to, rng.gen::<f64>() - 0.5, activation));
+                }
+            }
+            let mut double = Executor::create(39, vec![0, 1, 2, 3], (36..39).collect(), actions);
+            let mut single = double.cast::<f32>();
+            let mut compiled = single.compile();
+
+            let inputs = (0..50)
+                .map(|_| (0..4).map(|_| rng.gen::<f64>() * 2.0 - 1.0).collect())
+                .collect::<Vec<Vec<f64>>>();
+            let expected = double.execute_batch(&inputs);
+            let batch = single.execute_batch(&inputs);
+
+            for (i, input) in inputs.iter().enumerate() {
+                let outputs = [
+                    single.execute(input),
+                    compiled.execute(input),
+                    batch[i].clone(),
+                ];
+                for output in outputs.iter() {
+                    for (a, b) in output.iter().zip(expected[i].iter()) {
+                        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cast_round_trip() {
+        let executor = Executor::create(
+            3,
+            vec![0],
+            vec![2],
+            vec![
+                Action::Link(0, 1, 0.25),
+                Action::Activation(1, -0.5, Activation::Sigmoid),
+                Action::Link(1, 2, 1.5),
+            ],
+        );
+        let mut round_trip = executor.cast::<f32>().into_float::<f64>();
+        let mut same = executor.clone().into_float::<f64>();
+        assert_eq!(same.actions, executor.actions);
+        let mut executor = executor;
+        assert_eq!(round_trip.execute(&vec![2.0]), executor.execute(&vec![2.0]));
+        assert_eq!(same.execute(&vec![2.0]), executor.execute(&vec![2.0]));
+    }
+
+    #[test]
+    fn test_aggregations() {
+        let actions = vec![
+            Action::Link(0, 2, 2.0),
+            Action::Link(1, 2, -1.0),
+            Action::Link(0, 2, 0.5),
+            Action::Activation(2, 0.5, Activation::None),
+            Action::Link(2, 3, 1.0),
+            Action::Link(0, 3, 1.0),
+            Action::Activation(3, 0.0, Activation::None),
+        ];
+        let aggregations = vec![
+            Aggregation::Sum,
+            Aggregation::Sum,
+            Aggregation::Product,
+            Aggregation::Max,
+        ];
+        let mut executor = Executor::create(4, vec![0, 1], vec![2, 3], actions.clone())
+            .with_aggregations(aggregations.clone());
+        assert!(executor.validate_aggregations().is_ok());
+        // Node 2 is 2 * -3 * 0.5 + 0.5, node 3 the max of it and 1
+        assert_eq!(executor.execute(&vec![1.0, 3.0]), vec![-2.5, 1.0]);
+        assert_eq!(
+            executor.execute_batch(&[vec![1.0, 3.0], vec![-1.0, 1.0]]),
+            vec![vec![-2.5, 1.0], vec![-0.5, -0.5]]
+        );
+
+        // Links into an aggregated node must directly precede its activation
+        let mut interrupted = actions.clone();
+        interrupted.swap(2, 3);
+        let executor = Executor::create(4, vec![0, 1], vec![2, 3], interrupted)
+            .with_aggregations(aggregations.clone());
+        assert!(executor.validate_aggregations().is_err());
+        let executor = Executor::create(4, vec![0, 1], vec![2, 3], actions[..6].to_vec())
+            .with_aggregations(aggregations);
+        assert!(executor.validate_aggregations().is_err());
+    }
+
+    #[test]
+    fn test_output_activation() {
+        let mut executor = Executor::create(
+            4,
+            vec![0],
+            vec![1, 2, 3],
+            vec![
+                Action::Link(0, 1, 1.0),
+                Action::Link(0, 2, 2.0),
+                Action::Link(0, 3, -1.0),
+            ],
+        )
+        .with_output_activation(GroupActivation::Softmax);
+        let mut compiled = executor.compile();
+
+        for input in [0.0, 1.0, 500.0, -500.0].iter() {
+            let outputs = executor.execute(&vec![*input]);
+            assert!((outputs.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            assert!(outputs.iter().all(|p| *p >= 0.0 && *p <= 1.0));
+            assert_eq!(compiled.execute(&[*input]), outputs);
+            assert_eq!(executor.execute_batch(&[vec![*input]]), vec![outputs]);
+        }
+        assert_eq!(executor.execute(&vec![0.0]), vec![1.0 / 3.0; 3]);
+    }
 }
@@ -0,0 +1,289 @@
+use crate::activation::{Activation, GroupActivation};
+use crate::aggregation::Aggregation;
+use crate::compiled::CompiledExecutor;
+use crate::connection::Connections;
+use crate::execute::Executor;
+use crate::float::Float;
+use std::collections::HashMap;
+
+/// Form of an Executor that evaluates the network one layer at a time, with the layers given
+/// by Connections::layers. All nodes of a layer are aggregated before any of them is activated,
+/// as they only depend on earlier layers.
+///
+/// Requires topologically sorted actions, like the developers create, and then gives results
+/// identical to execute.
+#[derive(Clone, Debug)]
+pub struct LayeredExecutor<F: Float = f64> {
+    values: Vec<F>,
+    /// Aggregated value of each node in the layer being evaluated
+    layer_values: Vec<F>,
+    /// Weighted links of the node being aggregated
+    link_values: Vec<F>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    output_activation: GroupActivation,
+
+    // Per layer, the end of its steps
+    layer_ends: Vec<usize>,
+
+    // Per step, grouped by layer: target node, optional activation and the range of its links
+    step_nodes: Vec<usize>,
+    step_biases: Vec<F>,
+    step_activations: Vec<Option<Activation>>,
+    step_aggregations: Vec<Aggregation>,
+    step_link_ends: Vec<usize>,
+
+    // Per link, grouped by step
+    link_sources: Vec<usize>,
+    link_weights: Vec<F>,
+}
+
+impl<F: Float> From<&Executor<F>> for LayeredExecutor<F> {
+    fn from(executor: &Executor<F>) -> Self {
+        let compiled = CompiledExecutor::from(executor);
+
+        // Layers of the nodes that have links, all other nodes are in the first layer
+        let mut connections = Connections::<usize, ()>::new();
+        for (step, node) in compiled.step_nodes.iter().enumerate() {
+            let start = if step == 0 {
+                0
+            } else {
+                compiled.step_link_ends[step - 1]
+            };
+            for source in compiled.link_sources[start..compiled.step_link_ends[step]].iter() {
+                assert!(
+                    !connections.creates_cycle(*source, *node),
+                    "layered execution requires an acyclic network"
+                );
+                if !connections.contains(source, *node) {
+                    connections.add(*source, *node, ());
+                }
+            }
+        }
+        let depths = connections
+            .layers()
+            .iter()
+            .enumerate()
+            .flat_map(|(depth, layer)| layer.iter().map(move |node| (*node, depth)))
+            .collect::<HashMap<usize, usize>>();
+
+        // Steps of each layer, in the order they are compiled
+        let mut layers = Vec::<Vec<usize>>::new();
+        let mut stepped = vec![false; executor.values.len()];
+        for (step, node) in compiled.step_nodes.iter().enumerate() {
+            assert!(
+                !stepped[*node],
+                "layered execution requires topologically sorted actions"
+            );
+            stepped[*node] = true;
+
+            let depth = *depths.get(node).unwrap_or(&0);
+            if layers.len() <= depth {
+                layers.resize_with(depth + 1, Vec::new);
+            }
+            layers[depth].push(step);
+        }
+
+        let mut layered = Self {
+            values: vec![F::zero(); executor.values.len()],
+            layer_values: vec![F::zero(); layers.iter().map(Vec::len).max().unwrap_or(0)],
+            link_values: Vec::new(),
+            inputs: compiled.inputs.clone(),
+            outputs: compiled.outputs.clone(),
+            output_activation: compiled.output_activation,
+            layer_ends: Vec::new(),
+            step_nodes: Vec::new(),
+            step_biases: Vec::new(),
+            step_activations: Vec::new(),
+            step_aggregations: Vec::new(),
+            step_link_ends: Vec::new(),
+            link_sources: Vec::new(),
+            link_weights: Vec::new(),
+        };
+        for layer in layers.iter() {
+            for step in layer.iter().cloned() {
+                let start = if step == 0 {
+                    0
+                } else {
+                    compiled.step_link_ends[step - 1]
+                };
+                let end = compiled.step_link_ends[step];
+                layered
+                    .link_sources
+                    .extend_from_slice(&compiled.link_sources[start..end]);
+                layered
+                    .link_weights
+                    .extend_from_slice(&compiled.link_weights[start..end]);
+                layered.step_nodes.push(compiled.step_nodes[step]);
+                layered.step_biases.push(compiled.step_biases[step]);
+                layered
+                    .step_activations
+                    .push(compiled.step_activations[step]);
+                layered
+                    .step_aggregations
+                    .push(compiled.step_aggregations[step]);
+                layered.step_link_ends.push(layered.link_sources.len());
+            }
+            layered.layer_ends.push(layered.step_nodes.len());
+        }
+
+        layered
+    }
+}
+
+impl<F: Float> LayeredExecutor<F> {
+    /// Number of layers evaluated in sequence
+    pub fn depth(&self) -> usize {
+        self.layer_ends.len()
+    }
+
+    /// Evaluate network, takes input node values, returns output node values
+    pub fn execute(&mut self, inputs: &[f64]) -> Vec<f64> {
+        // Clear network
+        for value in self.values.iter_mut() {
+            *value = F::zero();
+        }
+
+        // Copy inputs into values
+        for (i, index) in self.inputs.iter().enumerate() {
+            self.values[i] = F::from_f64(inputs[*index]);
+        }
+
+        let mut step = 0;
+        let mut link = 0;
+        for layer_end in self.layer_ends.iter() {
+            let layer_start = step;
+
+            // Aggregate all nodes of the layer, reading only earlier layers
+            while step < *layer_end {
+                let end = self.step_link_ends[step];
+                let aggregation = self.step_aggregations[step];
+                let mut sum = self.values[self.step_nodes[step]];
+                if aggregation == Aggregation::Sum || link == end {
+                    while link < end {
+                        sum += self.values[self.link_sources[link]] * self.link_weights[link];
+                        link += 1;
+                    }
+                } else {
+                    self.link_values.clear();
+                    while link < end {
+                        self.link_values
+                            .push(self.values[self.link_sources[link]] * self.link_weights[link]);
+                        link += 1;
+                    }
+                    sum = aggregation.aggregate(&mut self.link_values);
+                }
+                self.layer_values[step - layer_start] = sum;
+                step += 1;
+            }
+
+            // Activate all nodes of the layer
+            for step in layer_start..*layer_end {
+                let sum = self.layer_values[step - layer_start];
+                self.values[self.step_nodes[step]] = match self.step_activations[step] {
+                    Some(activation) => activation.activate(sum + self.step_biases[step]),
+                    None => sum,
+                };
+            }
+        }
+
+        // Collect output
+        let mut outputs = self
+            .outputs
+            .iter()
+            .map(|o| {
+                if self.values[*o].is_finite() {
+                    self.values[*o].to_f64()
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<f64>>();
+        self.output_activation.activate(&mut outputs);
+        outputs
+    }
+}
+
+impl<F: Float> Executor<F> {
+    /// Convert to layered form, evaluating one layer at a time
+    pub fn layered(&self) -> LayeredExecutor<F> {
+        LayeredExecutor::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::OrderedAction;
+    use crate::execute::Action;
+    use rand::Rng;
+
+    #[test]
+    fn test_identical_results() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let mut connections = Connections::<usize, f64>::new();
+            for _ in 0..200 {
+                let from = rng.gen_range(0, 39);
+                let to = rng.gen_range(from + 1, 40);
+                if !connections.contains(&from, to) {
+                    connections.add(from, to, rng.gen::<f64>() - 0.5);
+                }
+            }
+            let actions = connections
+                .sort_topologically_stable()
+                .iter()
+                .filter_map(|action| match action {
+                    OrderedAction::Edge(from, to, weight) => {
+                        Some(Action::Link(*from, *to, *weight))
+                    }
+                    OrderedAction::Node(node) if *node >= 3 => Some(Action::Activation(
+                        *node,
+                        rng.gen::<f64>(),
+                        Activation::Tanh,
+                    )),
+                    _ => None,
+                })
+                .collect();
+            let aggregations = (0..40)
+                .map(|node| match node {
+                    0..=2 => Aggregation::Sum,
+                    _ => Aggregation::ALL[rng.gen_range(0, Aggregation::ALL.len())],
+                })
+                .collect();
+            let mut executor = Executor::create(40, vec![0, 1, 2], (37..40).collect(), actions)
+                .with_aggregations(aggregations);
+            let mut layered = executor.layered();
+            assert_eq!(layered.depth(), connections.layers().len());
+
+            for _ in 0..5 {
+                let inputs = (0..3).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+                assert_eq!(executor.execute(&inputs), layered.execute(&inputs));
+            }
+        }
+    }
+
+    #[test]
+    fn test_layers() {
+        let executor = Executor::create(
+            5,
+            vec![0, 1],
+            vec![4],
+            vec![
+                Action::Link(0, 2, 0.5),
+                Action::Activation(2, 0.0, Activation::None),
+                Action::Link(1, 3, 2.0),
+                Action::Activation(3, 0.5, Activation::None),
+                Action::Link(2, 4, 1.0),
+                Action::Link(3, 4, 1.0),
+                Action::Link(1, 4, -1.0),
+                Action::Activation(4, 0.0, Activation::None),
+            ],
+        );
+        let mut layered = executor.layered();
+        assert_eq!(layered.depth(), 3);
+        assert_eq!(layered.step_nodes, vec![2, 3, 4]);
+        assert_eq!(layered.layer_ends, vec![0, 2, 3]);
+        assert_eq!(layered.execute(&[1.0, 2.0]), vec![3.0]);
+    }
+}
@@ -4,5 +4,14 @@ extern crate enum_display_derive;
 extern crate derive_new;
 
 pub mod activation;
+pub mod aggregation;
+pub mod analysis;
+pub mod codegen;
+pub mod compiled;
 pub mod connection;
 pub mod execute;
+pub mod float;
+pub mod layered;
+pub mod onnx;
+pub mod serialize;
+pub mod simplify;
@@ -0,0 +1,662 @@
+mod proto;
+#[cfg(test)]
+mod reference;
+
+use self::proto::Message;
+use crate::activation::{Activation, GroupActivation, SELU_ALPHA, SELU_SCALE};
+use crate::aggregation::Aggregation;
+use crate::compiled::CompiledExecutor;
+use crate::execute::Executor;
+use crate::float::Float;
+use std::{collections::HashMap, fs, io, mem, path::Path};
+
+const IR_VERSION: i64 = 7;
+const OPSET_VERSION: i64 = 13;
+
+// TensorProto data types
+const FLOAT: i64 = 1;
+const INT64: i64 = 7;
+const DOUBLE: i64 = 11;
+
+// AttributeProto types
+const ATTRIBUTE_INT: i64 = 2;
+const ATTRIBUTE_TENSOR: i64 = 4;
+const ATTRIBUTE_INTS: i64 = 7;
+
+impl<F: Float> Executor<F> {
+    /// Export to an ONNX model, with input "input" of shape [N, inputs] and output "output"
+    /// of shape [N, outputs] for batch size N. Tensors have the float type of the executor.
+    ///
+    /// The model is built from the compiled form of the executor, so actions are assumed
+    /// to be topologically sorted.
+    pub fn to_onnx(&self) -> Vec<u8> {
+        export(&self.compile())
+    }
+
+    pub fn save_onnx<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_onnx())
+    }
+}
+
+fn export<F: Float>(compiled: &CompiledExecutor<F>) -> Vec<u8> {
+    let elem_type = if mem::size_of::<F>() == mem::size_of::<f32>() {
+        FLOAT
+    } else {
+        DOUBLE
+    };
+    let mut graph = Graph::new(elem_type);
+
+    // Current value of each node, as a tensor of shape [N, 1]
+    let mut values = HashMap::<usize, String>::new();
+    for (i, index) in compiled.inputs.iter().enumerate() {
+        let indices = graph.indices(&[*index]);
+        let value = graph.node(
+            "Gather",
+            &["input", &indices],
+            vec![int_attribute("axis", 1)],
+        );
+        values.insert(i, value);
+    }
+
+    // Each step is a weighted sum of the node's current value and its links, with the
+    // current value first so the order of additions matches the executor.
+    let mut link = 0;
+    for step in 0..compiled.step_nodes.len() {
+        let node = compiled.step_nodes[step];
+        let end = compiled.step_link_ends[step];
+
+        let links = link..end;
+        link = end;
+
+        // Aggregated nodes are set to the aggregation of their links, without current value
+        let aggregation = compiled.step_aggregations[step];
+        let aggregated = aggregation != Aggregation::Sum && !links.is_empty();
+
+        let mut terms = Vec::new();
+        let mut weights = Vec::new();
+        if let (Some(value), false) = (values.get(&node), aggregated) {
+            terms.push(value.clone());
+            weights.push(1.0);
+        }
+        for (source, weight) in compiled.link_sources[links.clone()]
+            .iter()
+            .zip(compiled.link_weights[links.clone()].iter())
+        {
+            terms.push(graph.value(&values, *source));
+            weights.push(weight.to_f64());
+        }
+
+        let mut value = if aggregated {
+            graph.aggregate(&terms, &weights, aggregation)
+        } else if terms.is_empty() {
+            graph.zero()
+        } else if links.is_empty() {
+            terms.pop().unwrap()
+        } else {
+            let columns = graph.concat(&terms, 1);
+            let weights = graph.constant(&[weights.len(), 1], &weights);
+            graph.node("MatMul", &[&columns, &weights], vec![])
+        };
+
+        if let Some(activation) = compiled.step_activations[step] {
+            let bias = graph.scalar(compiled.step_biases[step].to_f64());
+            value = graph.node("Add", &[&value, &bias], vec![]);
+            value = graph.activate(value, activation);
+        }
+        values.insert(node, value);
+    }
+
+    // Outputs, with non-finite values replaced by zero before the group activation
+    let outputs = compiled
+        .outputs
+        .iter()
+        .map(|output| graph.value(&values, *output))
+        .collect::<Vec<String>>();
+    let mut output = if outputs.is_empty() {
+        graph.empty()
+    } else {
+        graph.concat(&outputs, 1)
+    };
+    let nan = graph.node("IsNaN", &[&output], vec![]);
+    let inf = graph.node("IsInf", &[&output], vec![]);
+    let non_finite = graph.node("Or", &[&nan, &inf], vec![]);
+    let zero = graph.scalar(0.0);
+    output = graph.node("Where", &[&non_finite, &zero, &output], vec![]);
+    let output = match compiled.output_activation {
+        GroupActivation::None => output,
+        GroupActivation::Softmax => {
+            graph.node("Softmax", &[&output], vec![int_attribute("axis", 1)])
+        }
+        GroupActivation::LogSoftmax => {
+            graph.node("LogSoftmax", &[&output], vec![int_attribute("axis", 1)])
+        }
+    };
+    graph.node("Identity", &[&output], vec![]);
+    graph.rename_last("output");
+
+    let input_width = compiled.inputs.iter().max().map_or(0, |index| index + 1);
+    graph.into_model(input_width, compiled.outputs.len())
+}
+
+/// Operator type, inputs, outputs and attributes of a node in the graph
+type GraphNode = (String, Vec<String>, Vec<String>, Vec<Message>);
+
+/// ONNX graph under construction
+struct Graph {
+    elem_type: i64,
+    nodes: Vec<GraphNode>,
+    initializers: Vec<Message>,
+    tensors: usize,
+    zero: Option<String>,
+}
+
+impl Graph {
+    fn new(elem_type: i64) -> Self {
+        Self {
+            elem_type,
+            nodes: Vec::new(),
+            initializers: Vec::new(),
+            tensors: 0,
+            zero: None,
+        }
+    }
+
+    fn name(&mut self) -> String {
+        self.tensors += 1;
+        format!("t{}", self.tensors)
+    }
+
+    /// Add node, returns the name of its output
+    fn node(&mut self, op_type: &str, inputs: &[&str], attributes: Vec<Message>) -> String {
+        self.multi_output_node(op_type, inputs, attributes, 1)
+            .remove(0)
+    }
+
+    /// Add node with several outputs, returns their names
+    fn multi_output_node(
+        &mut self,
+        op_type: &str,
+        inputs: &[&str],
+        attributes: Vec<Message>,
+        outputs: usize,
+    ) -> Vec<String> {
+        let outputs = (0..outputs).map(|_| self.name()).collect::<Vec<_>>();
+        self.nodes.push((
+            op_type.to_string(),
+            inputs.iter().map(|input| input.to_string()).collect(),
+            outputs.clone(),
+            attributes,
+        ));
+        outputs
+    }
+
+    fn rename_last(&mut self, name: &str) {
+        if let Some(node) = self.nodes.last_mut() {
+            node.2[0] = name.to_string();
+        }
+    }
+
+    fn constant(&mut self, dims: &[usize], values: &[f64]) -> String {
+        let name = self.name();
+        self.initializers
+            .push(tensor(self.elem_type, dims, values).string(8, &name));
+        name
+    }
+
+    fn scalar(&mut self, value: f64) -> String {
+        self.constant(&[], &[value])
+    }
+
+    fn indices(&mut self, values: &[usize]) -> String {
+        let name = self.name();
+        let values = values.iter().map(|v| *v as f64).collect::<Vec<f64>>();
+        self.initializers
+            .push(tensor(INT64, &[values.len()], &values).string(8, &name));
+        name
+    }
+
+    fn concat(&mut self, tensors: &[String], axis: i64) -> String {
+        let inputs = tensors.iter().map(|t| &t[..]).collect::<Vec<&str>>();
+        self.node("Concat", &inputs, vec![int_attribute("axis", axis)])
+    }
+
+    /// Value of node, zero if never written
+    fn value(&mut self, values: &HashMap<usize, String>, node: usize) -> String {
+        values.get(&node).cloned().unwrap_or_else(|| self.zero())
+    }
+
+    /// Zeros of shape [N, 1], created once
+    fn zero(&mut self) -> String {
+        if let Some(zero) = &self.zero {
+            return zero.clone();
+        }
+        let zero = self.filled(1);
+        self.zero = Some(zero.clone());
+        zero
+    }
+
+    /// Zeros of shape [N, 0], for networks without outputs
+    fn empty(&mut self) -> String {
+        self.filled(0)
+    }
+
+    fn filled(&mut self, width: usize) -> String {
+        let shape = self.node("Shape", &["input"], vec![]);
+        let first = self.indices(&[0]);
+        let batch = self.node("Gather", &[&shape, &first], vec![int_attribute("axis", 0)]);
+        let width = self.indices(&[width]);
+        let shape = self.concat(&[batch, width], 0);
+        let value = Message::new()
+            .string(1, "value")
+            .int(20, ATTRIBUTE_TENSOR)
+            .message(5, &tensor(self.elem_type, &[1], &[0.0]));
+        self.node("ConstantOfShape", &[&shape], vec![value])
+    }
+
+    fn activate(&mut self, x: String, activation: Activation) -> String {
+        match activation {
+            Activation::None => x,
+            Activation::Linear => {
+                let (min, max) = (self.scalar(-1.0), self.scalar(1.0));
+                let x = self.node("Min", &[&x, &max], vec![]);
+                self.node("Max", &[&x, &min], vec![])
+            }
+            Activation::Step => {
+                let zero = self.scalar(0.0);
+                let positive = self.node("Greater", &[&x, &zero], vec![]);
+                let to = int_attribute("to", self.elem_type);
+                self.node("Cast", &[&positive], vec![to])
+            }
+            Activation::ReLU => self.node("Relu", &[&x], vec![]),
+            Activation::Sigmoid => self.node("Sigmoid", &[&x], vec![]),
+            Activation::Tanh => self.node("Tanh", &[&x], vec![]),
+            Activation::Softmax => self.node("Exp", &[&x], vec![]),
+            Activation::Gaussian => self.gaussian(x),
+            Activation::OffsetGaussian => {
+                let (one, two) = (self.scalar(1.0), self.scalar(2.0));
+                let x = self.gaussian(x);
+                let x = self.node("Mul", &[&x, &two], vec![]);
+                self.node("Sub", &[&x, &one], vec![])
+            }
+            Activation::Sine => {
+                let two = self.scalar(2.0);
+                let x = self.node("Mul", &[&x, &two], vec![]);
+                self.node("Sin", &[&x], vec![])
+            }
+            Activation::Cos => {
+                let two = self.scalar(2.0);
+                let x = self.node("Mul", &[&x, &two], vec![]);
+                self.node("Cos", &[&x], vec![])
+            }
+            Activation::Square => self.node("Mul", &[&x, &x], vec![]),
+            Activation::Abs => self.node("Abs", &[&x], vec![]),
+            Activation::Exp => self.bounded_exp(x, 1.0),
+            Activation::Sawtooth => {
+                let floor = self.node("Floor", &[&x], vec![]);
+                self.node("Sub", &[&x, &floor], vec![])
+            }
+            Activation::Triangle => {
+                let (half, one, two) = (self.scalar(0.5), self.scalar(1.0), self.scalar(2.0));
+                let y = self.node("Add", &[&x, &one], vec![]);
+                let y = self.node("Mul", &[&y, &half], vec![]);
+                let y = self.node("Floor", &[&y], vec![]);
+                let y = self.node("Mul", &[&two, &y], vec![]);
+                let y = self.node("Sub", &[&x, &y], vec![]);
+                let y = self.node("Abs", &[&y], vec![]);
+                let y = self.node("Mul", &[&two, &y], vec![]);
+                self.node("Sub", &[&one, &y], vec![])
+            }
+            Activation::Inverse => {
+                let (zero, one) = (self.scalar(0.0), self.scalar(1.0));
+                let is_zero = self.node("Equal", &[&x, &zero], vec![]);
+                let inverse = self.node("Div", &[&one, &x], vec![]);
+                self.node("Where", &[&is_zero, &zero, &inverse], vec![])
+            }
+            Activation::Log => {
+                let min = self.scalar(1e-7);
+                let x = self.node("Max", &[&x, &min], vec![]);
+                self.node("Log", &[&x], vec![])
+            }
+            Activation::Softplus => {
+                let (zero, one) = (self.scalar(0.0), self.scalar(1.0));
+                let positive = self.node("Max", &[&x, &zero], vec![]);
+                let y = self.node("Abs", &[&x], vec![]);
+                let y = self.node("Neg", &[&y], vec![]);
+                let y = self.node("Exp", &[&y], vec![]);
+                let y = self.node("Add", &[&one, &y], vec![]);
+                let y = self.node("Log", &[&y], vec![]);
+                self.node("Add", &[&positive, &y], vec![])
+            }
+            Activation::ELU => self.exponential_linear(x, None),
+            Activation::SELU => {
+                let scale = self.scalar(SELU_SCALE);
+                let x = self.exponential_linear(x, Some(SELU_ALPHA));
+                self.node("Mul", &[&scale, &x], vec![])
+            }
+            Activation::Hat => {
+                let (zero, one) = (self.scalar(0.0), self.scalar(1.0));
+                let x = self.node("Abs", &[&x], vec![]);
+                let x = self.node("Sub", &[&one, &x], vec![]);
+                self.node("Max", &[&x, &zero], vec![])
+            }
+            Activation::BipolarSigmoid => {
+                let (one, two) = (self.scalar(1.0), self.scalar(2.0));
+                let x = self.node("Sigmoid", &[&x], vec![]);
+                let x = self.node("Mul", &[&two, &x], vec![]);
+                self.node("Sub", &[&x, &one], vec![])
+            }
+            Activation::ScaledGaussian(p) => self.scaled_gaussian(x, p),
+            Activation::ScaledSine(p) => {
+                let scale = self.scalar(p);
+                let x = self.node("Mul", &[&scale, &x], vec![]);
+                self.node("Sin", &[&x], vec![])
+            }
+            Activation::ScaledSigmoid(p) => {
+                let scale = self.scalar(p);
+                let x = self.node("Mul", &[&scale, &x], vec![]);
+                self.node("Sigmoid", &[&x], vec![])
+            }
+            Activation::LeakyReLU(p) => {
+                let (zero, slope) = (self.scalar(0.0), self.scalar(p));
+                let positive = self.node("Greater", &[&x, &zero], vec![]);
+                let negative = self.node("Mul", &[&slope, &x], vec![]);
+                self.node("Where", &[&positive, &x, &negative], vec![])
+            }
+            Activation::BoundedExp(p) => self.bounded_exp(x, p),
+        }
+    }
+
+    /// Aggregation of the weighted terms, each of shape [N, 1]
+    fn aggregate(&mut self, terms: &[String], weights: &[f64], aggregation: Aggregation) -> String {
+        let columns = self.concat(terms, 1);
+        let weights = self.constant(&[weights.len()], weights);
+        let columns = self.node("Mul", &[&columns, &weights], vec![]);
+        let reduce = match aggregation {
+            Aggregation::Sum => unreachable!("summed links are a matrix product"),
+            Aggregation::Product => "ReduceProd",
+            Aggregation::Max => "ReduceMax",
+            Aggregation::Min => "ReduceMin",
+            Aggregation::Mean => "ReduceMean",
+            Aggregation::Median => return self.median(columns, terms.len()),
+        };
+        self.node(reduce, &[&columns], vec![ints_attribute("axes", &[1])])
+    }
+
+    /// Median of the columns of a tensor of shape [N, count]
+    fn median(&mut self, columns: String, count: usize) -> String {
+        let k = self.indices(&[count]);
+        let attributes = vec![
+            int_attribute("axis", 1),
+            int_attribute("largest", 0),
+            int_attribute("sorted", 1),
+        ];
+        let sorted = self
+            .multi_output_node("TopK", &[&columns, &k], attributes, 2)
+            .remove(0);
+        let mut column = |index: usize| {
+            let indices = self.indices(&[index]);
+            self.node(
+                "Gather",
+                &[&sorted, &indices],
+                vec![int_attribute("axis", 1)],
+            )
+        };
+        let middle = count / 2;
+        if count % 2 == 1 {
+            column(middle)
+        } else {
+            let (lower, upper) = (column(middle - 1), column(middle));
+            let half = self.scalar(0.5);
+            let sum = self.node("Add", &[&lower, &upper], vec![]);
+            self.node("Mul", &[&sum, &half], vec![])
+        }
+    }
+
+    fn gaussian(&mut self, x: String) -> String {
+        self.scaled_gaussian(x, 2.5)
+    }
+
+    fn scaled_gaussian(&mut self, x: String, scale: f64) -> String {
+        let scale = self.scalar(scale);
+        let x = self.node("Mul", &[&x, &scale], vec![]);
+        let x = self.node("Mul", &[&x, &x], vec![]);
+        let x = self.node("Neg", &[&x], vec![]);
+        self.node("Exp", &[&x], vec![])
+    }
+
+    fn bounded_exp(&mut self, x: String, bound: f64) -> String {
+        let bound = self.scalar(bound);
+        let x = self.node("Min", &[&x, &bound], vec![]);
+        self.node("Exp", &[&x], vec![])
+    }
+
+    /// x if positive, otherwise alpha * (exp(x) - 1), without the multiplication if alpha
+    /// is not given
+    fn exponential_linear(&mut self, x: String, alpha: Option<f64>) -> String {
+        let (zero, one) = (self.scalar(0.0), self.scalar(1.0));
+        let positive = self.node("Greater", &[&x, &zero], vec![]);
+        let negative = self.node("Exp", &[&x], vec![]);
+        let mut negative = self.node("Sub", &[&negative, &one], vec![]);
+        if let Some(alpha) = alpha {
+            let alpha = self.scalar(alpha);
+            negative = self.node("Mul", &[&alpha, &negative], vec![]);
+        }
+        self.node("Where", &[&positive, &x, &negative], vec![])
+    }
+
+    fn into_model(self, inputs: usize, outputs: usize) -> Vec<u8> {
+        let mut graph = Message::new().string(2, "network");
+        for (op_type, inputs, outputs, attributes) in self.nodes.iter() {
+            let mut node = Message::new();
+            for input in inputs.iter() {
+                node = node.string(1, input);
+            }
+            for output in outputs.iter() {
+                node = node.string(2, output);
+            }
+            node = node.string(3, &outputs[0]).string(4, op_type);
+            for attribute in attributes.iter() {
+                node = node.message(5, attribute);
+            }
+            graph = graph.message(1, &node);
+        }
+        for initializer in self.initializers.iter() {
+            graph = graph.message(5, initializer);
+        }
+        graph = graph
+            .message(11, &value_info("input", self.elem_type, inputs))
+            .message(12, &value_info("output", self.elem_type, outputs));
+
+        Message::new()
+            .int(1, IR_VERSION)
+            .string(2, "des-hyperneat")
+            .message(7, &graph)
+            .message(8, &Message::new().string(1, "").int(2, OPSET_VERSION))
+            .into_bytes()
+    }
+}
+
+fn int_attribute(name: &str, value: i64) -> Message {
+    Message::new()
+        .string(1, name)
+        .int(20, ATTRIBUTE_INT)
+        .int(3, value)
+}
+
+fn ints_attribute(name: &str, values: &[i64]) -> Message {
+    let mut attribute = Message::new().string(1, name).int(20, ATTRIBUTE_INTS);
+    for value in values.iter() {
+        attribute = attribute.int(8, *value);
+    }
+    attribute
+}
+
+/// TensorProto with little-endian raw data
+fn tensor(data_type: i64, dims: &[usize], values: &[f64]) -> Message {
+    let mut tensor = Message::new();
+    for dim in dims.iter() {
+        tensor = tensor.int(1, *dim as i64);
+    }
+    let raw = values
+        .iter()
+        .flat_map(|value| match data_type {
+            FLOAT => (*value as f32).to_le_bytes().to_vec(),
+            INT64 => (*value as i64).to_le_bytes().to_vec(),
+            _ => value.to_le_bytes().to_vec(),
+        })
+        .collect::<Vec<u8>>();
+    tensor.int(2, data_type).bytes(9, &raw)
+}
+
+/// ValueInfoProto of a tensor of shape [N, width]
+fn value_info(name: &str, elem_type: i64, width: usize) -> Message {
+    let shape = Message::new()
+        .message(1, &Message::new().string(2, "N"))
+        .message(1, &Message::new().int(1, width as i64));
+    let tensor_type = Message::new().int(1, elem_type).message(2, &shape);
+    Message::new()
+        .string(1, name)
+        .message(2, &Message::new().message(1, &tensor_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reference::Model;
+    use super::*;
+    use crate::connection::{Connections, OrderedAction};
+    use crate::execute::Action;
+    use rand::Rng;
+
+    /// Random topologically sorted network with 3 inputs read in permuted order and 3
+    /// outputs. Some nodes are summed without activation, and some never written.
+    fn random_executor(nodes: usize, links: usize, activations: &[Activation]) -> Executor {
+        let mut rng = rand::thread_rng();
+        let mut connections = Connections::<usize, f64>::new();
+        for _ in 0..links {
+            let from = rng.gen_range(0, nodes - 1);
+            let to = rng.gen_range(from + 1, nodes);
+            if !connections.contains(&from, to) {
+                connections.add(from, to, rng.gen::<f64>() * 2.0 - 1.0);
+            }
+        }
+
+        let actions = connections
+            .sort_topologically()
+            .iter()
+            .filter_map(|action| match action {
+                OrderedAction::Edge(from, to, weight) => Some(Action::Link(*from, *to, *weight)),
+                OrderedAction::Node(node) if *node >= 3 && rng.gen::<f64>() < 0.9 => {
+                    Some(Action::Activation(
+                        *node,
+                        rng.gen::<f64>() - 0.5,
+                        activations[rng.gen_range(0, activations.len())],
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Executor::create(nodes, vec![2, 0, 1], (nodes - 3..nodes).collect(), actions)
+    }
+
+    /// Random aggregation for each activated node
+    fn with_random_aggregations(executor: Executor) -> Executor {
+        let mut rng = rand::thread_rng();
+        let mut aggregations = vec![Aggregation::Sum; executor.values.len()];
+        for action in executor.actions.iter() {
+            if let Action::Activation(node, _, _) = action {
+                aggregations[*node] = Aggregation::ALL[rng.gen_range(0, Aggregation::ALL.len())];
+            }
+        }
+        executor.with_aggregations(aggregations)
+    }
+
+    fn random_inputs(samples: usize) -> Vec<Vec<f64>> {
+        let mut rng = rand::thread_rng();
+        (0..samples)
+            .map(|_| (0..3).map(|_| rng.gen::<f64>() * 4.0 - 2.0).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let output_activations = [
+            GroupActivation::None,
+            GroupActivation::Softmax,
+            GroupActivation::LogSoftmax,
+        ];
+        for i in 0..30 {
+            let mut executor = random_executor(25, 80, &Activation::ALL)
+                .with_output_activation(output_activations[i % output_activations.len()]);
+            let model = Model::parse(&executor.to_onnx());
+            assert_eq!(model.opset, OPSET_VERSION);
+            assert_eq!(model.elem_type, DOUBLE);
+
+            let inputs = random_inputs(10);
+            assert_eq!(model.run(&inputs), executor.execute_batch(&inputs));
+        }
+    }
+
+    #[test]
+    fn test_aggregations() {
+        for _ in 0..30 {
+            let mut executor = with_random_aggregations(random_executor(25, 80, &Activation::ALL));
+            let model = Model::parse(&executor.to_onnx());
+
+            let inputs = random_inputs(10);
+            assert_eq!(model.run(&inputs), executor.execute_batch(&inputs));
+        }
+    }
+
+    #[test]
+    fn test_single_precision() {
+        for _ in 0..10 {
+            // Functions that are discontinuous or steep near zero amplify rounding differences
+            let activations = Activation::ALL
+                .iter()
+                .filter(|activation| {
+                    !matches!(
+                        activation,
+                        Activation::Sawtooth | Activation::Inverse | Activation::Log
+                    )
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let executor = random_executor(25, 80, &activations)
+                .with_output_activation(GroupActivation::Softmax);
+            let mut single = executor.cast::<f32>();
+            let model = Model::parse(&single.to_onnx());
+            assert_eq!(model.elem_type, FLOAT);
+            assert!(model.data_types.iter().all(|t| *t == FLOAT || *t == INT64));
+
+            let inputs = random_inputs(10);
+            for (a, b) in model
+                .run(&inputs)
+                .iter()
+                .flatten()
+                .zip(single.execute_batch(&inputs).iter().flatten())
+            {
+                assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unconnected() {
+        // Outputs that are never written, and an input read as output
+        let mut executor = Executor::create(
+            4,
+            vec![1, 0],
+            vec![3, 1, 2],
+            vec![Action::Activation(2, 0.5, Activation::None)],
+        );
+        let model = Model::parse(&executor.to_onnx());
+        let inputs = vec![vec![1.0, 2.0], vec![-3.0, 4.0]];
+        assert_eq!(model.run(&inputs), executor.execute_batch(&inputs));
+        assert_eq!(model.run(&inputs)[1], vec![0.0, -3.0, 0.5]);
+
+        let mut empty = Executor::<f64>::create(1, vec![0], vec![], vec![]);
+        let model = Model::parse(&empty.to_onnx());
+        assert_eq!(model.run(&inputs), empty.execute_batch(&inputs));
+    }
+}
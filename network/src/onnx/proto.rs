@@ -0,0 +1,74 @@
+/// Protocol buffers message, encoded as fields are added. Only the wire types used by the
+/// ONNX messages of the exporter are supported.
+#[derive(Clone, Default)]
+pub struct Message {
+    buffer: Vec<u8>,
+}
+
+impl Message {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn int(mut self, field: u32, value: i64) -> Self {
+        self.key(field, 0);
+        self.varint(value as u64);
+        self
+    }
+
+    pub fn bytes(mut self, field: u32, value: &[u8]) -> Self {
+        self.key(field, 2);
+        self.varint(value.len() as u64);
+        self.buffer.extend_from_slice(value);
+        self
+    }
+
+    pub fn string(self, field: u32, value: &str) -> Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    pub fn message(self, field: u32, value: &Message) -> Self {
+        self.bytes(field, &value.buffer)
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buffer.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.buffer.push(value as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        // Examples from the protocol buffers encoding documentation
+        assert_eq!(
+            Message::new().int(1, 150).into_bytes(),
+            vec![0x08, 0x96, 0x01]
+        );
+        assert_eq!(
+            Message::new().string(2, "testing").into_bytes(),
+            vec![0x12, 0x07, 0x74, 0x65, 0x73, 0x74, 0x69, 0x6e, 0x67]
+        );
+        assert_eq!(
+            Message::new()
+                .message(3, &Message::new().int(1, 150))
+                .into_bytes(),
+            vec![0x1a, 0x03, 0x08, 0x96, 0x01]
+        );
+        assert_eq!(Message::new().int(1, -1).into_bytes().len(), 11);
+    }
+}
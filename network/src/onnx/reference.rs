@@ -0,0 +1,411 @@
+//! Reference interpreter for the ONNX models written by the exporter. It decodes the
+//! protocol buffers encoding and evaluates the operators in f64, in the same order of
+//! operations as the executor.
+
+use super::{DOUBLE, FLOAT, INT64};
+use std::{cmp::Ordering, collections::HashMap};
+
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+fn varint(bytes: &[u8], position: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*position];
+        *position += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+/// Fields of a message, in order
+fn fields(bytes: &[u8]) -> Vec<(u64, Value<'_>)> {
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        let key = varint(bytes, &mut position);
+        let value = match key & 7 {
+            0 => Value::Varint(varint(bytes, &mut position)),
+            2 => {
+                let length = varint(bytes, &mut position) as usize;
+                position += length;
+                Value::Bytes(&bytes[position - length..position])
+            }
+            wire_type => panic!("unsupported wire type {}", wire_type),
+        };
+        fields.push((key >> 3, value));
+    }
+    fields
+}
+
+fn field_bytes(bytes: &[u8], field: u64) -> Vec<&[u8]> {
+    fields(bytes)
+        .into_iter()
+        .filter_map(|(f, value)| match value {
+            Value::Bytes(bytes) if f == field => Some(bytes),
+            _ => None,
+        })
+        .collect()
+}
+
+fn field_ints(bytes: &[u8], field: u64) -> Vec<i64> {
+    fields(bytes)
+        .into_iter()
+        .filter_map(|(f, value)| match value {
+            Value::Varint(value) if f == field => Some(value as i64),
+            _ => None,
+        })
+        .collect()
+}
+
+fn field_string(bytes: &[u8], field: u64) -> String {
+    field_bytes(bytes, field)
+        .first()
+        .map(|bytes| String::from_utf8(bytes.to_vec()).unwrap())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tensor {
+    pub shape: Vec<usize>,
+    pub data: Vec<f64>,
+}
+
+/// Decode TensorProto with raw data, returns name, data type and tensor
+fn tensor(bytes: &[u8]) -> (String, i64, Tensor) {
+    let shape = field_ints(bytes, 1)
+        .iter()
+        .map(|d| *d as usize)
+        .collect::<Vec<usize>>();
+    let data_type = field_ints(bytes, 2)[0];
+    let raw = field_bytes(bytes, 9)[0];
+    let data: Vec<f64> = match data_type {
+        FLOAT => raw
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+            .collect(),
+        INT64 | DOUBLE => raw
+            .chunks(8)
+            .map(|b| {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(b);
+                if data_type == INT64 {
+                    i64::from_le_bytes(bytes) as f64
+                } else {
+                    f64::from_le_bytes(bytes)
+                }
+            })
+            .collect(),
+        _ => panic!("unsupported data type {}", data_type),
+    };
+    assert_eq!(shape.iter().product::<usize>(), data.len());
+    (field_string(bytes, 8), data_type, Tensor { shape, data })
+}
+
+struct Node {
+    op_type: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    ints: HashMap<String, i64>,
+    tensors: HashMap<String, Tensor>,
+}
+
+pub struct Model {
+    pub opset: i64,
+    pub elem_type: i64,
+    pub data_types: Vec<i64>,
+    nodes: Vec<Node>,
+    initializers: HashMap<String, Tensor>,
+    input: String,
+    output: String,
+}
+
+impl Model {
+    pub fn parse(bytes: &[u8]) -> Self {
+        let graph = field_bytes(bytes, 7)[0];
+        let opset = field_ints(field_bytes(bytes, 8)[0], 2)[0];
+
+        let nodes = field_bytes(graph, 1)
+            .iter()
+            .map(|node| {
+                let mut ints = HashMap::new();
+                let mut tensors = HashMap::new();
+                for attribute in field_bytes(node, 5) {
+                    let name = field_string(attribute, 1);
+                    // Single int, or the first of a list of ints
+                    if let Some(value) = field_ints(attribute, 3)
+                        .into_iter()
+                        .chain(field_ints(attribute, 8))
+                        .next()
+                    {
+                        ints.insert(name.clone(), value);
+                    }
+                    if let Some(value) = field_bytes(attribute, 5).first() {
+                        tensors.insert(name, tensor(value).2);
+                    }
+                }
+                Node {
+                    op_type: field_string(node, 4),
+                    inputs: field_bytes(node, 1)
+                        .iter()
+                        .map(|input| String::from_utf8(input.to_vec()).unwrap())
+                        .collect(),
+                    outputs: field_bytes(node, 2)
+                        .iter()
+                        .map(|output| String::from_utf8(output.to_vec()).unwrap())
+                        .collect(),
+                    ints,
+                    tensors,
+                }
+            })
+            .collect();
+
+        let mut data_types = Vec::new();
+        let mut initializers = HashMap::new();
+        for initializer in field_bytes(graph, 5) {
+            let (name, data_type, tensor) = tensor(initializer);
+            data_types.push(data_type);
+            initializers.insert(name, tensor);
+        }
+
+        let input = field_bytes(graph, 11)[0];
+        let tensor_type = field_bytes(field_bytes(input, 2)[0], 1)[0];
+
+        Self {
+            opset,
+            elem_type: field_ints(tensor_type, 1)[0],
+            data_types,
+            nodes,
+            initializers,
+            input: field_string(input, 1),
+            output: field_string(field_bytes(graph, 12)[0], 1),
+        }
+    }
+
+    pub fn run(&self, inputs: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut values = self.initializers.clone();
+        values.insert(
+            self.input.clone(),
+            Tensor {
+                shape: vec![inputs.len(), inputs.first().map_or(0, |i| i.len())],
+                data: inputs.iter().flatten().cloned().collect(),
+            },
+        );
+
+        for node in self.nodes.iter() {
+            let args = node
+                .inputs
+                .iter()
+                .map(|input| values.get(input).expect("unknown tensor"))
+                .collect::<Vec<&Tensor>>();
+            let axis = node.ints.get("axis").cloned().unwrap_or(0) as usize;
+            let output = match &node.op_type[..] {
+                "Identity" | "Cast" => args[0].clone(),
+                "Shape" => Tensor {
+                    shape: vec![args[0].shape.len()],
+                    data: args[0].shape.iter().map(|d| *d as f64).collect(),
+                },
+                "ConstantOfShape" => {
+                    let shape = args[0].data.iter().map(|d| *d as usize).collect::<Vec<_>>();
+                    let value = node.tensors["value"].data[0];
+                    Tensor {
+                        data: vec![value; shape.iter().product()],
+                        shape,
+                    }
+                }
+                "Gather" => gather(args[0], args[1], axis),
+                "Concat" => concat(&args, axis),
+                "MatMul" => matmul(args[0], args[1]),
+                "Add" => broadcast(&args, |v| v[0] + v[1]),
+                "Sub" => broadcast(&args, |v| v[0] - v[1]),
+                "Mul" => broadcast(&args, |v| v[0] * v[1]),
+                "Div" => broadcast(&args, |v| v[0] / v[1]),
+                "Min" => broadcast(&args, |v| v[0].min(v[1])),
+                "Max" => broadcast(&args, |v| v[0].max(v[1])),
+                "Greater" => broadcast(&args, |v| (v[0] > v[1]) as u8 as f64),
+                "Equal" => broadcast(&args, |v| (v[0] == v[1]) as u8 as f64),
+                "Or" => broadcast(&args, |v| (v[0] != 0.0 || v[1] != 0.0) as u8 as f64),
+                "Where" => broadcast(&args, |v| if v[0] != 0.0 { v[1] } else { v[2] }),
+                "Neg" => map(args[0], |x| -x),
+                "Exp" => map(args[0], f64::exp),
+                "Relu" => map(args[0], |x| x.max(0.0)),
+                "Sigmoid" => map(args[0], |x| 1.0 / (1.0 + (-x).exp())),
+                "Tanh" => map(args[0], f64::tanh),
+                "Sin" => map(args[0], f64::sin),
+                "Cos" => map(args[0], f64::cos),
+                "Abs" => map(args[0], f64::abs),
+                "Floor" => map(args[0], f64::floor),
+                "Log" => map(args[0], f64::ln),
+                "IsNaN" => map(args[0], |x| x.is_nan() as u8 as f64),
+                "IsInf" => map(args[0], |x| x.is_infinite() as u8 as f64),
+                "Softmax" | "LogSoftmax" => {
+                    assert_eq!(axis, args[0].shape.len() - 1);
+                    let mut output = args[0].clone();
+                    let width = output.shape[axis];
+                    for row in output.data.chunks_mut(width.max(1)) {
+                        let max = row.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                        if node.op_type == "Softmax" {
+                            let mut sum = 0.0;
+                            for x in row.iter_mut() {
+                                *x = (*x - max).exp();
+                                sum += *x;
+                            }
+                            for x in row.iter_mut() {
+                                *x /= sum;
+                            }
+                        } else {
+                            let sum = row.iter().map(|x| (x - max).exp()).sum::<f64>();
+                            let log_sum = max + sum.ln();
+                            for x in row.iter_mut() {
+                                *x -= log_sum;
+                            }
+                        }
+                    }
+                    output
+                }
+                "ReduceProd" | "ReduceMax" | "ReduceMin" | "ReduceMean" => {
+                    assert_eq!(node.ints["axes"], 1);
+                    let reduce = |row: &[f64]| match &node.op_type[..] {
+                        "ReduceProd" => row[1..].iter().fold(row[0], |a, b| a * b),
+                        "ReduceMax" => row[1..].iter().fold(row[0], |a, b| a.max(*b)),
+                        "ReduceMin" => row[1..].iter().fold(row[0], |a, b| a.min(*b)),
+                        _ => row[1..].iter().fold(row[0], |a, b| a + b) / row.len() as f64,
+                    };
+                    Tensor {
+                        shape: vec![args[0].shape[0], 1],
+                        data: args[0].data.chunks(args[0].shape[1]).map(reduce).collect(),
+                    }
+                }
+                "TopK" => {
+                    assert_eq!(axis, 1);
+                    assert_eq!(args[1].data[0] as usize, args[0].shape[1]);
+                    let largest = node.ints.get("largest").cloned().unwrap_or(1) != 0;
+                    let mut output = args[0].clone();
+                    for row in output.data.chunks_mut(args[0].shape[1]) {
+                        row.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                        if largest {
+                            row.reverse();
+                        }
+                    }
+                    output
+                }
+                op_type => panic!("unsupported operator {}", op_type),
+            };
+            values.insert(node.outputs[0].clone(), output);
+        }
+
+        let output = &values[&self.output];
+        let width = output.shape[1];
+        (0..output.shape[0])
+            .map(|row| output.data[row * width..(row + 1) * width].to_vec())
+            .collect()
+    }
+}
+
+fn map(x: &Tensor, f: impl Fn(f64) -> f64) -> Tensor {
+    Tensor {
+        shape: x.shape.clone(),
+        data: x.data.iter().cloned().map(f).collect(),
+    }
+}
+
+/// Elementwise operation with numpy-style broadcasting
+fn broadcast(args: &[&Tensor], f: impl Fn(&[f64]) -> f64) -> Tensor {
+    let rank = args.iter().map(|t| t.shape.len()).max().unwrap();
+    let shapes = args
+        .iter()
+        .map(|t| {
+            let mut shape = vec![1; rank - t.shape.len()];
+            shape.extend(t.shape.iter());
+            shape
+        })
+        .collect::<Vec<Vec<usize>>>();
+    let shape = (0..rank)
+        .map(|d| {
+            let dims = shapes.iter().map(|s| s[d]).filter(|d| *d != 1);
+            let dim = dims.clone().next().unwrap_or(1);
+            assert!(dims.clone().all(|d| d == dim), "shapes cannot be broadcast");
+            dim
+        })
+        .collect::<Vec<usize>>();
+
+    let mut operands = vec![0.0; args.len()];
+    let data = (0..shape.iter().product())
+        .map(|flat| {
+            for (operand, (arg, arg_shape)) in operands.iter_mut().zip(args.iter().zip(&shapes)) {
+                let (mut remaining, mut index, mut stride) = (flat, 0, 1);
+                for d in (0..rank).rev() {
+                    let coordinate = remaining % shape[d];
+                    remaining /= shape[d];
+                    if arg_shape[d] != 1 {
+                        index += coordinate * stride;
+                    }
+                    stride *= arg_shape[d];
+                }
+                *operand = arg.data[index];
+            }
+            f(&operands)
+        })
+        .collect();
+    Tensor { shape, data }
+}
+
+fn gather(data: &Tensor, indices: &Tensor, axis: usize) -> Tensor {
+    let outer = data.shape[..axis].iter().product::<usize>();
+    let inner = data.shape[axis + 1..].iter().product::<usize>();
+    let mut shape = data.shape.clone();
+    shape[axis] = indices.data.len();
+
+    let mut output = Vec::new();
+    for o in 0..outer {
+        for index in indices.data.iter() {
+            let start = (o * data.shape[axis] + *index as usize) * inner;
+            output.extend_from_slice(&data.data[start..start + inner]);
+        }
+    }
+    Tensor {
+        shape,
+        data: output,
+    }
+}
+
+fn concat(args: &[&Tensor], axis: usize) -> Tensor {
+    let outer = args[0].shape[..axis].iter().product::<usize>();
+    let mut shape = args[0].shape.clone();
+    shape[axis] = args.iter().map(|t| t.shape[axis]).sum();
+
+    let mut data = Vec::new();
+    for o in 0..outer {
+        for arg in args.iter() {
+            let chunk = arg.shape[axis..].iter().product::<usize>();
+            data.extend_from_slice(&arg.data[o * chunk..(o + 1) * chunk]);
+        }
+    }
+    Tensor { shape, data }
+}
+
+/// Matrix product, summing in order from zero
+fn matmul(a: &Tensor, b: &Tensor) -> Tensor {
+    let (rows, inner, columns) = (a.shape[0], a.shape[1], b.shape[1]);
+    assert_eq!(inner, b.shape[0]);
+    let mut data = vec![0.0; rows * columns];
+    for row in 0..rows {
+        for column in 0..columns {
+            let mut sum = 0.0;
+            for k in 0..inner {
+                sum += a.data[row * inner + k] * b.data[k * columns + column];
+            }
+            data[row * columns + column] = sum;
+        }
+    }
+    Tensor {
+        shape: vec![rows, columns],
+        data,
+    }
+}
@@ -0,0 +1,107 @@
+use crate::activation::GroupActivation;
+use crate::aggregation::Aggregation;
+use crate::execute::{Action, Executor};
+use crate::float::Float;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Version of the executor format, increased on incompatible changes.
+/// The format is described by the JSON schema in executor.schema.json.
+///
+/// Version 2 added aggregations, version 1 files are read with all nodes summing their links.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// Portable form of an Executor, without the buffers used during execution:
+///
+/// {"version": 2, "length": 4, "inputs": [0, 1], "outputs": [3], "output_activation": "None",
+///  "aggregations": [[2, "Product"]],
+///  "actions": [{"Link": [0, 2, 0.5]}, {"Activation": [2, 0.1, "Tanh"]}, {"Link": [2, 3, 1.0]}]}
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SerializedExecutor<F: Float = f64> {
+    pub version: u64,
+    pub length: usize,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+    pub output_activation: GroupActivation,
+    /// Nodes that do not sum their links
+    #[serde(default)]
+    pub aggregations: Vec<(usize, Aggregation)>,
+    pub actions: Vec<Action<F>>,
+}
+
+impl<F: Float> From<Executor<F>> for SerializedExecutor<F> {
+    fn from(executor: Executor<F>) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            length: executor.values.len(),
+            inputs: executor.inputs,
+            outputs: executor.outputs,
+            output_activation: executor.output_activation,
+            aggregations: executor
+                .aggregations
+                .iter()
+                .cloned()
+                .enumerate()
+                .filter(|(_, aggregation)| *aggregation != Aggregation::Sum)
+                .collect(),
+            actions: executor.actions,
+        }
+    }
+}
+
+impl<F: Float> TryFrom<SerializedExecutor<F>> for Executor<F> {
+    type Error = String;
+
+    fn try_from(serialized: SerializedExecutor<F>) -> Result<Self, String> {
+        if serialized.version == 0 || serialized.version > FORMAT_VERSION {
+            return Err(format!(
+                "unsupported executor format version {}, expected {}",
+                serialized.version, FORMAT_VERSION
+            ));
+        }
+
+        let length = serialized.length;
+        if serialized.inputs.len() > length {
+            return Err(format!(
+                "{} inputs do not fit in {} nodes",
+                serialized.inputs.len(),
+                length
+            ));
+        }
+        if let Some(output) = serialized.outputs.iter().find(|o| **o >= length) {
+            return Err(format!("output node {} out of bounds", output));
+        }
+        for action in serialized.actions.iter() {
+            let in_bounds = match action {
+                Action::Link(from, to, _) => *from < length && *to < length,
+                Action::Activation(node, _, _) => *node < length,
+            };
+            if !in_bounds {
+                return Err(format!("action {:?} out of bounds", action));
+            }
+        }
+
+        let mut aggregations = Vec::new();
+        if !serialized.aggregations.is_empty() {
+            aggregations = vec![Aggregation::Sum; length];
+            for (node, aggregation) in serialized.aggregations.iter() {
+                if *node >= length {
+                    return Err(format!("aggregated node {} out of bounds", node));
+                }
+                aggregations[*node] = *aggregation;
+            }
+        }
+
+        let executor = Executor::create(
+            length,
+            serialized.inputs,
+            serialized.outputs,
+            serialized.actions,
+        )
+        .with_output_activation(serialized.output_activation)
+        .with_aggregations(aggregations);
+        executor.validate_aggregations()?;
+        Ok(executor)
+    }
+}
@@ -0,0 +1,453 @@
+use crate::activation::Activation;
+use crate::aggregation::Aggregation;
+use crate::execute::{Action, Executor};
+use crate::float::Float;
+use std::collections::{HashMap, HashSet};
+
+impl<F: Float> Executor<F> {
+    /// Network computing the same outputs, up to rounding, with fewer actions. Like compile,
+    /// this assumes topologically sorted actions.
+    ///
+    /// Links with weights no larger than epsilon are dropped, nodes that do not depend on the
+    /// inputs are folded into the biases of their targets, identity nodes with a single
+    /// outgoing link are collapsed into their target, and nodes that do not reach an output
+    /// are removed. Remaining hidden nodes are renumbered, while input nodes, which are the
+    /// first inputs.len() nodes, keep their numbers and the sample values they read.
+    ///
+    /// Links into nodes with non-sum aggregations are kept regardless of their weight, and
+    /// constants and identities are only folded in networks where all nodes sum their links.
+    pub fn simplify(&self, epsilon: f64) -> Executor<F> {
+        assert!(
+            self.inputs.len() <= self.values.len(),
+            "more inputs than nodes"
+        );
+        let actions = self
+            .actions
+            .iter()
+            .filter(|action| match action {
+                Action::Link(_, to, weight) => {
+                    weight.to_f64().abs() > epsilon || self.aggregation(*to) != Aggregation::Sum
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        let actions = if self
+            .aggregations
+            .iter()
+            .all(|aggregation| *aggregation == Aggregation::Sum)
+        {
+            let actions = self.fold_constants(actions);
+            self.collapse_identities(actions)
+        } else {
+            actions
+        };
+        let actions = self.remove_dead(actions);
+        self.renumber(actions)
+    }
+
+    /// Number of nodes connected by links, and number of links
+    pub fn size(&self) -> (usize, usize) {
+        let mut nodes = HashSet::new();
+        let mut links = 0;
+        for action in self.actions.iter() {
+            if let Action::Link(from, to, _) = action {
+                nodes.insert(*from);
+                nodes.insert(*to);
+                links += 1;
+            }
+        }
+        (nodes.len(), links)
+    }
+
+    /// Evaluate nodes that do not depend on the inputs. Their contributions to other nodes
+    /// are added to the bias of the target, or as an identity activation before it is read.
+    fn fold_constants(&self, actions: Vec<Action<F>>) -> Vec<Action<F>> {
+        let length = self.values.len();
+        let mut constants = (0..length)
+            .map(|node| {
+                if node < self.inputs.len() {
+                    None
+                } else {
+                    Some(F::zero())
+                }
+            })
+            .collect::<Vec<Option<F>>>();
+        let mut pending = vec![F::zero(); length];
+        let mut folded = Vec::new();
+
+        let materialize = |node: usize, pending: &mut Vec<F>, folded: &mut Vec<Action<F>>| {
+            if pending[node] != F::zero() {
+                folded.push(Action::Activation(node, pending[node], Activation::None));
+                pending[node] = F::zero();
+            }
+        };
+
+        for action in actions.into_iter() {
+            match action {
+                Action::Link(from, to, weight) => match constants[from] {
+                    Some(value) => match constants[to] {
+                        Some(target) => constants[to] = Some(target + value * weight),
+                        None => pending[to] += value * weight,
+                    },
+                    None => {
+                        materialize(from, &mut pending, &mut folded);
+                        if let Some(target) = constants[to].take() {
+                            pending[to] += target;
+                        }
+                        folded.push(Action::Link(from, to, weight));
+                    }
+                },
+                Action::Activation(node, bias, activation) => match constants[node] {
+                    Some(value) => constants[node] = Some(activation.activate(value + bias)),
+                    None => {
+                        folded.push(Action::Activation(node, bias + pending[node], activation));
+                        pending[node] = F::zero();
+                    }
+                },
+            }
+        }
+
+        for output in self.outputs.iter() {
+            if let Some(value) = constants[*output] {
+                pending[*output] = value;
+                constants[*output] = None;
+            }
+            materialize(*output, &mut pending, &mut folded);
+        }
+        folded
+    }
+
+    /// Replace identity nodes, with a single outgoing link, by links from their sources to
+    /// the target of the outgoing link. Nodes are visited in reverse order, so chains of
+    /// identity nodes collapse into a single link.
+    fn collapse_identities(&self, actions: Vec<Action<F>>) -> Vec<Action<F>> {
+        let length = self.values.len();
+        let mut in_links = vec![Vec::new(); length];
+        let mut out_links = vec![Vec::new(); length];
+        let mut activations = vec![Vec::new(); length];
+        for (i, action) in actions.iter().enumerate() {
+            match action {
+                Action::Link(from, to, _) => {
+                    out_links[*from].push(i);
+                    in_links[*to].push(i);
+                }
+                Action::Activation(node, _, _) => activations[*node].push(i),
+            }
+        }
+        let outputs = self.outputs.iter().cloned().collect::<HashSet<usize>>();
+        let mut actions = actions.into_iter().map(Some).collect::<Vec<_>>();
+
+        let mut order = (self.inputs.len()..length)
+            .filter(|node| activations[*node].len() == 1 && !outputs.contains(node))
+            .collect::<Vec<usize>>();
+        order.sort_by_key(|node| activations[*node][0]);
+
+        for node in order.into_iter().rev() {
+            let activation_index = activations[node][0];
+            let bias = match actions[activation_index] {
+                Some(Action::Activation(_, bias, Activation::None)) => bias,
+                _ => continue,
+            };
+            if out_links[node].len() != 1
+                || in_links[node].iter().any(|link| *link > activation_index)
+            {
+                continue;
+            }
+            let link_index = out_links[node][0];
+            let (target, weight) = match actions[link_index] {
+                Some(Action::Link(_, to, weight)) if link_index > activation_index => (to, weight),
+                _ => continue,
+            };
+
+            // The bias is added to the activation of the target
+            let target_activation = activations[target]
+                .iter()
+                .find(|index| **index > link_index)
+                .cloned();
+            if bias != F::zero() && target_activation.is_none() || target == node {
+                continue;
+            }
+            if let Some(index) = target_activation {
+                if let Some(Action::Activation(_, target_bias, _)) = &mut actions[index] {
+                    *target_bias += bias * weight;
+                }
+            }
+
+            for link in in_links[node].drain(..).collect::<Vec<usize>>() {
+                if let Some(Action::Link(_, to, source_weight)) = &mut actions[link] {
+                    *to = target;
+                    *source_weight = *source_weight * weight;
+                }
+                in_links[target].push(link);
+            }
+            in_links[target].retain(|link| *link != link_index);
+            out_links[node].clear();
+            actions[link_index] = None;
+            actions[activation_index] = None;
+        }
+
+        actions.into_iter().flatten().collect()
+    }
+
+    /// Remove actions that do not contribute to an output
+    fn remove_dead(&self, actions: Vec<Action<F>>) -> Vec<Action<F>> {
+        let mut live = self.outputs.iter().cloned().collect::<HashSet<usize>>();
+        let mut kept = actions
+            .into_iter()
+            .rev()
+            .filter(|action| match action {
+                Action::Link(from, to, _) => {
+                    if live.contains(to) {
+                        live.insert(*from);
+                        true
+                    } else {
+                        false
+                    }
+                }
+                Action::Activation(node, _, _) => live.contains(node),
+            })
+            .collect::<Vec<_>>();
+        kept.reverse();
+        kept
+    }
+
+    /// Number nodes after the input nodes in order of first use
+    fn renumber(&self, actions: Vec<Action<F>>) -> Executor<F> {
+        let mut mapping = (0..self.inputs.len())
+            .map(|node| (node, node))
+            .collect::<HashMap<usize, usize>>();
+        let mut map = |node: usize| {
+            let next = mapping.len();
+            *mapping.entry(node).or_insert(next)
+        };
+
+        let actions = actions
+            .into_iter()
+            .map(|action| match action {
+                Action::Link(from, to, weight) => Action::Link(map(from), map(to), weight),
+                Action::Activation(node, bias, activation) => {
+                    Action::Activation(map(node), bias, activation)
+                }
+            })
+            .collect::<Vec<_>>();
+        let outputs = self.outputs.iter().map(|output| map(*output)).collect();
+
+        let mut aggregations = Vec::new();
+        if !self.aggregations.is_empty() {
+            aggregations = vec![Aggregation::Sum; mapping.len()];
+            for (node, new) in mapping.iter() {
+                aggregations[*new] = self.aggregations[*node];
+            }
+        }
+
+        Executor::create(mapping.len(), self.inputs.clone(), outputs, actions)
+            .with_output_activation(self.output_activation)
+            .with_aggregations(aggregations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{Connections, OrderedAction};
+    use rand::Rng;
+
+    /// Random network with topologically sorted actions, where many hidden nodes are
+    /// identities, some weights are tiny and some nodes have no path from the inputs
+    fn random_executor(nodes: usize, links: usize) -> Executor {
+        let mut rng = rand::thread_rng();
+        let mut connections = Connections::<usize, f64>::new();
+        for _ in 0..links {
+            let from = rng.gen_range(0, nodes - 1);
+            let to = rng.gen_range(from + 1, nodes);
+            if !connections.contains(&from, to) {
+                let weight = if rng.gen::<f64>() < 0.1 {
+                    1e-12
+                } else {
+                    rng.gen::<f64>() * 2.0 - 1.0
+                };
+                connections.add(from, to, weight);
+            }
+        }
+        // Constant subgraph, not reachable from the inputs
+        connections.add(nodes, nodes + 1, 0.5);
+        connections.add(nodes + 1, nodes - 1, -0.3);
+
+        let activations = [
+            Activation::None,
+            Activation::None,
+            Activation::Tanh,
+            Activation::Sigmoid,
+        ];
+        let actions = connections
+            .sort_topologically()
+            .iter()
+            .filter_map(|action| match action {
+                OrderedAction::Edge(from, to, weight) => Some(Action::Link(*from, *to, *weight)),
+                OrderedAction::Node(node) if *node >= 3 => Some(Action::Activation(
+                    *node,
+                    rng.gen::<f64>() - 0.5,
+                    activations[rng.gen_range(0, activations.len())],
+                )),
+                _ => None,
+            })
+            .collect();
+
+        Executor::create(
+            nodes + 2,
+            vec![0, 1, 2],
+            vec![nodes - 2, nodes - 1],
+            actions,
+        )
+    }
+
+    #[test]
+    fn test_same_outputs() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let mut executor = random_executor(30, 80);
+            let mut simplified = executor.simplify(1e-9);
+            assert!(simplified.actions.len() <= executor.actions.len());
+            assert!(simplified.values.len() <= executor.values.len());
+
+            for _ in 0..5 {
+                let inputs = (0..3).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+                for (a, b) in executor
+                    .execute(&inputs)
+                    .iter()
+                    .zip(simplified.execute(&inputs).iter())
+                {
+                    assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_aggregations() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let aggregations = (0..32)
+                .map(|node| match node {
+                    0..=2 => Aggregation::Sum,
+                    _ => Aggregation::ALL[rng.gen_range(0, Aggregation::ALL.len())],
+                })
+                .collect();
+            let mut executor = random_executor(30, 80).with_aggregations(aggregations);
+            let mut simplified = executor.simplify(1e-9);
+            assert_eq!(simplified.validate_aggregations(), Ok(()));
+
+            for _ in 0..5 {
+                let inputs = (0..3).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>();
+                for (a, b) in executor
+                    .execute(&inputs)
+                    .iter()
+                    .zip(simplified.execute(&inputs).iter())
+                {
+                    assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_identity_chain() {
+        // 0 -> 2 -> 3 -> 4, where 2 and 3 are identities
+        let executor = Executor::create(
+            5,
+            vec![0, 1],
+            vec![4],
+            vec![
+                Action::Link(0, 2, 2.0),
+                Action::Link(1, 2, -1.0),
+                Action::Activation(2, 0.5, Activation::None),
+                Action::Link(2, 3, 3.0),
+                Action::Activation(3, 0.0, Activation::None),
+                Action::Link(3, 4, 0.5),
+                Action::Activation(4, 0.25, Activation::Tanh),
+            ],
+        );
+        let mut simplified = executor.simplify(0.0);
+        assert_eq!(
+            simplified.actions,
+            vec![
+                Action::Link(0, 2, 3.0),
+                Action::Link(1, 2, -1.5),
+                Action::Activation(2, 1.0, Activation::Tanh),
+            ]
+        );
+        assert_eq!(simplified.execute(&vec![1.0, 2.0]), vec![1f64.tanh()]);
+    }
+
+    #[test]
+    fn test_input_indices() {
+        // Input nodes 0 and 1 read the last two of four sample values
+        let executor = Executor::create(
+            4,
+            vec![3, 2],
+            vec![3],
+            vec![
+                Action::Link(0, 2, 2.0),
+                Action::Link(1, 2, -1.0),
+                Action::Activation(2, 0.0, Activation::None),
+                Action::Link(2, 3, 1.0),
+                Action::Activation(3, 0.0, Activation::Tanh),
+            ],
+        );
+        let mut simplified = executor.simplify(0.0);
+        assert_eq!(simplified.inputs, vec![3, 2]);
+        assert_eq!(
+            simplified.execute(&vec![5.0, 7.0, 1.0, 0.25]),
+            vec![(0.5f64 - 1.0).tanh()]
+        );
+    }
+
+    #[test]
+    fn test_constants_and_dead_nodes() {
+        let executor = Executor::create(
+            7,
+            vec![0],
+            vec![5, 6],
+            vec![
+                // Constant node 2 feeds 3
+                Action::Activation(2, 0.5, Activation::Sigmoid),
+                Action::Link(2, 3, 2.0),
+                Action::Link(0, 3, 1.0),
+                Action::Activation(3, 0.0, Activation::Tanh),
+                // Node 4 never reaches an output
+                Action::Link(3, 4, 1.0),
+                Action::Activation(4, 0.0, Activation::Tanh),
+                Action::Link(3, 5, 1.0),
+                Action::Link(0, 5, 0.0),
+                // Output 6 is constant
+                Action::Activation(6, 1.5, Activation::Square),
+            ],
+        );
+        let mut executor_copy = executor.clone();
+        let mut simplified = executor.simplify(0.0);
+
+        assert_eq!(simplified.values.len(), 4);
+        assert_eq!(simplified.size(), (3, 2));
+        assert_eq!(
+            simplified.actions,
+            vec![
+                Action::Link(0, 1, 1.0),
+                Action::Activation(1, Activation::Sigmoid.activate(0.5) * 2.0, Activation::Tanh),
+                Action::Link(1, 2, 1.0),
+                Action::Activation(3, 2.25, Activation::None),
+            ]
+        );
+        assert_eq!(simplified.outputs, vec![2, 3]);
+        for input in [-1.0, 0.0, 2.0].iter() {
+            let expected = executor_copy.execute(&vec![*input]);
+            for (a, b) in simplified
+                .execute(&vec![*input])
+                .iter()
+                .zip(expected.iter())
+            {
+                assert!((a - b).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Executor format, tested apart from the unit tests so that the PartialEq impls of
+//! serde_json do not leak into their type inference
+
+use network::activation::{Activation, GroupActivation};
+use network::aggregation::Aggregation;
+use network::execute::{Action, Executor};
+use network::serialize::FORMAT_VERSION;
+
+fn executor() -> Executor {
+    Executor::create(
+        4,
+        vec![1, 0],
+        vec![3],
+        vec![
+            Action::Link(0, 2, 0.5),
+            Action::Activation(2, 0.1, Activation::Tanh),
+            Action::Link(2, 3, 1.0),
+            Action::Link(1, 3, -2.0),
+            Action::Activation(3, 0.0, Activation::None),
+        ],
+    )
+    .with_output_activation(GroupActivation::Softmax)
+    .with_aggregations(vec![
+        Aggregation::Sum,
+        Aggregation::Sum,
+        Aggregation::Sum,
+        Aggregation::Min,
+    ])
+}
+
+#[test]
+fn test_round_trip() {
+    let mut executor = executor();
+    let json = serde_json::to_string(&executor).unwrap();
+    let mut loaded = serde_json::from_str::<Executor>(&json).unwrap();
+
+    assert_eq!(loaded.actions, executor.actions);
+    assert_eq!(loaded.output_activation, executor.output_activation);
+    assert_eq!(loaded.aggregations, executor.aggregations);
+    assert_eq!(
+        loaded.execute(&vec![0.3, -0.7]),
+        executor.execute(&vec![0.3, -0.7])
+    );
+
+    let single = serde_json::to_string(&executor.cast::<f32>()).unwrap();
+    assert!(serde_json::from_str::<Executor<f32>>(&single).is_ok());
+}
+
+#[test]
+fn test_format() {
+    let json = serde_json::to_value(executor()).unwrap();
+    assert_eq!(json["version"], FORMAT_VERSION);
+    assert_eq!(json["length"], 4);
+    assert_eq!(json["inputs"], serde_json::json!([1, 0]));
+    assert_eq!(json["output_activation"], "Softmax");
+    assert_eq!(json["aggregations"], serde_json::json!([[3, "Min"]]));
+    assert_eq!(json["actions"][0], serde_json::json!({"Link": [0, 2, 0.5]}));
+    assert_eq!(
+        json["actions"][1],
+        serde_json::json!({"Activation": [2, 0.1, "Tanh"]})
+    );
+    assert_eq!(
+        serde_json::to_value(Action::Activation(1, 0.0, Activation::ScaledSine(3.0))).unwrap(),
+        serde_json::json!({"Activation": [1, 0.0, {"ScaledSine": 3.0}]})
+    );
+
+    let schema =
+        serde_json::from_str::<serde_json::Value>(include_str!("../executor.schema.json")).unwrap();
+    assert_eq!(schema["properties"]["version"]["const"], FORMAT_VERSION);
+
+    // Every activation function is described by the schema
+    let activations = &schema["properties"]["actions"]["items"]["oneOf"][1]["properties"]
+        ["Activation"]["items"][2]["oneOf"];
+    for activation in Activation::ALL.iter() {
+        match serde_json::to_value(activation).unwrap() {
+            serde_json::Value::String(name) => assert!(activations[0]["enum"]
+                .as_array()
+                .unwrap()
+                .contains(&serde_json::Value::String(name))),
+            value => {
+                let name = value.as_object().unwrap().keys().next().unwrap();
+                assert!(activations[1]["properties"].get(name).is_some());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_invalid() {
+    let mut json = serde_json::to_value(executor()).unwrap();
+    json["version"] = serde_json::json!(FORMAT_VERSION + 1);
+    let error = serde_json::from_value::<Executor>(json).unwrap_err();
+    assert!(error.to_string().contains("version"));
+
+    let mut json = serde_json::to_value(executor()).unwrap();
+    json["actions"][2] = serde_json::json!({"Link": [2, 4, 1.0]});
+    let error = serde_json::from_value::<Executor>(json).unwrap_err();
+    assert!(error.to_string().contains("out of bounds"));
+
+    let mut json = serde_json::to_value(executor()).unwrap();
+    json["actions"][1] = serde_json::json!({"Activation": [2, 0.1, "Unknown"]});
+    assert!(serde_json::from_value::<Executor>(json).is_err());
+
+    let mut json = serde_json::to_value(executor()).unwrap();
+    json["actions"].as_array_mut().unwrap().pop();
+    let error = serde_json::from_value::<Executor>(json).unwrap_err();
+    assert!(error.to_string().contains("not activated"));
+}
+
+#[test]
+fn test_version_1() {
+    let mut json = serde_json::to_value(executor()).unwrap();
+    json["version"] = serde_json::json!(1);
+    json.as_object_mut().unwrap().remove("aggregations");
+    let loaded = serde_json::from_value::<Executor>(json).unwrap();
+    assert!(loaded.aggregations.is_empty());
+}
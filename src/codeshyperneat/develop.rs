@@ -1,7 +1,10 @@
 use crate::codeshyperneat::{genome::Genome as BlueprintGenome, link::Link, node::Node};
 use crate::cppn::genome::Genome as CppnGenome;
-use crate::deshyperneat::desgenome::DesGenome;
-use evolution::neat::{genome::NeatGenome, node::NodeRef};
+use crate::deshyperneat::{desgenome::DesGenome, genome::validate_cppn};
+use evolution::{
+    genome::GenericGenome,
+    neat::{genome::NeatGenome, node::NodeRef},
+};
 use std::collections::HashMap;
 
 #[derive(new, Clone)]
@@ -10,6 +13,31 @@ pub struct CombinedGenome {
     pub modules: HashMap<u64, (usize, CppnGenome)>,
 }
 
+impl CombinedGenome {
+    /// Check the blueprint, and that every module species it refers to is selected
+    pub fn validate(&self) -> Result<(), String> {
+        self.blueprint.validate()?;
+
+        let neat = &self.blueprint.neat;
+        for module_species in neat
+            .inputs
+            .values()
+            .chain(neat.hidden_nodes.values())
+            .chain(neat.outputs.values())
+            .map(|node| node.module_species)
+            .chain(neat.links.values().map(|link| link.module_species))
+        {
+            let (_, cppn) = self
+                .modules
+                .get(&module_species)
+                .ok_or_else(|| format!("module species {} is not selected", module_species))?;
+            validate_cppn(cppn).map_err(|e| format!("module {}: {}", module_species, e))?;
+        }
+
+        Ok(())
+    }
+}
+
 impl DesGenome for CombinedGenome {
     type Node = Node;
     type Link = Link;
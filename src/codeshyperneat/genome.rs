@@ -1,6 +1,6 @@
 use crate::codeshyperneat::{link::Link, node::Node, state::State};
 use crate::cppn::genome::Genome as CppnGenome;
-use crate::deshyperneat::conf::DESHYPERNEAT;
+use crate::deshyperneat::{conf::DESHYPERNEAT, genome::max_depth};
 use evolution::{
     genome::{GenericGenome as GenericEvolvableGenome, Genome as EvolvableGenome},
     neat::{conf::NeatConfig, genome::NeatGenome, node::NodeRef, state::InitConfig},
@@ -115,6 +115,28 @@ impl GenericEvolvableGenome<NeatConfig, State, InitConfig, NoStats> for Genome {
     fn get_stats(&self) -> NoStats {
         NoStats {}
     }
+
+    fn validate(&self) -> Result<(), String> {
+        self.neat.validate()?;
+
+        for (node_ref, node) in self
+            .neat
+            .inputs
+            .iter()
+            .chain(self.neat.hidden_nodes.iter())
+            .chain(self.neat.outputs.iter())
+        {
+            let limit = max_depth(node_ref);
+            if node.depth > limit {
+                return Err(format!(
+                    "node {} has depth {} above limit {}",
+                    node_ref, node.depth, limit
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Genome {
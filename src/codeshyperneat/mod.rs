@@ -11,23 +11,30 @@ use crate::codeshyperneat::{
     conf::CODESHYPERNEAT, develop::CombinedGenome, genome::Genome as BlueprintGenome,
 };
 use crate::cppn::genome::Genome as CppnGenome;
-use crate::deshyperneat::developer::{topology_init_config, Developer};
+use crate::deshyperneat::{
+    developer::{topology_init_config, Developer},
+    serialize::SerializedGenome,
+    state::State as DesState,
+};
 use conf::MethodConfig;
 use envconfig::Envconfig;
 use evolution::{
     conf::{EvolutionConfig, PopulationConfig, EVOLUTION},
-    develop::Develop,
+    develop::{Develop, FloatDeveloper},
     environment::Environment,
     evaluate::{Evaluate, MultiEvaluator},
     log::{Log, Logger},
     neat::{conf::NeatConfig, state::InitConfig},
     population::Population,
     stats::{NoStats, OrganismStats, PopulationStats},
+    write_champion, write_phenotype,
 };
-use network::execute::Executor;
+use network::{execute::Executor, float::Float};
 use serde::Serialize;
 use std::{
     collections::HashMap,
+    fs,
+    sync::Arc,
     time::{Duration, SystemTime},
     u64,
 };
@@ -45,10 +52,34 @@ struct Config<N: Serialize, E: Serialize> {
 }
 
 pub fn codeshyperneat<
-    E: Environment<Phenotype = Executor> + Default + 'static,
+    F: Float,
+    E: Environment<Phenotype = Executor<F>> + 'static,
     N: Serialize + Default,
->() {
-    let environment = &E::default();
+>(
+    environment: Arc<E>,
+) -> Option<Executor<F>> {
+    // Champions are saved as the DES-HyperNEAT genome their blueprint and modules combine into,
+    // which can be developed but not split back into a population
+    if !EVOLUTION.seed_genome.is_empty() {
+        panic!("SEED_GENOME is not supported by this method, its champions cannot be seeded");
+    }
+    if !EVOLUTION.develop_genome.is_empty() {
+        let serialized =
+            fs::read_to_string(&EVOLUTION.develop_genome).expect("unable to read genome");
+        let genome = serde_json::from_str::<SerializedGenome>(&serialized)
+            .map_err(|e| e.to_string())
+            .and_then(|serialized| {
+                serialized.into_genome(
+                    &topology_init_config(&environment.description()),
+                    &mut DesState::default(),
+                )
+            })
+            .unwrap_or_else(|e| panic!("unable to load genome: {}", e));
+        let (phenotype, _) =
+            FloatDeveloper::<Developer, F>::from(environment.description()).develop(genome);
+        write_phenotype(&*environment, &phenotype);
+        return None;
+    }
 
     let module_population_config = PopulationConfig::init().unwrap();
     let module_genome_config = NeatConfig::default();
@@ -67,13 +98,14 @@ pub fn codeshyperneat<
         &blueprint_genome_init,
     );
 
-    let evaluator = MultiEvaluator::<CombinedGenome, Developer, E>::new(
+    let evaluator = MultiEvaluator::<CombinedGenome, FloatDeveloper<Developer, F>, E>::new(
         blueprints.population_config.population_size,
         if EVOLUTION.thread_count > 0 {
             EVOLUTION.thread_count
         } else {
             num_cpus::get() as u64
         },
+        environment.clone(),
     );
     let config = Config::new(
         EVOLUTION.clone(),
@@ -98,9 +130,17 @@ pub fn codeshyperneat<
         u64::MAX
     };
 
+    // Best combined genome of the last generation
+    let mut champion: Option<(f64, CombinedGenome)> = None;
+    let developer = Developer::from(environment.description());
     let start_time = SystemTime::now();
     for i in 0..iterations {
         let mut avg_fitnesses = Vec::<f64>::new();
+        champion = None;
+
+        // Combined genomes of each blueprint, kept to analyze the phenotypes of the logged best
+        let logged = <Logger as Log<BlueprintGenome>>::is_logged(&logger, i);
+        let mut developed = HashMap::<(u64, usize), Vec<CombinedGenome>>::new();
 
         let mut stats = HashMap::<
             (u64, usize),
@@ -115,14 +155,16 @@ pub fn codeshyperneat<
             let mut combined_genomes = blueprints
                 .enumerate()
                 .map(|(species_index, organism_index, organism)| {
-                    (
-                        species_index,
-                        organism_index,
-                        CombinedGenome::new(
-                            organism.genome.clone(),
-                            organism.genome.select_modules(&modules),
-                        ),
-                    )
+                    let combined_genome = CombinedGenome::new(
+                        organism.genome.clone(),
+                        organism.genome.select_modules(&modules),
+                    );
+                    debug_assert_eq!(
+                        combined_genome.validate(),
+                        Ok(()),
+                        "invalid combined genome"
+                    );
+                    (species_index, organism_index, combined_genome)
                 })
                 .collect::<Vec<(u64, usize, CombinedGenome)>>();
 
@@ -137,6 +179,15 @@ pub fn codeshyperneat<
                 (_, _, fitness, phenotype_stats, evaluation_stats),
             ) in combined_genomes.drain(..).zip(fitnesses.drain(..))
             {
+                if !matches!(&champion, Some((best, _)) if *best >= fitness) {
+                    champion = Some((fitness, combined_genome.clone()));
+                }
+                if logged {
+                    developed
+                        .entry((species_index, organism_index))
+                        .or_default()
+                        .push(combined_genome.clone());
+                }
                 if let Some(mut organism_stats) = stats.get_mut(&(species_index, organism_index)) {
                     organism_stats.fitness += fitness;
                     organism_stats.genome.push(NoStats {});
@@ -196,6 +247,18 @@ pub fn codeshyperneat<
             organism_stats.fitness =
                 organism_stats.fitness / CODESHYPERNEAT.blueprint_developments as f64;
         }
+        if let Some((key, best)) = stats
+            .iter_mut()
+            .filter(|(key, _)| developed.contains_key(key))
+            .max_by(|(_, a), (_, b)| a.fitness.partial_cmp(&b.fitness).unwrap())
+        {
+            best.phenotype = developed
+                .remove(key)
+                .unwrap()
+                .into_iter()
+                .map(|genome| developer.develop_analyzed(genome).1)
+                .collect();
+        }
         let stats = PopulationStats::new(stats.drain().map(|(_, v)| v).collect::<Vec<_>>());
 
         logger.log(i, &blueprints, &stats);
@@ -214,4 +277,18 @@ pub fn codeshyperneat<
         blueprints.state.custom.species = modules.next_id;
     }
     <Logger as Log<BlueprintGenome>>::close(&mut logger);
+
+    champion.map(|(_, genome)| {
+        if !EVOLUTION.save_champion.is_empty() {
+            let serialized = serde_json::to_string_pretty(&SerializedGenome::from(&genome))
+                .expect("unable to serialize champion");
+            write_champion(&*environment, &serialized);
+        }
+        let (phenotype, _) =
+            FloatDeveloper::<Developer, F>::from(environment.description()).develop(genome);
+        if !EVOLUTION.save_phenotype.is_empty() {
+            write_phenotype(&*environment, &phenotype);
+        }
+        phenotype
+    })
 }
@@ -1,11 +1,24 @@
 use envconfig::Envconfig;
 use lazy_static::lazy_static;
+use network::float::Precision;
 use serde::Serialize;
 
 #[derive(Envconfig, Serialize)]
 pub struct MainConfig {
     #[envconfig(from = "METHOD", default = "DES-HyperNEAT")]
     pub method: String,
+
+    /// Float type used when evaluating phenotype networks, f32 or f64
+    #[envconfig(from = "PRECISION", default = "f64")]
+    pub precision: Precision,
+
+    /// Simplify developed substrate networks before they are evaluated
+    #[envconfig(from = "SIMPLIFY_NETWORKS", default = "false")]
+    pub simplify_networks: bool,
+
+    /// Links with weights no larger than this are dropped when simplifying
+    #[envconfig(from = "SIMPLIFY_EPSILON", default = "0.0")]
+    pub simplify_epsilon: f64,
 }
 
 impl Default for MainConfig {
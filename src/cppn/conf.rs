@@ -1,6 +1,7 @@
 use envconfig::Envconfig;
+use evolution::neat::perturbation::Perturbation;
 use lazy_static::lazy_static;
-use network::activation::Activations;
+use network::{activation::Activations, aggregation::Aggregations};
 use serde::Serialize;
 
 #[derive(Envconfig, Serialize)]
@@ -14,6 +15,9 @@ pub struct MethodConfig {
     #[envconfig(from = "MUTATE_HIDDEN_ACTIVATION_PROBABILITY", default = "0.1")]
     pub mutate_hidden_activation_probability: f64,
 
+    #[envconfig(from = "MUTATE_HIDDEN_AGGREGATION_PROBABILITY", default = "0.1")]
+    pub mutate_hidden_aggregation_probability: f64,
+
     #[envconfig(from = "MUTATE_OUTPUT_BIAS_PROBABILITY", default = "0.8")]
     pub mutate_output_bias_probability: f64,
 
@@ -23,6 +27,32 @@ pub struct MethodConfig {
     #[envconfig(from = "MUTATE_OUTPUT_ACTIVATION_PROBABILITY", default = "0.1")]
     pub mutate_output_activation_probability: f64,
 
+    #[envconfig(from = "MUTATE_OUTPUT_AGGREGATION_PROBABILITY", default = "0.1")]
+    pub mutate_output_aggregation_probability: f64,
+
+    /// Mutation of the parameter of a parameterized activation, e.g. ScaledSine(2.0)
+    #[envconfig(from = "MUTATE_ACTIVATION_PARAMETER_PROBABILITY", default = "0.1")]
+    pub mutate_activation_parameter_probability: f64,
+
+    #[envconfig(from = "MUTATE_ACTIVATION_PARAMETER_SIZE", default = "0.1")]
+    pub mutate_activation_parameter_size: f64,
+
+    #[envconfig(from = "ACTIVATION_PARAMETER_PERTURBATION", default = "Uniform")]
+    pub activation_parameter_perturbation: Perturbation,
+
+    /// Bound of mutated activation parameters, e.g. so that BoundedExp cannot overflow
+    #[envconfig(from = "MAX_ACTIVATION_PARAMETER", default = "10.0")]
+    pub max_activation_parameter: f64,
+
+    #[envconfig(from = "BIAS_PERTURBATION", default = "Uniform")]
+    pub bias_perturbation: Perturbation,
+
+    #[envconfig(from = "MAX_HIDDEN_BIAS", default = "0.0")]
+    pub max_hidden_bias: f64,
+
+    #[envconfig(from = "MAX_OUTPUT_BIAS", default = "0.0")]
+    pub max_output_bias: f64,
+
     #[envconfig(
         from = "HIDDEN_ACTIVATIONS",
         default = "None Linear Step ReLU Sigmoid Tanh Gaussian OffsetGaussian Sine"
@@ -35,6 +65,13 @@ pub struct MethodConfig {
     )]
     pub output_activations: Activations,
 
+    /// How nodes combine their incoming links, e.g. "Sum Product Max"
+    #[envconfig(from = "HIDDEN_AGGREGATIONS", default = "Sum")]
+    pub hidden_aggregations: Aggregations,
+
+    #[envconfig(from = "OUTPUT_AGGREGATIONS", default = "Sum")]
+    pub output_aggregations: Aggregations,
+
     #[envconfig(from = "PAD_MISSING_OUTPUTS", default = "false")]
     pub pad_missing_outputs: bool,
 
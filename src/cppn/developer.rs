@@ -1,9 +1,11 @@
 use crate::cppn::{conf::CPPN, Genome};
 use evolution::{
-    develop::Develop, environment::EnvironmentDescription, neat::developer::NetworkStats,
+    develop::Develop,
+    environment::EnvironmentDescription,
+    neat::developer::{NetworkStats, NetworkStructure},
     neat::node::NodeRef,
 };
-use network::{connection, execute, execute::Executor};
+use network::{aggregation::Aggregation, connection, execute, execute::Executor};
 use std::collections::HashMap;
 
 pub struct Developer {}
@@ -20,7 +22,7 @@ impl Develop<Genome> for Developer {
 
     fn develop(&self, genome: Genome) -> (Self::Phenotype, Self::Stats) {
         // Sort genomes netowrk topologically
-        let order = genome.neat.connections.sort_topologically();
+        let order = genome.neat.connections.sort_topologically_stable();
 
         // Create vector of all input node indexes, for insertion of nerual network inputs
         let num_input_nodes = genome.neat.inputs.keys().map(|n| n.id()).max().unwrap() as usize + 1;
@@ -98,15 +100,34 @@ impl Develop<Genome> for Developer {
             })
             .collect::<Vec<_>>();
 
+        // Aggregation of each node, padded outputs sum. Left empty if all nodes sum.
+        let mut aggregations = nodes
+            .iter()
+            .map(|node| {
+                genome
+                    .neat
+                    .get_node(node)
+                    .map_or(Aggregation::Sum, |node| node.aggregation)
+            })
+            .collect::<Vec<_>>();
+        if aggregations.iter().all(|a| *a == Aggregation::Sum) {
+            aggregations.clear();
+        }
+
         // Create neural network executor
-        let network = Executor::create(nodes.len(), inputs, outputs, actions);
-        let stats = NetworkStats {
-            nodes: nodes.len() as u64,
-            edges: genome.neat.links.len() as u64,
-        };
+        let network =
+            Executor::create(nodes.len(), inputs, outputs, actions).with_aggregations(aggregations);
+        let stats = NetworkStats::new(nodes.len() as u64, genome.neat.links.len() as u64);
 
         (network, stats)
     }
+
+    fn develop_analyzed(&self, genome: Genome) -> (Self::Phenotype, Self::Stats) {
+        let structure = NetworkStructure::from(&genome.neat.connections);
+        let (network, mut stats) = self.develop(genome);
+        stats.structure = Some(structure);
+        (network, stats)
+    }
 }
 
 #[cfg(test)]
@@ -121,7 +142,7 @@ mod tests {
             state::{InitConfig, NeatState},
         },
     };
-    use network::activation::Activation;
+    use network::{activation::Activation, aggregation::Aggregation};
 
     #[test]
     fn test_develop() {
@@ -167,4 +188,25 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_aggregation() {
+        let mut state = NeatState::default();
+        let config = NeatConfig::default();
+        let mut genome = CppnGenome::new(&config, &InitConfig::new(2, 1), &mut state);
+        genome
+            .neat
+            .insert_link(NeatLink::new(NodeRef::Input(0), NodeRef::Output(0), 2.0, 0));
+        genome
+            .neat
+            .insert_link(NeatLink::new(NodeRef::Input(1), NodeRef::Output(0), 3.0, 1));
+        let output = genome.neat.outputs.get_mut(&NodeRef::Output(0)).unwrap();
+        output.activation = Activation::None;
+        output.aggregation = Aggregation::Product;
+
+        let mut phenotype = Developer::from(EnvironmentDescription::new(2, 1))
+            .develop(genome)
+            .0;
+        assert_eq!(phenotype.execute(&vec![0.5, -2.0]), vec![-6.0]);
+    }
 }
@@ -1,4 +1,5 @@
-use crate::cppn::genome::Genome as CppnGenome;
+use crate::cppn::{genome::Genome as CppnGenome, node::Node};
+use network::aggregation::Aggregation;
 use std::{fs::File, io::prelude::Write, path::Path};
 
 pub fn genome_to_dot<P: AsRef<Path>>(fname: P, genome: &CppnGenome) -> std::io::Result<()> {
@@ -29,7 +30,10 @@ pub fn genome_to_dot<P: AsRef<Path>>(fname: P, genome: &CppnGenome) -> std::io::
     for node in genome.neat.hidden_nodes.values() {
         let s = format!(
             "    {} [ label = \"{} {:.2} {}\"]\n",
-            node.neat.node_ref, node.neat.node_ref, node.bias, node.activation
+            node.neat.node_ref,
+            node.neat.node_ref,
+            node.bias,
+            function_label(node)
         );
         file.write_all(s.as_bytes())?;
     }
@@ -37,7 +41,10 @@ pub fn genome_to_dot<P: AsRef<Path>>(fname: P, genome: &CppnGenome) -> std::io::
     for node in genome.neat.outputs.values() {
         let s = format!(
             "    {} [ label = \"{} {:.2} {}\", shape=box, style=filled, color=\".0 .0 .85\"]\n",
-            node.neat.node_ref, node.neat.node_ref, node.bias, node.activation
+            node.neat.node_ref,
+            node.neat.node_ref,
+            node.bias,
+            function_label(node)
         );
         file.write_all(s.as_bytes())?;
     }
@@ -46,3 +53,12 @@ pub fn genome_to_dot<P: AsRef<Path>>(fname: P, genome: &CppnGenome) -> std::io::
 
     return Ok(());
 }
+
+/// Activation, preceded by the aggregation if it is not a sum
+fn function_label(node: &Node) -> String {
+    if node.aggregation == Aggregation::Sum {
+        node.activation.to_string()
+    } else {
+        format!("{} {}", node.aggregation, node.activation)
+    }
+}
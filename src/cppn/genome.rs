@@ -1,4 +1,4 @@
-use crate::cppn::{conf::CPPN, node::Node};
+use crate::cppn::{conf::CPPN, node::Node, serialize::SerializedGenome};
 use evolution::{
     genome::{GenericGenome as GenericEvolvableGenome, Genome as EvolvableGenome},
     neat::{
@@ -6,10 +6,11 @@ use evolution::{
         genome::{NeatGenome, NeatGenomeStats},
         link::NeatLink,
         node::NodeRef,
+        perturbation,
         state::{InitConfig, NeatState},
     },
 };
-use network::activation;
+use network::{activation, aggregation};
 use rand::Rng;
 
 #[derive(Clone)]
@@ -58,6 +59,10 @@ impl GenericEvolvableGenome<NeatConfig, NeatState, InitConfig, NeatGenomeStats>
             self.mutate_hidden_activation();
         }
 
+        if rng.gen::<f64>() < CPPN.mutate_hidden_aggregation_probability {
+            self.mutate_hidden_aggregation();
+        }
+
         if rng.gen::<f64>() < CPPN.mutate_output_bias_probability {
             self.mutate_output_bias();
         }
@@ -65,6 +70,14 @@ impl GenericEvolvableGenome<NeatConfig, NeatState, InitConfig, NeatGenomeStats>
         if rng.gen::<f64>() < CPPN.mutate_output_activation_probability {
             self.mutate_output_activation();
         }
+
+        if rng.gen::<f64>() < CPPN.mutate_output_aggregation_probability {
+            self.mutate_output_aggregation();
+        }
+
+        if rng.gen::<f64>() < CPPN.mutate_activation_parameter_probability {
+            self.mutate_activation_parameter();
+        }
     }
 
     fn distance(&self, config: &NeatConfig, other: &Self) -> f64 {
@@ -74,6 +87,27 @@ impl GenericEvolvableGenome<NeatConfig, NeatState, InitConfig, NeatGenomeStats>
     fn get_stats(&self) -> NeatGenomeStats {
         self.neat.get_stats()
     }
+
+    fn validate(&self) -> Result<(), String> {
+        self.neat.validate()
+    }
+
+    const SAVABLE: bool = true;
+
+    fn save(&self) -> Option<String> {
+        serde_json::to_string_pretty(&SerializedGenome::from(self)).ok()
+    }
+
+    fn load(
+        _: &NeatConfig,
+        init_config: &InitConfig,
+        serialized: &str,
+        state: &mut NeatState,
+    ) -> Result<Self, String> {
+        serde_json::from_str::<SerializedGenome>(serialized)
+            .map_err(|e| e.to_string())?
+            .into_genome(init_config, state)
+    }
 }
 
 impl Genome {
@@ -85,6 +119,14 @@ impl Genome {
         }
     }
 
+    pub fn get_aggregation(&self, node_ref: &NodeRef) -> aggregation::Aggregation {
+        match node_ref {
+            NodeRef::Input(_) => self.neat.inputs.get(node_ref).unwrap().aggregation,
+            NodeRef::Hidden(_) => self.neat.hidden_nodes.get(node_ref).unwrap().aggregation,
+            NodeRef::Output(_) => self.neat.outputs.get(node_ref).unwrap().aggregation,
+        }
+    }
+
     pub fn get_bias(&self, node_ref: &NodeRef) -> f64 {
         match node_ref {
             NodeRef::Input(_) => self.neat.inputs.get(node_ref).unwrap().bias,
@@ -98,8 +140,12 @@ impl Genome {
 
         if !self.neat.hidden_nodes.is_empty() {
             let link_index = rng.gen_range(0, self.neat.hidden_nodes.len());
-            if let Some(node) = self.neat.hidden_nodes.values_mut().skip(link_index).next() {
-                node.bias += (rng.gen::<f64>() - 0.5) * 2.0 * CPPN.mutate_hidden_bias_size;
+            if let Some(node) = self.neat.hidden_nodes.values_mut().nth(link_index) {
+                let bias = node.bias
+                    + CPPN
+                        .bias_perturbation
+                        .sample(&mut rng, CPPN.mutate_hidden_bias_size);
+                node.bias = perturbation::clamp(bias, CPPN.max_hidden_bias);
             }
         }
     }
@@ -115,13 +161,28 @@ impl Genome {
         }
     }
 
+    fn mutate_hidden_aggregation(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        if !self.neat.hidden_nodes.is_empty() {
+            let node_index = rng.gen_range(0, self.neat.hidden_nodes.len());
+            if let Some(node) = self.neat.hidden_nodes.values_mut().nth(node_index) {
+                node.aggregation = CPPN.hidden_aggregations.random();
+            }
+        }
+    }
+
     fn mutate_output_bias(&mut self) {
         let mut rng = rand::thread_rng();
 
         if !self.neat.outputs.is_empty() {
             let link_index = rng.gen_range(0, self.neat.outputs.len());
-            if let Some(node) = self.neat.outputs.values_mut().skip(link_index).next() {
-                node.bias += (rng.gen::<f64>() - 0.5) * 2.0 * CPPN.mutate_output_bias_size;
+            if let Some(node) = self.neat.outputs.values_mut().nth(link_index) {
+                let bias = node.bias
+                    + CPPN
+                        .bias_perturbation
+                        .sample(&mut rng, CPPN.mutate_output_bias_size);
+                node.bias = perturbation::clamp(bias, CPPN.max_output_bias);
             }
         }
     }
@@ -136,4 +197,40 @@ impl Genome {
             }
         }
     }
+
+    fn mutate_output_aggregation(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        if !self.neat.outputs.is_empty() {
+            let node_index = rng.gen_range(0, self.neat.outputs.len());
+            if let Some(node) = self.neat.outputs.values_mut().nth(node_index) {
+                node.aggregation = CPPN.output_aggregations.random();
+            }
+        }
+    }
+
+    /// Perturb the parameter of a hidden or output node with a parameterized activation
+    fn mutate_activation_parameter(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        let mut nodes = self
+            .neat
+            .hidden_nodes
+            .values_mut()
+            .chain(self.neat.outputs.values_mut())
+            .filter(|node| node.activation.parameter().is_some())
+            .collect::<Vec<_>>();
+        if !nodes.is_empty() {
+            let node_index = rng.gen_range(0, nodes.len());
+            let node = &mut nodes[node_index];
+            let parameter = node.activation.parameter().unwrap()
+                + CPPN
+                    .activation_parameter_perturbation
+                    .sample(&mut rng, CPPN.mutate_activation_parameter_size);
+            node.activation = node.activation.with_parameter(perturbation::clamp(
+                parameter,
+                CPPN.max_activation_parameter,
+            ));
+        }
+    }
 }
@@ -35,4 +35,8 @@ impl log::Log<Genome> for Logger {
     fn close(&mut self) {
         <log::Logger as log::Log<Genome>>::close(&mut self.default_logger);
     }
+
+    fn is_logged(&self, iteration: u64) -> bool {
+        <log::Logger as log::Log<Genome>>::is_logged(&self.default_logger, iteration)
+    }
 }
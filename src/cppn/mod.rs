@@ -2,6 +2,7 @@ use conf::MethodConfig;
 use developer::Developer;
 use evolution::{
     algorithm::Algorithm,
+    develop::FloatDeveloper,
     environment::Environment,
     environment::EnvironmentDescription,
     evolve,
@@ -9,8 +10,9 @@ use evolution::{
 };
 use genome::Genome;
 use log::Logger;
-use network::execute::Executor;
+use network::{execute::Executor, float::Float};
 use serde::Serialize;
+use std::sync::Arc;
 
 pub mod conf;
 pub mod developer;
@@ -18,13 +20,14 @@ pub mod dot;
 pub mod genome;
 pub mod log;
 pub mod node;
+pub mod serialize;
 
 pub struct Cppn;
 
-impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Cppn {
+impl<F: Float, E: Environment<Phenotype = Executor<F>>> Algorithm<E> for Cppn {
     type Config = MethodConfig;
     type Genome = Genome;
-    type Developer = Developer;
+    type Developer = FloatDeveloper<Developer, F>;
 
     fn genome_config(_: &EnvironmentDescription) -> NeatConfig {
         NeatConfig::default()
@@ -35,6 +38,8 @@ impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Cppn {
     }
 }
 
-pub fn cppn<E: Environment<Phenotype = Executor> + Default + 'static, C: Serialize + Default>() {
-    evolve::<E, Cppn, Logger, C>();
+pub fn cppn<F: Float, E: Environment<Phenotype = Executor<F>> + 'static, C: Serialize + Default>(
+    environment: Arc<E>,
+) -> Option<Executor<F>> {
+    evolve::<E, Cppn, Logger, C>(environment)
 }
@@ -3,7 +3,7 @@ use evolution::neat::{
     genome::GetNeat,
     node::{NeatNode, NodeExtension, NodeRef},
 };
-use network::activation::Activation;
+use network::{activation::Activation, aggregation::Aggregation};
 use rand::Rng;
 
 #[derive(Clone, GetNeat)]
@@ -11,6 +11,7 @@ pub struct Node {
     #[neat]
     pub neat: NeatNode,
     pub activation: Activation,
+    pub aggregation: Aggregation,
     pub bias: f64,
 }
 
@@ -27,6 +28,11 @@ impl NodeExtension for Node {
                 NodeRef::Hidden(_) => CPPN.hidden_activations.random(),
                 NodeRef::Output(_) => CPPN.output_activations.random(),
             },
+            aggregation: match neat.node_ref {
+                NodeRef::Input(_) => Aggregation::Sum,
+                NodeRef::Hidden(_) => CPPN.hidden_aggregations.random(),
+                NodeRef::Output(_) => CPPN.output_aggregations.random(),
+            },
         }
     }
 
@@ -45,12 +51,23 @@ impl NodeExtension for Node {
             } else {
                 other.activation
             },
+            aggregation: if rand::thread_rng().gen::<bool>() {
+                self.aggregation
+            } else {
+                other.aggregation
+            },
         }
     }
 
     fn distance(&self, _: &Self::Config, other: &Self) -> f64 {
         let mut distance = self.neat.distance(&other.neat);
-        distance += 0.5 * ((self.activation != other.activation) as u8) as f64;
+        distance += match (self.activation.parameter(), other.activation.parameter()) {
+            (Some(a), Some(b)) if self.activation.same_function(&other.activation) => {
+                0.5 * (a - b).abs().tanh()
+            }
+            _ => 0.5 * ((self.activation != other.activation) as u8) as f64,
+        };
+        distance += 0.5 * ((self.aggregation != other.aggregation) as u8) as f64;
         distance += 0.5 * (self.bias - other.bias).abs().tanh();
         distance
     }
@@ -0,0 +1,257 @@
+use crate::cppn::{genome::Genome, node::Node};
+use evolution::{
+    genome::GenericGenome,
+    neat::{
+        genome::NeatGenome,
+        link::NeatLink,
+        node::{NeatNode, NodeExtension, NodeRef},
+        state::{InitConfig, NeatState},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// JSON form of a cppn, written for saved champions and suitable for hand-written seeds.
+/// Nodes are referred to as I0, H0 and O0. Input and output nodes left out of the file
+/// are created with default properties, as are activations and aggregations left out of a node.
+#[derive(Serialize, Deserialize)]
+pub struct SerializedGenome {
+    pub nodes: Vec<SerializedNode>,
+    pub links: Vec<SerializedLink>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedNode {
+    pub node: String,
+    #[serde(default)]
+    pub activation: Option<String>,
+    #[serde(default)]
+    pub aggregation: Option<String>,
+    #[serde(default)]
+    pub bias: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedLink {
+    pub from: String,
+    pub to: String,
+    pub weight: f64,
+}
+
+impl From<&Genome> for SerializedGenome {
+    fn from(genome: &Genome) -> Self {
+        let mut node_refs = genome
+            .neat
+            .inputs
+            .keys()
+            .chain(genome.neat.hidden_nodes.keys())
+            .chain(genome.neat.outputs.keys())
+            .cloned()
+            .collect::<Vec<NodeRef>>();
+        node_refs.sort();
+        let mut link_refs = genome.neat.links.keys().cloned().collect::<Vec<_>>();
+        link_refs.sort();
+
+        Self {
+            nodes: node_refs
+                .iter()
+                .map(|node_ref| SerializedNode {
+                    node: node_ref.to_string(),
+                    activation: Some(genome.get_activation(node_ref).to_string()),
+                    aggregation: Some(genome.get_aggregation(node_ref).to_string()),
+                    bias: genome.get_bias(node_ref),
+                })
+                .collect(),
+            links: link_refs
+                .iter()
+                .map(|(from, to)| SerializedLink {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    weight: genome.neat.links[&(*from, *to)].weight,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SerializedGenome {
+    /// Build a genome for an environment of the given size. Hidden nodes get ids that are
+    /// unused in the state, and links are registered as innovations in the state.
+    pub fn into_genome(
+        self,
+        init_config: &InitConfig,
+        state: &mut NeatState,
+    ) -> Result<Genome, String> {
+        let mut neat = NeatGenome::<Node, NeatLink>::empty();
+        let mut hidden_ids = HashMap::<NodeRef, NodeRef>::new();
+
+        for serialized in self.nodes.iter() {
+            let node_ref = match serialized.node.parse::<NodeRef>()? {
+                NodeRef::Input(id) if id >= init_config.inputs => {
+                    return Err(format!(
+                        "input {} is not in the environment",
+                        serialized.node
+                    ))
+                }
+                NodeRef::Output(id) if id >= init_config.outputs => {
+                    return Err(format!(
+                        "output {} is not in the environment",
+                        serialized.node
+                    ))
+                }
+                NodeRef::Hidden(id) => {
+                    let new_ref = NodeRef::Hidden(state.next_innovation.node_number);
+                    state.next_innovation.node_number += 1;
+                    hidden_ids.insert(NodeRef::Hidden(id), new_ref);
+                    new_ref
+                }
+                node_ref => node_ref,
+            };
+
+            let mut node = Node::new(&(), NeatNode::new(node_ref), &mut ());
+            if let Some(activation) = &serialized.activation {
+                node.activation = activation
+                    .parse()
+                    .map_err(|_| format!("cannot parse activation {}", activation))?;
+            }
+            if let Some(aggregation) = &serialized.aggregation {
+                node.aggregation = aggregation
+                    .parse()
+                    .map_err(|_| format!("cannot parse aggregation {}", aggregation))?;
+            }
+            node.bias = serialized.bias;
+
+            let previous = match node_ref {
+                NodeRef::Input(_) => neat.inputs.insert(node_ref, node),
+                NodeRef::Hidden(_) => neat.hidden_nodes.insert(node_ref, node),
+                NodeRef::Output(_) => neat.outputs.insert(node_ref, node),
+            };
+            if previous.is_some() {
+                return Err(format!("duplicate node {}", serialized.node));
+            }
+        }
+
+        for id in 0..init_config.inputs {
+            let node_ref = NodeRef::Input(id);
+            neat.inputs
+                .entry(node_ref)
+                .or_insert_with(|| Node::new(&(), NeatNode::new(node_ref), &mut ()));
+        }
+        for id in 0..init_config.outputs {
+            let node_ref = NodeRef::Output(id);
+            neat.outputs
+                .entry(node_ref)
+                .or_insert_with(|| Node::new(&(), NeatNode::new(node_ref), &mut ()));
+        }
+
+        let resolve = |node: &String| -> Result<NodeRef, String> {
+            let unknown = || format!("link refers to unknown node {}", node);
+            match node.parse::<NodeRef>()? {
+                // Hidden nodes are renamed, so their ids only refer to declared nodes
+                node_ref @ NodeRef::Hidden(_) => {
+                    hidden_ids.get(&node_ref).cloned().ok_or_else(unknown)
+                }
+                node_ref if neat.get_node(&node_ref).is_some() => Ok(node_ref),
+                _ => Err(unknown()),
+            }
+        };
+        let link_refs = self
+            .links
+            .iter()
+            .map(|link| Ok((resolve(&link.from)?, resolve(&link.to)?, link.weight)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        for (from, to, weight) in link_refs {
+            if neat.links.contains_key(&(from, to)) {
+                return Err(format!("duplicate link {}->{}", from, to));
+            }
+            if neat.connections.creates_cycle(from, to) {
+                return Err(format!("link {}->{} creates a cycle", from, to));
+            }
+            let innovation = state.get_connect_innovation(from, to);
+            neat.insert_link(NeatLink::new(from, to, weight, innovation));
+        }
+
+        let genome = Genome { neat };
+        genome.validate()?;
+        Ok(genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cppn::developer::Developer;
+    use crate::eshyperneat::genome::identity_genome;
+    use evolution::{develop::Develop, environment::EnvironmentDescription};
+
+    #[test]
+    fn test_round_trip() {
+        let (genome, _) = identity_genome();
+        let json = serde_json::to_string(&SerializedGenome::from(&genome)).unwrap();
+        let mut state = NeatState::default();
+        let loaded = serde_json::from_str::<SerializedGenome>(&json)
+            .unwrap()
+            .into_genome(&InitConfig::new(4, 2), &mut state)
+            .unwrap();
+        assert_eq!(
+            loaded.neat.hidden_nodes.len(),
+            genome.neat.hidden_nodes.len()
+        );
+        assert_eq!(loaded.neat.links.len(), genome.neat.links.len());
+
+        let developer = Developer::from(EnvironmentDescription::new(4, 2));
+        let mut original = developer.develop(genome).0;
+        let mut loaded = developer.develop(loaded).0;
+        for inputs in [[0.1, -0.4, 0.3, 0.2], [-0.7, 0.5, 0.0, 0.9]].iter() {
+            let inputs = inputs.to_vec();
+            assert_eq!(original.execute(&inputs), loaded.execute(&inputs));
+        }
+    }
+
+    #[test]
+    fn test_hand_written() {
+        let json = r#"{
+            "nodes": [
+                {"node": "H7", "activation": "Gaussian"},
+                {"node": "O0", "activation": "Linear", "bias": 0.5}
+            ],
+            "links": [
+                {"from": "I1", "to": "H7", "weight": 2.0},
+                {"from": "H7", "to": "O0", "weight": -1.0}
+            ]
+        }"#;
+        let mut state = NeatState::default();
+        state.next_innovation.node_number = 3;
+        let genome = serde_json::from_str::<SerializedGenome>(json)
+            .unwrap()
+            .into_genome(&InitConfig::new(2, 1), &mut state)
+            .unwrap();
+
+        assert_eq!(genome.neat.inputs.len(), 2);
+        assert!(genome.neat.hidden_nodes.contains_key(&NodeRef::Hidden(3)));
+        assert_eq!(
+            genome.neat.links[&(NodeRef::Hidden(3), NodeRef::Output(0))].weight,
+            -1.0
+        );
+        assert_eq!(genome.get_bias(&NodeRef::Output(0)), 0.5);
+
+        let cycle = r#"{"nodes": [{"node": "H0"}, {"node": "H1"}], "links": [
+            {"from": "H0", "to": "H1", "weight": 1.0},
+            {"from": "H1", "to": "H0", "weight": 1.0}
+        ]}"#;
+        let unknown = r#"{"nodes": [], "links": [{"from": "I0", "to": "H4", "weight": 1.0}]}"#;
+        let too_many_inputs = r#"{"nodes": [{"node": "I5"}], "links": []}"#;
+        // H7 is renamed to H3, which must not make the undeclared H3 refer to it
+        let renamed = r#"{"nodes": [{"node": "H7"}], "links": [
+            {"from": "H3", "to": "O0", "weight": 1.0}
+        ]}"#;
+        for json in [cycle, unknown, too_many_inputs, renamed].iter() {
+            state.next_innovation.node_number = 3;
+            assert!(serde_json::from_str::<SerializedGenome>(json)
+                .unwrap()
+                .into_genome(&InitConfig::new(2, 1), &mut state)
+                .is_err());
+        }
+    }
+}
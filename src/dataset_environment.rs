@@ -1,15 +1,32 @@
-use data::{accuracy, conf::DatasetConfig, dataset::Dataset, error};
+use data::{
+    accuracy,
+    conf::{DatasetConfig, DATA},
+    dataset::Dataset,
+    matrix::Matrix,
+    metric::{parse_metrics, Metric, Task},
+};
 use evolution::{
     environment::{Environment, EnvironmentDescription},
     stats::Stats,
 };
-use network::execute::Executor;
+use network::{activation::GroupActivation, execute::Executor, float::Float};
 use serde::Serialize;
-use std::fmt::{Display, Formatter, Result};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Result},
+    marker::PhantomData,
+};
 
-pub struct DatasetEnvironment {
+/// Evaluates networks with values and weights of float type F on a dataset
+pub struct DatasetEnvironment<F: Float = f64> {
     dataset: Dataset,
+    /// Test fold in cross-validation
+    fold: Option<u64>,
     description: EnvironmentDescription,
+    task: Task,
+    fitness: Metric,
+    metrics: Vec<Metric>,
+    float: PhantomData<F>,
 }
 
 #[derive(Serialize)]
@@ -17,6 +34,9 @@ pub struct DatasetStats {
     validation_fitness: f64,
     training_accuracy: f64,
     validation_accuracy: f64,
+    /// METRICS of the validation split
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    validation_metrics: BTreeMap<String, f64>,
 }
 
 impl Display for DatasetStats {
@@ -25,27 +45,154 @@ impl Display for DatasetStats {
             f,
             "Val fitness: {} \t Tr acc: {} \t Val acc: {}",
             self.validation_fitness, self.training_accuracy, self.validation_accuracy
-        )
+        )?;
+        for (name, value) in self.validation_metrics.iter() {
+            write!(f, " \t Val {}: {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluation of a phenotype on the test split
+pub struct TestReport {
+    pub fitness: f64,
+    pub accuracy: f64,
+    pub metrics: Vec<(Metric, f64)>,
+    /// Samples of each target class, by row, predicted as each class, by column
+    pub confusion_matrix: Option<Vec<Vec<usize>>>,
+}
+
+impl Display for TestReport {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f,
+            "Test fitness: {} \t Test acc: {}",
+            self.fitness, self.accuracy
+        )?;
+        for (metric, value) in self.metrics.iter() {
+            write!(f, " \t Test {}: {}", metric, value)?;
+        }
+        if let Some(matrix) = &self.confusion_matrix {
+            write!(
+                f,
+                "\nConfusion matrix (target by row, prediction by column):"
+            )?;
+            for row in matrix.iter() {
+                write!(f, "\n{:?}", row)?;
+            }
+        }
+        Ok(())
     }
 }
 
 impl Stats for DatasetStats {}
 
-impl Default for DatasetEnvironment {
-    fn default() -> DatasetEnvironment {
-        let dataset = Dataset::load();
-        let description =
+impl<F: Float> DatasetEnvironment<F> {
+    /// Environment of the configured dataset, testing on the given fold in cross-validation
+    pub fn new(fold: Option<u64>) -> DatasetEnvironment<F> {
+        let dataset = Dataset::load(fold.unwrap_or(0));
+        let mut description =
             EnvironmentDescription::new(dataset.dimensions.inputs, dataset.dimensions.outputs);
+        description.input_grid = dataset
+            .image
+            .map(|image| (image.width as u64, image.height as u64));
+        description.one_hot_output = dataset.is_classification && dataset.one_hot_output;
+
+        let task = Task {
+            is_classification: dataset.is_classification,
+            one_hot_output: dataset.one_hot_output,
+            huber_delta: DATA.huber_delta,
+        };
+        let fitness = DATA
+            .fitness
+            .resolve(&task, true)
+            .unwrap_or_else(|error| panic!("invalid fitness: {}", error))[0];
+        let metrics = parse_metrics(&DATA.metrics, &task)
+            .unwrap_or_else(|error| panic!("invalid metrics: {}", error));
 
         DatasetEnvironment {
             dataset,
+            fold,
             description,
+            task,
+            fitness,
+            metrics,
+            float: PhantomData,
         }
     }
-}
 
-impl DatasetEnvironment {
-    fn accuracy(&self, targets: &Vec<Vec<f64>>, predictions: &Vec<Vec<f64>>) -> f64 {
+    /// Evaluation on the test split, if there are test samples
+    pub fn test(&self, executor: &mut Executor<F>) -> Option<TestReport> {
+        if self.dataset.test_count == 0 {
+            return None;
+        }
+        let targets = &self.dataset.test_targets;
+        let (test_pred, _, probabilities) =
+            self.predict(executor, &self.dataset.test_inputs, &Matrix::default());
+        Some(TestReport {
+            fitness: self.fitness(targets, &test_pred, probabilities),
+            accuracy: self.accuracy(targets, &test_pred),
+            metrics: self
+                .metrics
+                .iter()
+                .map(|metric| {
+                    let value = metric.measure(&self.task, targets, &test_pred, probabilities);
+                    (*metric, value)
+                })
+                .collect(),
+            confusion_matrix: if self.dataset.is_classification {
+                Some(accuracy::confusion_matrix(
+                    targets,
+                    &test_pred,
+                    self.dataset.one_hot_output,
+                ))
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Predictions for two sets of inputs, and whether they are probabilities
+    fn predict(
+        &self,
+        executor: &mut Executor<F>,
+        first: &Matrix,
+        second: &Matrix,
+    ) -> (Matrix, Matrix, bool) {
+        let mut predictions = (
+            Matrix::zeros(first.len(), executor.outputs.len()),
+            Matrix::zeros(second.len(), executor.outputs.len()),
+        );
+        let (first_predictions, second_predictions) = &mut predictions;
+        executor.execute_rows_into(
+            first.as_slice(),
+            first.len(),
+            first.columns(),
+            first_predictions.as_mut_slice(),
+        );
+        executor.execute_rows_into(
+            second.as_slice(),
+            second.len(),
+            second.columns(),
+            second_predictions.as_mut_slice(),
+        );
+        let (mut first, mut second) = predictions;
+
+        // Log-probabilities are converted to probabilities for the fitness functions
+        if executor.output_activation == GroupActivation::LogSoftmax {
+            for value in first
+                .as_mut_slice()
+                .iter_mut()
+                .chain(second.as_mut_slice().iter_mut())
+            {
+                *value = value.exp();
+            }
+        }
+        let probabilities = executor.output_activation != GroupActivation::None;
+        (first, second, probabilities)
+    }
+
+    fn accuracy(&self, targets: &Matrix, predictions: &Matrix) -> f64 {
         if !self.dataset.is_classification {
             0.0
         } else if self.dataset.one_hot_output {
@@ -55,49 +202,64 @@ impl DatasetEnvironment {
         }
     }
 
-    fn fitness(&self, targets: &Vec<Vec<f64>>, predictions: &Vec<Vec<f64>>) -> f64 {
-        let norm = self.dataset.is_classification && self.dataset.one_hot_output;
-
-        if self.dataset.is_classification && self.dataset.one_hot_output {
-            (-error::crossentropy(targets, predictions, norm)).exp()
-        } else {
-            let e = 1.0 - error::mse(targets, predictions, norm);
-            if e.is_finite() {
-                e
-            } else {
-                0.0
-            }
-        }
+    /// Predictions that are already probabilities, from a softmax output, are not normalized
+    fn fitness(&self, targets: &Matrix, predictions: &Matrix, probabilities: bool) -> f64 {
+        self.fitness
+            .fitness(&self.task, targets, predictions, probabilities)
     }
 }
 
-impl Environment for DatasetEnvironment {
+impl<F: Float> Environment for DatasetEnvironment<F> {
     type Config = DatasetConfig;
-    type Phenotype = Executor;
+    type Phenotype = Executor<F>;
     type Stats = DatasetStats;
 
     fn description(&self) -> EnvironmentDescription {
         self.description.clone()
     }
 
-    fn evaluate(&self, executor: &mut Executor) -> (f64, DatasetStats) {
-        let tr_pred = self
-            .dataset
-            .training_inputs
-            .iter()
-            .map(|input| executor.execute(input))
-            .collect::<Vec<Vec<_>>>();
-        let val_pred = self
-            .dataset
-            .validation_inputs
-            .iter()
-            .map(|input| executor.execute(input))
-            .collect::<Vec<Vec<_>>>();
+    fn preprocessing(&self) -> Option<String> {
+        if self.dataset.preprocessing.is_identity() {
+            None
+        } else {
+            Some(
+                serde_json::to_string_pretty(&self.dataset.preprocessing)
+                    .expect("unable to serialize preprocessing"),
+            )
+        }
+    }
+
+    fn save_suffix(&self) -> Option<String> {
+        self.fold.map(|fold| format!("fold{}", fold))
+    }
 
-        let training_fitness = self.fitness(&self.dataset.training_targets, &tr_pred);
-        let validation_fitness = self.fitness(&self.dataset.validation_targets, &val_pred);
+    fn evaluate(&self, executor: &mut Executor<F>) -> (f64, DatasetStats) {
+        let (tr_pred, val_pred, probabilities) = self.predict(
+            executor,
+            &self.dataset.training_inputs,
+            &self.dataset.validation_inputs,
+        );
+
+        let training_fitness =
+            self.fitness(&self.dataset.training_targets, &tr_pred, probabilities);
+        let validation_fitness =
+            self.fitness(&self.dataset.validation_targets, &val_pred, probabilities);
         let training_accuracy = self.accuracy(&self.dataset.training_targets, &tr_pred);
         let validation_accuracy = self.accuracy(&self.dataset.validation_targets, &val_pred);
+        let validation_metrics = self
+            .metrics
+            .iter()
+            .filter(|_| self.dataset.validation_count > 0)
+            .map(|metric| {
+                let value = metric.measure(
+                    &self.task,
+                    &self.dataset.validation_targets,
+                    &val_pred,
+                    probabilities,
+                );
+                (metric.to_string(), value)
+            })
+            .collect();
 
         (
             training_fitness,
@@ -105,6 +267,7 @@ impl Environment for DatasetEnvironment {
                 validation_fitness,
                 training_accuracy,
                 validation_accuracy,
+                validation_metrics,
             },
         )
     }
@@ -1,5 +1,9 @@
 use envconfig::Envconfig;
-use evolution::neat::conf::{ConfigProvider, NeatConfig};
+use evolution::neat::{
+    conf::{ConfigProvider, NeatConfig},
+    perturbation::Perturbation,
+    topology::InitialTopology,
+};
 use lazy_static::lazy_static;
 use serde::Serialize;
 
@@ -8,7 +12,8 @@ pub struct MethodConfig {
     #[envconfig(from = "SINGLE_CPPN_STATE", default = "false")]
     pub single_cppn_state: bool,
 
-    #[envconfig(from = "INPUT_CONFIG", default = "line")]
+    /// Input layout: line, grid, auto for a grid if the inputs form an image, or JSON points
+    #[envconfig(from = "INPUT_CONFIG", default = "auto")]
     pub input_config: String,
 
     #[envconfig(from = "OUTPUT_CONFIG", default = "line")]
@@ -40,6 +45,18 @@ pub struct MethodConfig {
 
     #[envconfig(from = "STATIC_SUBSTRATE_DEPTH", default = "-1")]
     pub static_substrate_depth: i64,
+
+    #[envconfig(from = "SPLIT_INPUT_SUBSTRATE_PROBABILITY", default = "0.0")]
+    pub split_input_substrate_probability: f64,
+
+    #[envconfig(from = "MERGE_INPUT_SUBSTRATES_PROBABILITY", default = "0.0")]
+    pub merge_input_substrates_probability: f64,
+
+    #[envconfig(from = "SPLIT_OUTPUT_SUBSTRATE_PROBABILITY", default = "0.0")]
+    pub split_output_substrate_probability: f64,
+
+    #[envconfig(from = "MERGE_OUTPUT_SUBSTRATES_PROBABILITY", default = "0.0")]
+    pub merge_output_substrates_probability: f64,
 }
 
 #[derive(Envconfig, Clone, Serialize)]
@@ -73,6 +90,25 @@ pub struct TopologyConfig {
 
     #[envconfig(from = "LAYOUT_MUTATE_ONLY_ONE_LINK", default = "true")]
     pub mutate_only_one_link: bool,
+
+    #[envconfig(from = "LAYOUT_MUTATE_EACH_LINK_PROBABILITY", default = "1.0")]
+    pub mutate_each_link_probability: f64,
+
+    #[envconfig(from = "LAYOUT_WEIGHT_PERTURBATION", default = "Uniform")]
+    pub weight_perturbation: Perturbation,
+
+    #[envconfig(from = "LAYOUT_MAX_LINK_WEIGHT", default = "0.0")]
+    pub max_link_weight: f64,
+
+    #[envconfig(from = "LAYOUT_INITIAL_TOPOLOGY", default = "Empty")]
+    pub initial_topology: InitialTopology,
+
+    #[envconfig(from = "LAYOUT_INITIAL_LINK_DENSITY", default = "0.5")]
+    pub initial_link_density: f64,
+
+    #[serde(with = "bson::compat::u2f")]
+    #[envconfig(from = "LAYOUT_INITIAL_HIDDEN_NODES", default = "1")]
+    pub initial_hidden_nodes: u64,
 }
 
 impl Default for MethodConfig {
@@ -113,6 +149,12 @@ impl Default for GenomeConfig {
         topology.only_hidden_node_distance = topology_conf.only_hidden_node_distance;
         topology.link_distance_weight = topology_conf.link_distance_weight;
         topology.mutate_only_one_link = topology_conf.mutate_only_one_link;
+        topology.mutate_each_link_probability = topology_conf.mutate_each_link_probability;
+        topology.weight_perturbation = topology_conf.weight_perturbation;
+        topology.max_link_weight = topology_conf.max_link_weight;
+        topology.initial_topology = topology_conf.initial_topology;
+        topology.initial_link_density = topology_conf.initial_link_density;
+        topology.initial_hidden_nodes = topology_conf.initial_hidden_nodes;
 
         GenomeConfig { cppn, topology }
     }
@@ -14,6 +14,10 @@ pub trait DesGenome {
     fn get_node_cppn(&self, node: &NodeRef) -> &CppnGenome;
     fn get_link_cppn(&self, source: NodeRef, target: NodeRef) -> &CppnGenome;
     fn get_depth(&self, node: &NodeRef) -> u64;
+    /// Groups of INPUT_CONFIG or OUTPUT_CONFIG covered by an I/O substrate
+    fn get_substrates(&self, node: &NodeRef) -> Vec<u64> {
+        vec![node.id()]
+    }
     fn get_neat(&self) -> &NeatGenome<Self::Node, Self::Link>;
 }
 
@@ -40,6 +44,10 @@ impl DesGenome for Genome {
         }
     }
 
+    fn get_substrates(&self, node: &NodeRef) -> Vec<u64> {
+        self.neat.get_node(node).unwrap().substrates.clone()
+    }
+
     fn get_neat(&self) -> &NeatGenome<Self::Node, Self::Link> {
         &self.neat
     }
@@ -1,15 +1,22 @@
+use crate::conf::CONF;
 use crate::cppn::developer::Developer as CppnDeveloper;
 use crate::deshyperneat::{conf::DESHYPERNEAT, desgenome::DesGenome};
 use crate::eshyperneat::{conf::ESHYPERNEAT, search};
-use crate::hyperneat::substrate;
+use crate::hyperneat::{developer::output_activation, substrate};
 use bson;
 use evolution::{
     develop::Develop,
     environment::EnvironmentDescription,
-    neat::{developer::NetworkStats, genome::GetNeat, node::NodeRef, state::InitConfig},
+    neat::{
+        developer::{NetworkStats, NetworkStructure},
+        genome::GetNeat,
+        node::NodeRef,
+        state::InitConfig,
+    },
     stats::Stats,
 };
 use network::{
+    activation::GroupActivation,
     connection,
     execute::{Action, Executor},
 };
@@ -21,59 +28,49 @@ pub struct Developer {
     cppn_developer: CppnDeveloper,
     input_nodes: Vec<Vec<(i64, i64)>>,
     output_nodes: Vec<Vec<(i64, i64)>>,
-    output_nodes_hash: Vec<HashSet<(i64, i64)>>,
-    flattened_inputs: Vec<(NodeRef, i64, i64)>,
-    flattened_outputs: Vec<(NodeRef, i64, i64)>,
-    flattened_inputs_hash: HashSet<(NodeRef, i64, i64)>,
-    flattened_outputs_hash: HashSet<(NodeRef, i64, i64)>,
+    output_activation: GroupActivation,
 }
 
 impl From<EnvironmentDescription> for Developer {
     fn from(description: EnvironmentDescription) -> Self {
         let r = ESHYPERNEAT.resolution as f64;
-        let input_nodes = parse_nodes(&DESHYPERNEAT.input_config, r, description.inputs);
-        let output_nodes = parse_nodes(&DESHYPERNEAT.output_config, r, description.outputs);
-
-        let flattened_inputs = input_nodes
-            .iter()
-            .enumerate()
-            .flat_map(|(i, nodes)| {
-                nodes
-                    .iter()
-                    .map(move |node| (NodeRef::Input(i as u64), node.0, node.1))
-            })
-            .collect::<Vec<(NodeRef, i64, i64)>>();
-        let flattened_outputs = output_nodes
-            .iter()
-            .enumerate()
-            .flat_map(|(i, nodes)| {
-                nodes
-                    .iter()
-                    .map(move |node| (NodeRef::Output(i as u64), node.0, node.1))
-            })
-            .collect::<Vec<(NodeRef, i64, i64)>>();
+        let input_nodes = parse_nodes(
+            &DESHYPERNEAT.input_config,
+            r,
+            description.inputs,
+            description.input_grid,
+        );
+        let output_nodes = parse_nodes(&DESHYPERNEAT.output_config, r, description.outputs, None);
 
         Self {
             cppn_developer: CppnDeveloper::from(description),
             input_nodes,
-            output_nodes_hash: output_nodes
-                .iter()
-                .map(|nodes| nodes.iter().cloned().collect::<HashSet<_>>())
-                .collect::<Vec<_>>(),
             output_nodes,
-            flattened_inputs_hash: flattened_inputs.iter().cloned().collect::<HashSet<_>>(),
-            flattened_outputs_hash: flattened_outputs.iter().cloned().collect::<HashSet<_>>(),
-            flattened_inputs,
-            flattened_outputs,
+            output_activation: output_activation(&description, ESHYPERNEAT.output_group_activation),
         }
     }
 }
 
+type AssembledConnections = connection::Connections<(NodeRef, i64, i64), f64>;
+
+/// Placement of I/O nodes, given how a genome partitions the input and output groups into substrates
+struct Layout {
+    substrates: HashMap<NodeRef, Vec<(i64, i64)>>,
+    substrates_hash: HashMap<NodeRef, HashSet<(i64, i64)>>,
+    flattened_inputs: Vec<(NodeRef, i64, i64)>,
+    flattened_outputs: Vec<(NodeRef, i64, i64)>,
+    flattened_inputs_hash: HashSet<(NodeRef, i64, i64)>,
+    flattened_outputs_hash: HashSet<(NodeRef, i64, i64)>,
+}
+
 #[derive(Serialize, new)]
 pub struct MultiSubstrateNetworkStats {
     #[serde(with = "bson::compat::u2f")]
     pub hidden_substrates: u64,
     pub hidden_substrate_node_counts: Vec<i64>,
+    /// Structure of the links between substrates, only analyzed for logged networks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub substrate_structure: Option<NetworkStructure>,
     pub network_stats: NetworkStats,
 }
 
@@ -88,30 +85,61 @@ impl Developer {
         let mut genome = genome;
         genome.init_desgenome();
 
+        let layout = self.layout(&genome);
+        let (mut assembled_connections, _) = self.assemble(&genome, &layout);
+
+        assembled_connections.prune(&layout.flattened_inputs, &layout.flattened_outputs, false);
+        assembled_connections
+    }
+
+    /// Place the configured input and output groups in the genome's I/O substrates
+    fn layout<G: DesGenome>(&self, genome: &G) -> Layout {
+        let flattened_inputs = flatten(genome, genome.get_neat().inputs.keys(), &self.input_nodes);
+        let flattened_outputs =
+            flatten(genome, genome.get_neat().outputs.keys(), &self.output_nodes);
+
+        let mut substrates = genome
+            .get_neat()
+            .inputs
+            .keys()
+            .chain(genome.get_neat().outputs.keys())
+            .map(|node_ref| (*node_ref, Vec::new()))
+            .collect::<HashMap<NodeRef, Vec<(i64, i64)>>>();
+        for (node_ref, x, y) in flattened_inputs.iter().chain(flattened_outputs.iter()) {
+            substrates.get_mut(node_ref).unwrap().push((*x, *y));
+        }
+
+        Layout {
+            substrates_hash: substrates
+                .iter()
+                .map(|(node_ref, nodes)| (*node_ref, nodes.iter().cloned().collect()))
+                .collect(),
+            substrates,
+            flattened_inputs_hash: flattened_inputs.iter().cloned().collect(),
+            flattened_outputs_hash: flattened_outputs.iter().cloned().collect(),
+            flattened_inputs,
+            flattened_outputs,
+        }
+    }
+
+    /// Develop all substrates and links, returning the assembled network and the nodes of each substrate
+    fn assemble<G: DesGenome>(
+        &self,
+        genome: &G,
+        layout: &Layout,
+    ) -> (AssembledConnections, HashMap<NodeRef, HashSet<(i64, i64)>>) {
         // Init assembled network
         let mut assembled_connections = connection::Connections::<(NodeRef, i64, i64), f64>::new();
 
         // Init known nodes with the input and output nodes
-        let mut substrate_nodes = HashMap::<NodeRef, HashSet<(i64, i64)>>::new();
-        for (i, nodes) in self.input_nodes.iter().enumerate() {
-            substrate_nodes.insert(
-                NodeRef::Input(i as u64),
-                nodes.iter().cloned().collect::<HashSet<(i64, i64)>>(),
-            );
-        }
-        for (i, nodes) in self.output_nodes.iter().enumerate() {
-            substrate_nodes.insert(
-                NodeRef::Output(i as u64),
-                nodes.iter().cloned().collect::<HashSet<(i64, i64)>>(),
-            );
-        }
+        let mut substrate_nodes = layout.substrates_hash.clone();
         // All hidden substrates are empty
         for node_ref in genome.get_neat().hidden_nodes.keys() {
             substrate_nodes.insert(*node_ref, HashSet::new());
         }
 
         // Iterative network completion in topologically sorted order
-        let order = genome.get_neat().connections.sort_topologically();
+        let order = genome.get_neat().connections.sort_topologically_stable();
         for element in order.iter() {
             match element {
                 connection::OrderedAction::Edge(from, to, _) => {
@@ -137,10 +165,10 @@ impl Developer {
                             false,
                             true,
                         ),
-                        NodeRef::Output(id) => {
+                        NodeRef::Output(_) => {
                             let (mut layers_reverse, mut connections_reverse) =
                                 search::explore_substrate(
-                                    self.output_nodes[*id as usize].clone(),
+                                    layout.substrates[to].clone(),
                                     &vec![],
                                     &mut cppn,
                                     1,
@@ -168,13 +196,12 @@ impl Developer {
                                 // If therere are any connections to output nodes, these will also be
                                 // present in the reverse search. Remvove to avoid duplicates.
                                 if layers.len() > 1 {
-                                    layers[1].retain(|node| {
-                                        !self.output_nodes_hash[*id as usize].contains(node)
-                                    });
+                                    layers[1]
+                                        .retain(|node| !layout.substrates_hash[to].contains(node));
                                 }
 
                                 connections.retain(|connection| {
-                                    !self.output_nodes_hash[*id as usize].contains(&connection.to)
+                                    !layout.substrates_hash[to].contains(&connection.to)
                                 });
 
                                 // Merge the normal and reverse search.
@@ -246,8 +273,8 @@ impl Developer {
                                 false,
                             ),
                             // Output substrates are searched in reverse, starting at the output nodes
-                            NodeRef::Output(id) => search::explore_substrate(
-                                self.output_nodes[*id as usize].clone(),
+                            NodeRef::Output(_) => search::explore_substrate(
+                                layout.substrates[node_ref].clone(),
                                 &vec![],
                                 &mut cppn,
                                 depth,
@@ -277,8 +304,7 @@ impl Developer {
             }
         }
 
-        assembled_connections.prune(&self.flattened_inputs, &self.flattened_outputs, false);
-        assembled_connections
+        (assembled_connections, substrate_nodes)
     }
 }
 
@@ -287,202 +313,32 @@ impl<G: DesGenome> Develop<G> for Developer {
     type Stats = MultiSubstrateNetworkStats;
 
     fn develop(&self, genome: G) -> (Self::Phenotype, Self::Stats) {
+        self.develop_network(genome, false)
+    }
+
+    fn develop_analyzed(&self, genome: G) -> (Self::Phenotype, Self::Stats) {
+        self.develop_network(genome, true)
+    }
+}
+
+impl Developer {
+    /// Develop the network, analyzing the substrate graph and the assembled graph of nodes
+    /// if requested
+    fn develop_network<G: DesGenome>(
+        &self,
+        genome: G,
+        analyze: bool,
+    ) -> (Executor, MultiSubstrateNetworkStats) {
         // Let the genome prepeare to provide cppns and depth
         let mut genome = genome;
         genome.init_desgenome();
 
-        // Init assembled network
-        let mut assembled_connections = connection::Connections::<(NodeRef, i64, i64), f64>::new();
-
-        // Init known nodes with the input and output nodes
-        let mut substrate_nodes = HashMap::<NodeRef, HashSet<(i64, i64)>>::new();
-        for (i, nodes) in self.input_nodes.iter().enumerate() {
-            substrate_nodes.insert(
-                NodeRef::Input(i as u64),
-                nodes.iter().cloned().collect::<HashSet<(i64, i64)>>(),
-            );
-        }
-        for (i, nodes) in self.output_nodes.iter().enumerate() {
-            substrate_nodes.insert(
-                NodeRef::Output(i as u64),
-                nodes.iter().cloned().collect::<HashSet<(i64, i64)>>(),
-            );
-        }
-        // All hidden substrates are empty
-        for node_ref in genome.get_neat().hidden_nodes.keys() {
-            substrate_nodes.insert(*node_ref, HashSet::new());
-        }
-
-        // Iterative network completion in topologically sorted order
-        let order = genome.get_neat().connections.sort_topologically();
-        for element in order.iter() {
-            match element {
-                connection::OrderedAction::Edge(from, to, _) => {
-                    // Develop the link's cppn
-                    let mut cppn = self
-                        .cppn_developer
-                        .develop(genome.get_link_cppn(*from, *to).clone())
-                        .0;
-
-                    // Search for connections
-                    let (layers, connections) = match to {
-                        NodeRef::Input(_) => panic!("target is input substrate"),
-                        NodeRef::Hidden(_) => search::explore_substrate(
-                            substrate_nodes
-                                .get(from)
-                                .unwrap()
-                                .iter()
-                                .cloned()
-                                .collect::<Vec<(i64, i64)>>(),
-                            &vec![],
-                            &mut cppn,
-                            1,
-                            false,
-                            true,
-                        ),
-                        NodeRef::Output(id) => {
-                            let (mut layers_reverse, mut connections_reverse) =
-                                search::explore_substrate(
-                                    self.output_nodes[*id as usize].clone(),
-                                    &vec![],
-                                    &mut cppn,
-                                    1,
-                                    true,
-                                    true,
-                                );
-                            if genome.get_depth(to) > 0 {
-                                // When depth of output substrate is > 0, search for additional non-reverse connections.
-                                // These can potentially be conencted to the output when the output substrate is developed.
-
-                                let (mut layers, mut connections) = search::explore_substrate(
-                                    substrate_nodes
-                                        .get(from)
-                                        .unwrap()
-                                        .iter()
-                                        .cloned()
-                                        .collect::<Vec<(i64, i64)>>(),
-                                    &vec![],
-                                    &mut cppn,
-                                    1,
-                                    false,
-                                    true,
-                                );
-
-                                // If therere are any connections to output nodes, these will also be
-                                // present in the reverse search. Remvove to avoid duplicates.
-                                if layers.len() > 1 {
-                                    layers[1].retain(|node| {
-                                        !self.output_nodes_hash[*id as usize].contains(node)
-                                    });
-                                }
-
-                                connections.retain(|connection| {
-                                    !self.output_nodes_hash[*id as usize].contains(&connection.to)
-                                });
-
-                                // Merge the normal and reverse search.
-                                layers = vec![
-                                    vec![],
-                                    layers
-                                        .drain(..)
-                                        .skip(1)
-                                        .take(1)
-                                        .flatten()
-                                        .chain(layers_reverse.drain(..).skip(1).take(1).flatten())
-                                        .collect::<Vec<_>>(),
-                                ];
-                                connections.append(&mut connections_reverse);
-
-                                (layers, connections)
-                            } else {
-                                (layers_reverse, connections_reverse)
-                            }
-                        }
-                    };
-
-                    // Add discovered nodes to target substrate
-                    let nodes = substrate_nodes.get_mut(to).unwrap();
-                    // First layer contains source nodes
-                    // Never more than a single layer of new nodes since depth = 1
-                    for node in layers.iter().skip(1).take(1).flatten() {
-                        nodes.insert(*node);
-                    }
-
-                    // Add discovered connections to assembled network
-                    for connection in connections.iter() {
-                        assembled_connections.add(
-                            (*from, connection.from.0, connection.from.1),
-                            (*to, connection.to.0, connection.to.1),
-                            connection.edge
-                                * genome
-                                    .get_neat()
-                                    .links
-                                    .get(&(*from, *to))
-                                    .unwrap()
-                                    .neat()
-                                    .weight,
-                        );
-                    }
-                }
-                connection::OrderedAction::Node(node_ref) => {
-                    let depth = genome.get_depth(node_ref);
-                    if depth > 0 {
-                        // Develop the node's cppn
-                        let mut cppn = self
-                            .cppn_developer
-                            .develop(genome.get_node_cppn(node_ref).clone())
-                            .0;
-
-                        // Develop substrate
-                        let (layers, connections) = match node_ref {
-                            NodeRef::Input(_) | NodeRef::Hidden(_) => search::explore_substrate(
-                                substrate_nodes
-                                    .get(node_ref)
-                                    .unwrap()
-                                    .iter()
-                                    .cloned()
-                                    .collect::<Vec<(i64, i64)>>(),
-                                &vec![],
-                                &mut cppn,
-                                depth,
-                                false,
-                                false,
-                            ),
-                            // Output substrates are searched in reverse, starting at the output nodes
-                            NodeRef::Output(id) => search::explore_substrate(
-                                self.output_nodes[*id as usize].clone(),
-                                &vec![],
-                                &mut cppn,
-                                depth,
-                                true,
-                                false,
-                            ),
-                        };
-
-                        // Add discovered nodes to target substrate
-                        let nodes = substrate_nodes.get_mut(node_ref).unwrap();
-                        // First layer contains source nodes
-                        for layer in layers.iter().skip(1) {
-                            for node in layer.iter() {
-                                nodes.insert(*node);
-                            }
-                        }
-                        // Add discovered connections to assembled network
-                        for connection in connections.iter() {
-                            assembled_connections.add(
-                                (*node_ref, connection.from.0, connection.from.1),
-                                (*node_ref, connection.to.0, connection.to.1),
-                                connection.edge,
-                            );
-                        }
-                    }
-                }
-            }
-        }
+        let layout = self.layout(&genome);
+        let (mut assembled_connections, substrate_nodes) = self.assemble(&genome, &layout);
 
         // Remove any node not on a path between input and output nodes
         let pruned =
-            assembled_connections.prune(&self.flattened_inputs, &self.flattened_outputs, true);
+            assembled_connections.prune(&layout.flattened_inputs, &layout.flattened_outputs, true);
         let pruned = pruned.iter().collect::<HashSet<_>>();
 
         // Collect all hidden nodes, in all hidden substrates and I/O substrates
@@ -503,7 +359,7 @@ impl<G: DesGenome> Develop<G> for Developer {
                     .unwrap()
                     .iter()
                     .map(|node| (*node_ref, node.0, node.1))
-                    .filter(|node| !self.flattened_inputs_hash.contains(node)),
+                    .filter(|node| !layout.flattened_inputs_hash.contains(node)),
             );
         }
         for node_ref in genome.get_neat().outputs.keys() {
@@ -513,22 +369,23 @@ impl<G: DesGenome> Develop<G> for Developer {
                     .unwrap()
                     .iter()
                     .map(|node| (*node_ref, node.0, node.1))
-                    .filter(|node| !self.flattened_outputs_hash.contains(node)),
+                    .filter(|node| !layout.flattened_outputs_hash.contains(node)),
             );
         }
 
         // Collect all nodes (in all substrates)
-        let nodes = self
+        let nodes = layout
             .flattened_inputs
             .iter()
             .cloned()
             .chain(hidden_nodes.drain(..).filter(|node| !pruned.contains(node)))
-            .chain(self.flattened_outputs.iter().cloned())
+            .chain(layout.flattened_outputs.iter().cloned())
             .collect::<Vec<(NodeRef, i64, i64)>>();
 
-        let first_output_id = nodes.len() - self.flattened_outputs.len();
-        let inputs = (0..self.flattened_inputs.len()).collect();
-        let outputs = (first_output_id..(first_output_id + self.flattened_outputs.len())).collect();
+        let first_output_id = nodes.len() - layout.flattened_outputs.len();
+        let inputs = (0..layout.flattened_inputs.len()).collect();
+        let outputs =
+            (first_output_id..(first_output_id + layout.flattened_outputs.len())).collect();
 
         // Create mapping from nodes to array index in Network's node vector
         let node_mapping: HashMap<(NodeRef, i64, i64), usize> = nodes
@@ -539,7 +396,7 @@ impl<G: DesGenome> Develop<G> for Developer {
 
         // Map topologically sorted order to neural network actions.
         let actions = assembled_connections
-            .sort_topologically()
+            .sort_topologically_stable()
             .iter()
             .map(|action| match action {
                 connection::OrderedAction::Edge(from, to, weight) => Action::Link(
@@ -584,28 +441,73 @@ impl<G: DesGenome> Develop<G> for Developer {
             );
         }
 
-        let stats = MultiSubstrateNetworkStats {
+        let mut stats = MultiSubstrateNetworkStats {
             hidden_substrates: hidden_substrate_node_counts.len() as u64,
             hidden_substrate_node_counts: hidden_substrate_node_counts
                 .values()
                 .cloned()
                 .collect::<Vec<i64>>(),
-            network_stats: NetworkStats {
-                nodes: assembled_connections.get_all_nodes().len() as u64,
-                edges: assembled_connections.get_all_connections().len() as u64,
-            },
+            substrate_structure: None,
+            network_stats: NetworkStats::new(
+                assembled_connections.get_all_nodes().len() as u64,
+                assembled_connections.get_all_connections().len() as u64,
+            ),
         };
-        let network = Executor::create(nodes.len(), inputs, outputs, actions);
+        let mut network = Executor::create(nodes.len(), inputs, outputs, actions)
+            .with_output_activation(self.output_activation);
+        if CONF.simplify_networks {
+            network = stats.network_stats.simplify(network, CONF.simplify_epsilon);
+        }
+        if analyze {
+            stats.substrate_structure =
+                Some(NetworkStructure::from(&genome.get_neat().connections));
+            stats.network_stats.analyze(&assembled_connections);
+        }
 
         (network, stats)
     }
 }
 
-pub fn parse_nodes(conf: &String, r: f64, num: u64) -> Vec<Vec<(i64, i64)>> {
-    match &conf[..] {
-        "line" => vec![substrate::horizontal_row(num, 0)],
-        "separate" => vec![vec![(0, 0)]; num as usize],
-        _ => serde_json::from_str::<Vec<Vec<Vec<f64>>>>(conf)
+/// Flatten the groups of input or output nodes, in configuration order, tagged with their substrate
+fn flatten<'a, G: DesGenome>(
+    genome: &G,
+    node_refs: impl Iterator<Item = &'a NodeRef>,
+    groups: &[Vec<(i64, i64)>],
+) -> Vec<(NodeRef, i64, i64)> {
+    let mut owners = vec![None; groups.len()];
+    for node_ref in node_refs {
+        for group in genome.get_substrates(node_ref) {
+            owners[group as usize] = Some(*node_ref);
+        }
+    }
+
+    groups
+        .iter()
+        .zip(owners.iter())
+        .enumerate()
+        .flat_map(|(i, (nodes, owner))| {
+            let owner = owner.unwrap_or_else(|| panic!("group {} is not in any substrate", i));
+            nodes.iter().map(move |node| (owner, node.0, node.1))
+        })
+        .collect()
+}
+
+/// Groups of nodes given by a layout, like substrate::parse_nodes, where "separate" places
+/// each node in its own group. A grid is a single group.
+pub fn parse_nodes(
+    conf: &String,
+    r: f64,
+    num: u64,
+    grid_size: Option<(u64, u64)>,
+) -> Vec<Vec<(i64, i64)>> {
+    match (&conf[..], grid_size) {
+        ("auto", Some((width, height))) | ("grid", Some((width, height))) => {
+            vec![substrate::grid(width, height, num, r)]
+        }
+        ("grid", None) => panic!("grid layout requires inputs that form an image"),
+        ("line", _) | ("auto", None) => vec![substrate::horizontal_row(num, 0)],
+        ("separate", _) => vec![vec![(0, 0)]; num as usize],
+        (_, _) => serde_json::from_str::<Vec<Vec<Vec<f64>>>>(conf)
             .expect("unable to parse nodes")
             .iter()
             .map(|points| {
@@ -618,9 +520,27 @@ pub fn parse_nodes(conf: &String, r: f64, num: u64) -> Vec<Vec<(i64, i64)>> {
     }
 }
 
+/// Whether two sets of I/O groups share any node position, so they cannot form a single substrate
+pub fn groups_overlap(conf: &String, a: &[u64], b: &[u64]) -> bool {
+    match &conf[..] {
+        // Lines and grids are a single group, and separate groups are all placed at the origin
+        "line" | "grid" | "auto" | "separate" => true,
+        _ => {
+            let groups = parse_nodes(conf, ESHYPERNEAT.resolution, 0, None);
+            let positions = a
+                .iter()
+                .flat_map(|group| groups[*group as usize].iter())
+                .collect::<HashSet<_>>();
+            b.iter()
+                .flat_map(|group| groups[*group as usize].iter())
+                .any(|position| positions.contains(position))
+        }
+    }
+}
+
 pub fn parse_num_substrates(conf: &String, num: u64) -> u64 {
     match &conf[..] {
-        "line" => 1,
+        "line" | "grid" | "auto" => 1,
         "separate" => num,
         _ => serde_json::from_str::<Vec<Vec<Vec<f64>>>>(conf)
             .expect("unable to parse num substrates")
@@ -633,3 +553,29 @@ pub fn topology_init_config(init_config: &EnvironmentDescription) -> InitConfig
     let outputs = parse_num_substrates(&DESHYPERNEAT.output_config, init_config.outputs);
     InitConfig::new(inputs, outputs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deshyperneat::{conf::GenomeConfig, genome::Genome, state::State};
+    use evolution::genome::GenericGenome;
+
+    #[test]
+    fn test_flatten_merged_substrates() {
+        let config = GenomeConfig::default();
+        let mut state = State::default();
+        let mut genome = Genome::new(&config, &InitConfig::new(3, 1), &mut state);
+        genome.merge_substrates(&config, &mut state, NodeRef::Input(0), NodeRef::Input(2));
+
+        let groups = vec![vec![(0, 0)], vec![(1, 0)], vec![(2, 0), (3, 0)]];
+        assert_eq!(
+            flatten(&genome, genome.neat.inputs.keys(), &groups),
+            vec![
+                (NodeRef::Input(0), 0, 0),
+                (NodeRef::Input(1), 1, 0),
+                (NodeRef::Input(0), 2, 0),
+                (NodeRef::Input(0), 3, 0),
+            ]
+        );
+    }
+}
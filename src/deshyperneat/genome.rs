@@ -1,20 +1,25 @@
+use crate::cppn::genome::Genome as CppnGenome;
 use crate::deshyperneat::{
     conf::{GenomeConfig, DESHYPERNEAT},
+    developer::groups_overlap,
     link::Link,
     node::Node,
+    serialize::SerializedGenome,
     state::State,
 };
 use evolution::{
     genome::{GenericGenome as GenericEvolvableGenome, Genome as EvolvableGenome},
     neat::{
         genome::{NeatGenome, NeatGenomeStats},
-        node::NodeRef,
+        link::{LinkExtension, NeatLink},
+        node::{NeatNode, NodeExtension, NodeRef},
         state::InitConfig,
     },
     stats::Stats,
 };
 use rand::{seq::SliceRandom, Rng};
 use serde::Serialize;
+use std::collections::HashSet;
 
 #[derive(Clone)]
 pub struct Genome {
@@ -141,6 +146,19 @@ impl GenericEvolvableGenome<GenomeConfig, State, InitConfig, DESGenomeStats> for
                 mutate_node(&mut node, limit, &mut rng);
             }
         }
+
+        if rng.gen::<f64>() < DESHYPERNEAT.split_input_substrate_probability {
+            self.mutation_split_substrate(config, state, true);
+        }
+        if rng.gen::<f64>() < DESHYPERNEAT.merge_input_substrates_probability {
+            self.mutation_merge_substrates(config, state, true);
+        }
+        if rng.gen::<f64>() < DESHYPERNEAT.split_output_substrate_probability {
+            self.mutation_split_substrate(config, state, false);
+        }
+        if rng.gen::<f64>() < DESHYPERNEAT.merge_output_substrates_probability {
+            self.mutation_merge_substrates(config, state, false);
+        }
     }
 
     fn distance(&self, config: &GenomeConfig, other: &Self) -> f64 {
@@ -162,6 +180,264 @@ impl GenericEvolvableGenome<GenomeConfig, State, InitConfig, DESGenomeStats> for
             link_cppns: accumulate_neat_stats(self.neat.links.values().map(|x| x.cppn.get_stats())),
         }
     }
+
+    fn validate(&self) -> Result<(), String> {
+        self.neat.validate()?;
+
+        for (node_ref, node) in self
+            .neat
+            .inputs
+            .iter()
+            .chain(self.neat.hidden_nodes.iter())
+            .chain(self.neat.outputs.iter())
+        {
+            validate_cppn(&node.cppn).map_err(|e| format!("node {} cppn: {}", node_ref, e))?;
+
+            let limit = max_depth(node_ref);
+            if node.depth > limit {
+                return Err(format!(
+                    "node {} has depth {} above limit {}",
+                    node_ref, node.depth, limit
+                ));
+            }
+        }
+
+        for (kind, nodes) in [("input", &self.neat.inputs), ("output", &self.neat.outputs)].iter() {
+            let mut groups = HashSet::<u64>::new();
+            for (node_ref, node) in nodes.iter() {
+                // Substrates are named after their first group
+                if node.substrates.iter().min() != Some(&node_ref.id()) {
+                    return Err(format!(
+                        "node {} has {} groups {:?}",
+                        node_ref, kind, node.substrates
+                    ));
+                }
+                for group in node.substrates.iter() {
+                    if !groups.insert(*group) {
+                        return Err(format!("{} group {} is in several substrates", kind, group));
+                    }
+                }
+            }
+        }
+        for (node_ref, node) in self.neat.hidden_nodes.iter() {
+            if !node.substrates.is_empty() {
+                return Err(format!("hidden node {} has I/O groups", node_ref));
+            }
+        }
+
+        for ((from, to), link) in self.neat.links.iter() {
+            validate_cppn(&link.cppn).map_err(|e| format!("link {}->{} cppn: {}", from, to, e))?;
+        }
+
+        Ok(())
+    }
+
+    const SAVABLE: bool = true;
+
+    fn save(&self) -> Option<String> {
+        serde_json::to_string_pretty(&SerializedGenome::from(self)).ok()
+    }
+
+    fn load(
+        _: &GenomeConfig,
+        init_config: &InitConfig,
+        serialized: &str,
+        state: &mut State,
+    ) -> Result<Self, String> {
+        serde_json::from_str::<SerializedGenome>(serialized)
+            .map_err(|e| e.to_string())?
+            .into_genome(init_config, state)
+    }
+}
+
+impl Genome {
+    /// Split a random I/O substrate consisting of several groups in two
+    fn mutation_split_substrate(&mut self, config: &GenomeConfig, state: &mut State, input: bool) {
+        let mut rng = rand::thread_rng();
+        let nodes = if input {
+            &self.neat.inputs
+        } else {
+            &self.neat.outputs
+        };
+
+        if let Some(node_ref) = nodes
+            .iter()
+            .filter(|(_, node)| node.substrates.len() > 1)
+            .map(|(node_ref, _)| *node_ref)
+            .collect::<Vec<NodeRef>>()
+            .choose(&mut rng)
+        {
+            // The substrate keeps the group it is named after, a random subset of the rest is moved
+            let mut groups = nodes[node_ref]
+                .substrates
+                .iter()
+                .cloned()
+                .filter(|group| *group != node_ref.id())
+                .collect::<Vec<u64>>();
+            groups.shuffle(&mut rng);
+            let moved = groups.split_off(rng.gen_range(0, groups.len()));
+
+            self.split_substrate(config, state, *node_ref, moved);
+        }
+    }
+
+    /// Merge two random I/O substrates, if their groups do not overlap
+    fn mutation_merge_substrates(&mut self, config: &GenomeConfig, state: &mut State, input: bool) {
+        let mut rng = rand::thread_rng();
+        let (nodes, conf) = if input {
+            (&self.neat.inputs, &DESHYPERNEAT.input_config)
+        } else {
+            (&self.neat.outputs, &DESHYPERNEAT.output_config)
+        };
+
+        let pair = nodes
+            .keys()
+            .cloned()
+            .collect::<Vec<NodeRef>>()
+            .choose_multiple(&mut rng, 2)
+            .cloned()
+            .collect::<Vec<NodeRef>>();
+        if pair.len() < 2
+            || groups_overlap(
+                conf,
+                &nodes[&pair[0]].substrates,
+                &nodes[&pair[1]].substrates,
+            )
+        {
+            return;
+        }
+
+        if pair[0].id() < pair[1].id() {
+            self.merge_substrates(config, state, pair[0], pair[1]);
+        } else {
+            self.merge_substrates(config, state, pair[1], pair[0]);
+        }
+    }
+
+    /// Move groups of an I/O substrate to a new substrate, which copies its links
+    pub fn split_substrate(
+        &mut self,
+        config: &GenomeConfig,
+        state: &mut State,
+        node_ref: NodeRef,
+        mut groups: Vec<u64>,
+    ) {
+        groups.sort();
+        let new_ref = match node_ref {
+            NodeRef::Input(_) => NodeRef::Input(groups[0]),
+            NodeRef::Hidden(_) => panic!("cannot split hidden node"),
+            NodeRef::Output(_) => NodeRef::Output(groups[0]),
+        };
+
+        self.neat
+            .get_node_mut(&node_ref)
+            .unwrap()
+            .substrates
+            .retain(|group| !groups.contains(group));
+        let mut new_node: Node =
+            NodeExtension::new(&config.cppn, NeatNode::new(new_ref), &mut state.custom);
+        new_node.substrates = groups;
+
+        if let NodeRef::Input(_) = node_ref {
+            self.neat.inputs.insert(new_ref, new_node);
+        } else {
+            self.neat.outputs.insert(new_ref, new_node);
+        }
+
+        for link in self
+            .neat
+            .links
+            .values()
+            .filter(|link| link.neat.from == node_ref || link.neat.to == node_ref)
+            .cloned()
+            .collect::<Vec<Link>>()
+        {
+            if link.neat.from == node_ref {
+                self.copy_link(config, state, &link, new_ref, link.neat.to);
+            } else {
+                self.copy_link(config, state, &link, link.neat.from, new_ref);
+            }
+        }
+    }
+
+    /// Merge an I/O substrate into another, moving links that the remaining substrate lacks
+    pub fn merge_substrates(
+        &mut self,
+        config: &GenomeConfig,
+        state: &mut State,
+        node_ref: NodeRef,
+        removed_ref: NodeRef,
+    ) {
+        let removed = match removed_ref {
+            NodeRef::Input(_) => self.neat.inputs.remove(&removed_ref),
+            NodeRef::Hidden(_) => panic!("cannot merge hidden node"),
+            NodeRef::Output(_) => self.neat.outputs.remove(&removed_ref),
+        }
+        .expect("cannot merge nonexistent node");
+
+        let node = self.neat.get_node_mut(&node_ref).unwrap();
+        node.substrates.extend(removed.substrates);
+        node.substrates.sort();
+
+        for connection in self.neat.connections.remove_node(removed_ref) {
+            let link = self
+                .neat
+                .links
+                .remove(&(connection.from, connection.to))
+                .unwrap();
+            let (from, to) = if connection.from == removed_ref {
+                (node_ref, connection.to)
+            } else {
+                (connection.from, node_ref)
+            };
+            if !self.neat.links.contains_key(&(from, to)) {
+                self.copy_link(config, state, &link, from, to);
+            }
+        }
+    }
+
+    fn copy_link(
+        &mut self,
+        config: &GenomeConfig,
+        state: &mut State,
+        link: &Link,
+        from: NodeRef,
+        to: NodeRef,
+    ) {
+        let innovation = state.neat.get_connect_innovation(from, to);
+        let neat = NeatLink::new(from, to, link.neat.weight, innovation);
+
+        // The cppn can only be copied if no other link in the population has used this link's state
+        let key = (from, to);
+        let link = if state.custom.unique_cppn_states.contains_key(&key)
+            || state.custom.cppn_state_redirects.contains_key(&key)
+        {
+            <Link as LinkExtension>::new(&config.cppn, neat, &mut state.custom)
+        } else {
+            link.clone_with(&config.cppn, neat, &mut state.custom)
+        };
+        self.neat.insert_link(link);
+    }
+}
+
+/// Maximum substrate depth of a node, according to its type
+pub fn max_depth(node_ref: &NodeRef) -> u64 {
+    match node_ref {
+        NodeRef::Input(_) => DESHYPERNEAT.max_input_substrate_depth,
+        NodeRef::Hidden(_) => DESHYPERNEAT.max_hidden_substrate_depth,
+        NodeRef::Output(_) => DESHYPERNEAT.max_output_substrate_depth,
+    }
+}
+
+/// Check a substrate cppn, which must provide weights through its first output
+pub fn validate_cppn(cppn: &CppnGenome) -> Result<(), String> {
+    cppn.validate()?;
+
+    if !cppn.neat.outputs.contains_key(&NodeRef::Output(0)) {
+        return Err("missing weight output O0".to_owned());
+    }
+
+    Ok(())
 }
 
 fn mutate_node<R: Rng>(node: &mut Node, limit: u64, rng: &mut R) {
@@ -206,3 +482,109 @@ fn accumulate_neat_stats(iter: impl Iterator<Item = NeatGenomeStats>) -> NeatGen
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evolution::genome::check_random_evolution;
+
+    #[test]
+    fn test_random_mutations() {
+        let mut config = GenomeConfig::default();
+        config.topology.add_node_probability = 0.3;
+        config.topology.add_link_probability = 0.6;
+        config.topology.remove_node_probability = 0.05;
+        config.topology.remove_link_probability = 0.2;
+        let mut rng = rand::thread_rng();
+
+        check_random_evolution::<Genome>(
+            &config,
+            &InitConfig::new(3, 3),
+            5,
+            500,
+            |genome, state| {
+                // Merge regardless of the configured groups, which may overlap
+                let input = rng.gen::<bool>();
+                if rng.gen::<f64>() < 0.1 {
+                    let nodes = if input {
+                        &genome.neat.inputs
+                    } else {
+                        &genome.neat.outputs
+                    };
+                    let mut pair = nodes.keys().cloned().collect::<Vec<NodeRef>>();
+                    pair.sort_by_key(|node_ref| node_ref.id());
+                    if pair.len() > 1 {
+                        genome.merge_substrates(&config, state, pair[0], pair[1]);
+                    }
+                } else if rng.gen::<f64>() < 0.1 {
+                    genome.mutation_split_substrate(&config, state, input);
+                }
+            },
+        );
+    }
+
+    fn add_link(
+        genome: &mut Genome,
+        config: &GenomeConfig,
+        state: &mut State,
+        from: NodeRef,
+        to: NodeRef,
+    ) {
+        let innovation = state.neat.get_connect_innovation(from, to);
+        genome.neat.insert_link(<Link as LinkExtension>::new(
+            &config.cppn,
+            NeatLink::new(from, to, 1.0, innovation),
+            &mut state.custom,
+        ));
+    }
+
+    #[test]
+    fn test_split_merge_substrates() {
+        let config = GenomeConfig::default();
+        let mut state = State::default();
+        let mut genome = Genome::new(&config, &InitConfig::new(3, 2), &mut state);
+        for i in 0..3 {
+            add_link(
+                &mut genome,
+                &config,
+                &mut state,
+                NodeRef::Input(i),
+                NodeRef::Output(0),
+            );
+        }
+        add_link(
+            &mut genome,
+            &config,
+            &mut state,
+            NodeRef::Input(1),
+            NodeRef::Output(1),
+        );
+
+        genome.merge_substrates(&config, &mut state, NodeRef::Input(0), NodeRef::Input(2));
+        assert_eq!(genome.validate(), Ok(()));
+        assert_eq!(genome.neat.inputs.len(), 2);
+        assert_eq!(
+            genome.neat.inputs[&NodeRef::Input(0)].substrates,
+            vec![0, 2]
+        );
+        assert_eq!(genome.neat.links.len(), 3);
+
+        genome.merge_substrates(&config, &mut state, NodeRef::Output(0), NodeRef::Output(1));
+        assert_eq!(genome.validate(), Ok(()));
+        assert_eq!(
+            genome.neat.outputs[&NodeRef::Output(0)].substrates,
+            vec![0, 1]
+        );
+        assert_eq!(genome.neat.links.len(), 2);
+
+        genome.split_substrate(&config, &mut state, NodeRef::Input(0), vec![2]);
+        assert_eq!(genome.validate(), Ok(()));
+        assert_eq!(genome.neat.inputs[&NodeRef::Input(0)].substrates, vec![0]);
+        assert_eq!(genome.neat.inputs[&NodeRef::Input(2)].substrates, vec![2]);
+        assert!(genome
+            .neat
+            .links
+            .contains_key(&(NodeRef::Input(2), NodeRef::Output(0))));
+        assert_eq!(genome.neat.links.len(), 3);
+    }
+}
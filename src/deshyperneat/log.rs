@@ -45,4 +45,8 @@ impl<G: Genome + DesGenome> log::Log<G> for Logger {
     fn close(&mut self) {
         <log::Logger as log::Log<G>>::close(&mut self.default_logger);
     }
+
+    fn is_logged(&self, iteration: u64) -> bool {
+        <log::Logger as log::Log<G>>::is_logged(&self.default_logger, iteration)
+    }
 }
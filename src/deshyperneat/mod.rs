@@ -6,25 +6,27 @@ pub mod genome;
 pub mod link;
 pub mod log;
 pub mod node;
+pub mod serialize;
 pub mod state;
 
 use conf::{GenomeConfig, MethodConfig};
 use developer::{topology_init_config, Developer};
 use evolution::{
-    algorithm::Algorithm, environment::Environment, environment::EnvironmentDescription, evolve,
-    neat::state::InitConfig,
+    algorithm::Algorithm, develop::FloatDeveloper, environment::Environment,
+    environment::EnvironmentDescription, evolve, neat::state::InitConfig,
 };
 use genome::Genome;
 use log::Logger;
-use network::execute::Executor;
+use network::{execute::Executor, float::Float};
 use serde::Serialize;
+use std::sync::Arc;
 
 pub struct Deshyperneat;
 
-impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Deshyperneat {
+impl<F: Float, E: Environment<Phenotype = Executor<F>>> Algorithm<E> for Deshyperneat {
     type Config = MethodConfig;
     type Genome = Genome;
-    type Developer = Developer;
+    type Developer = FloatDeveloper<Developer, F>;
 
     fn genome_config(_: &EnvironmentDescription) -> GenomeConfig {
         GenomeConfig::default()
@@ -36,8 +38,11 @@ impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Deshyperneat {
 }
 
 pub fn deshyperneat<
-    E: Environment<Phenotype = Executor> + Default + 'static,
+    F: Float,
+    E: Environment<Phenotype = Executor<F>> + 'static,
     C: Serialize + Default,
->() {
-    evolve::<E, Deshyperneat, Logger, C>();
+>(
+    environment: Arc<E>,
+) -> Option<Executor<F>> {
+    evolve::<E, Deshyperneat, Logger, C>(environment)
 }
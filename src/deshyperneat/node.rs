@@ -17,6 +17,8 @@ pub struct Node {
     pub neat: NeatNode,
     pub cppn: CppnGenome,
     pub depth: u64,
+    /// Groups of INPUT_CONFIG or OUTPUT_CONFIG making up an I/O substrate, empty for hidden nodes
+    pub substrates: Vec<u64>,
 }
 
 impl NodeExtension for Node {
@@ -50,7 +52,12 @@ impl NodeExtension for Node {
             })
             .max(0);
 
-        Self::new(neat, cppn, depth)
+        let substrates = match neat.node_ref {
+            NodeRef::Input(id) | NodeRef::Output(id) => vec![id],
+            NodeRef::Hidden(_) => vec![],
+        };
+
+        Self::new(neat, cppn, depth, substrates)
     }
 
     fn crossover(
@@ -70,6 +77,7 @@ impl NodeExtension for Node {
             } else {
                 other.depth
             },
+            substrates: self.substrates.clone(),
         }
     }
 
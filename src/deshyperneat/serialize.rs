@@ -0,0 +1,293 @@
+use crate::cppn::{genome::Genome as CppnGenome, serialize::SerializedGenome as SerializedCppn};
+use crate::deshyperneat::{
+    conf::DESHYPERNEAT, desgenome::DesGenome, genome::Genome, link::Link, node::Node, state::State,
+};
+use evolution::{
+    genome::GenericGenome,
+    neat::{
+        genome::{GetNeat, NeatGenome},
+        link::NeatLink,
+        node::{NeatNode, NodeRef},
+        state::InitConfig,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// JSON form of a DES-HyperNEAT genome, with the cppn of each substrate and link in the form
+/// of cppn::serialize. Combined genomes of CoDES-HyperNEAT are saved in the same form.
+/// Substrates are referred to as I0, H0 and O0, and I/O substrates without groups in the file
+/// cover the group they are named after.
+#[derive(Serialize, Deserialize)]
+pub struct SerializedGenome {
+    pub nodes: Vec<SerializedNode>,
+    pub links: Vec<SerializedLink>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedNode {
+    pub node: String,
+    pub depth: u64,
+    #[serde(default)]
+    pub substrates: Vec<u64>,
+    pub cppn: SerializedCppn,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedLink {
+    pub from: String,
+    pub to: String,
+    pub weight: f64,
+    pub cppn: SerializedCppn,
+}
+
+impl<G: DesGenome> From<&G> for SerializedGenome {
+    fn from(genome: &G) -> Self {
+        let neat = genome.get_neat();
+        let mut node_refs = neat
+            .inputs
+            .keys()
+            .chain(neat.hidden_nodes.keys())
+            .chain(neat.outputs.keys())
+            .cloned()
+            .collect::<Vec<NodeRef>>();
+        node_refs.sort();
+        let mut link_refs = neat.links.keys().cloned().collect::<Vec<_>>();
+        link_refs.sort();
+
+        Self {
+            nodes: node_refs
+                .iter()
+                .map(|node_ref| SerializedNode {
+                    node: node_ref.to_string(),
+                    depth: genome.get_depth(node_ref),
+                    substrates: match node_ref {
+                        NodeRef::Hidden(_) => Vec::new(),
+                        _ => genome.get_substrates(node_ref),
+                    },
+                    cppn: SerializedCppn::from(genome.get_node_cppn(node_ref)),
+                })
+                .collect(),
+            links: link_refs
+                .iter()
+                .map(|(from, to)| SerializedLink {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    weight: neat.links[&(*from, *to)].neat().weight,
+                    cppn: SerializedCppn::from(genome.get_link_cppn(*from, *to)),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SerializedGenome {
+    /// Build a genome for the given numbers of input and output groups. Hidden substrates get
+    /// ids that are unused in the state, and links and cppns are registered in the state.
+    pub fn into_genome(
+        self,
+        init_config: &InitConfig,
+        state: &mut State,
+    ) -> Result<Genome, String> {
+        let mut neat = NeatGenome::<Node, Link>::empty();
+        let mut hidden_ids = HashMap::<NodeRef, NodeRef>::new();
+
+        for serialized in self.nodes.into_iter() {
+            let node_ref = match serialized.node.parse::<NodeRef>()? {
+                NodeRef::Input(id) if id >= init_config.inputs => {
+                    return Err(format!(
+                        "input {} is not in the environment",
+                        serialized.node
+                    ))
+                }
+                NodeRef::Output(id) if id >= init_config.outputs => {
+                    return Err(format!(
+                        "output {} is not in the environment",
+                        serialized.node
+                    ))
+                }
+                NodeRef::Hidden(id) => {
+                    let new_ref = NodeRef::Hidden(state.neat.next_innovation.node_number);
+                    state.neat.next_innovation.node_number += 1;
+                    if hidden_ids.insert(NodeRef::Hidden(id), new_ref).is_some() {
+                        return Err(format!("duplicate node {}", serialized.node));
+                    }
+                    new_ref
+                }
+                node_ref => node_ref,
+            };
+
+            let substrates = match node_ref {
+                NodeRef::Hidden(_) => serialized.substrates,
+                _ if serialized.substrates.is_empty() => vec![node_ref.id()],
+                _ => serialized.substrates,
+            };
+            let cppn = load_cppn(serialized.cppn, state, (node_ref, node_ref))
+                .map_err(|e| format!("node {} cppn: {}", node_ref, e))?;
+            let node = Node::new(NeatNode::new(node_ref), cppn, serialized.depth, substrates);
+
+            let previous = match node_ref {
+                NodeRef::Input(_) => neat.inputs.insert(node_ref, node),
+                NodeRef::Hidden(_) => neat.hidden_nodes.insert(node_ref, node),
+                NodeRef::Output(_) => neat.outputs.insert(node_ref, node),
+            };
+            if previous.is_some() {
+                return Err(format!("duplicate node {}", serialized.node));
+            }
+        }
+
+        for (kind, nodes, count) in [
+            ("input", &neat.inputs, init_config.inputs),
+            ("output", &neat.outputs, init_config.outputs),
+        ]
+        .iter()
+        {
+            for group in 0..*count {
+                if !nodes.values().any(|node| node.substrates.contains(&group)) {
+                    return Err(format!("{} group {} is in no substrate", kind, group));
+                }
+            }
+        }
+
+        for link in self.links.into_iter() {
+            let resolve = |node: &String| -> Result<NodeRef, String> {
+                let unknown = || format!("link refers to unknown node {}", node);
+                match node.parse::<NodeRef>()? {
+                    node_ref @ NodeRef::Hidden(_) => {
+                        hidden_ids.get(&node_ref).cloned().ok_or_else(unknown)
+                    }
+                    node_ref if neat.get_node(&node_ref).is_some() => Ok(node_ref),
+                    _ => Err(unknown()),
+                }
+            };
+            let (from, to) = (resolve(&link.from)?, resolve(&link.to)?);
+            if neat.links.contains_key(&(from, to)) {
+                return Err(format!("duplicate link {}->{}", from, to));
+            }
+            if neat.connections.creates_cycle(from, to) {
+                return Err(format!("link {}->{} creates a cycle", from, to));
+            }
+
+            let innovation = state.neat.get_connect_innovation(from, to);
+            let cppn = load_cppn(link.cppn, state, (from, to))
+                .map_err(|e| format!("link {}->{} cppn: {}", from, to, e))?;
+            neat.insert_link(Link::new(
+                NeatLink::new(from, to, link.weight, innovation),
+                cppn,
+                1,
+            ));
+        }
+
+        let genome = Genome { neat };
+        genome.validate()?;
+        Ok(genome)
+    }
+}
+
+/// Load a cppn into the state it is mutated with, which is keyed by its node or link unless
+/// SINGLE_CPPN_STATE is set
+fn load_cppn(
+    cppn: SerializedCppn,
+    state: &mut State,
+    key: (NodeRef, NodeRef),
+) -> Result<CppnGenome, String> {
+    let cppn_state = if DESHYPERNEAT.single_cppn_state {
+        &mut state.custom.single_cppn_state
+    } else {
+        state.custom.unique_cppn_states.entry(key).or_default()
+    };
+    cppn.into_genome(&InitConfig::new(4, 2), cppn_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deshyperneat::{
+        conf::GenomeConfig,
+        developer::{topology_init_config, Developer},
+    };
+    use evolution::{develop::Develop, environment::EnvironmentDescription, genome::GenericGenome};
+
+    fn round_trip(genome: &Genome, init_config: &InitConfig) -> Genome {
+        let json = serde_json::to_string(&SerializedGenome::from(genome)).unwrap();
+        let loaded = serde_json::from_str::<SerializedGenome>(&json)
+            .unwrap()
+            .into_genome(init_config, &mut State::default())
+            .unwrap();
+        assert_eq!(
+            loaded.neat.hidden_nodes.len(),
+            genome.neat.hidden_nodes.len()
+        );
+        assert_eq!(loaded.neat.links.len(), genome.neat.links.len());
+        loaded
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut config = GenomeConfig::default();
+        config.topology.add_node_probability = 0.5;
+        config.topology.add_link_probability = 0.5;
+        let description = EnvironmentDescription::new(3, 2);
+        let init_config = topology_init_config(&description);
+        let mut state = State::default();
+        let mut genome = Genome::new(&config, &init_config, &mut state);
+        for _ in 0..20 {
+            genome.mutate(&config, &mut state);
+        }
+
+        // Both develop into the same network
+        let loaded = round_trip(&genome, &init_config);
+        let developer = Developer::from(description);
+        let inputs = vec![0.5, -0.25, 1.0];
+        assert_eq!(
+            developer.develop(genome).0.execute(&inputs),
+            developer.develop(loaded).0.execute(&inputs)
+        );
+
+        let mut genome = Genome::new(&config, &InitConfig::new(3, 2), &mut state);
+        genome.merge_substrates(&config, &mut state, NodeRef::Input(0), NodeRef::Input(2));
+        let loaded = round_trip(&genome, &InitConfig::new(3, 2));
+        assert_eq!(
+            loaded.neat.inputs[&NodeRef::Input(0)].substrates,
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let cppn = r#"{"nodes": [], "links": [{"from": "I0", "to": "O0", "weight": 1.0}]}"#;
+        let node = |node: &str, substrates: &str| {
+            format!(
+                r#"{{"node": "{}", "depth": 0, "substrates": {}, "cppn": {}}}"#,
+                node, substrates, cppn
+            )
+        };
+        let genome = |nodes: Vec<String>, links: &str| {
+            format!(
+                r#"{{"nodes": [{}], "links": [{}]}}"#,
+                nodes.join(","),
+                links
+            )
+        };
+        let link = |from: &str, to: &str| {
+            format!(
+                r#"{{"from": "{}", "to": "{}", "weight": 1.0, "cppn": {}}}"#,
+                from, to, cppn
+            )
+        };
+
+        let valid = genome(vec![node("I0", "[]"), node("O0", "[]")], &link("I0", "O0"));
+        let missing_group = genome(vec![node("I0", "[]")], "");
+        let overlapping = genome(vec![node("I0", "[0]"), node("O0", "[0, 0]")], "");
+        let unknown = genome(vec![node("I0", "[]"), node("O0", "[]")], &link("H0", "O0"));
+        let load = |json: &str| {
+            serde_json::from_str::<SerializedGenome>(json)
+                .unwrap()
+                .into_genome(&InitConfig::new(1, 1), &mut State::default())
+        };
+        assert!(load(&valid).is_ok());
+        for json in [missing_group, overlapping, unknown].iter() {
+            assert!(load(json).is_err());
+        }
+    }
+}
@@ -1,12 +1,13 @@
 use bson;
 use envconfig::Envconfig;
 use lazy_static::lazy_static;
-use network::activation::Activation;
+use network::activation::{Activation, GroupActivation};
 use serde::Serialize;
 
 #[derive(Envconfig, Serialize, Clone)]
 pub struct MethodConfig {
-    #[envconfig(from = "INPUT_CONFIG", default = "line")]
+    /// Input layout: line, grid, auto for a grid if the inputs form an image, or JSON points
+    #[envconfig(from = "INPUT_CONFIG", default = "auto")]
     pub input_config: String,
 
     #[envconfig(from = "OUTPUT_CONFIG", default = "line")]
@@ -47,9 +48,13 @@ pub struct MethodConfig {
     #[envconfig(from = "HIDDEN_ACTIVATION", default = "None")]
     pub hidden_activation: Activation,
 
-    #[envconfig(from = "OUTPUT_ACTIVATION", default = "Softmax")]
+    #[envconfig(from = "OUTPUT_ACTIVATION", default = "None")]
     pub output_activation: Activation,
 
+    /// Applied to the outputs of one-hot classification only
+    #[envconfig(from = "OUTPUT_GROUP_ACTIVATION", default = "Softmax")]
+    pub output_group_activation: GroupActivation,
+
     #[envconfig(from = "MAX_VARIANCE", default = "false")]
     pub max_variance: bool,
 
@@ -1,10 +1,12 @@
+use crate::conf::CONF;
 use crate::cppn::{developer::Developer as CppnDeveloper, genome::Genome};
 use crate::eshyperneat::{conf::ESHYPERNEAT, search};
-use crate::hyperneat::substrate;
+use crate::hyperneat::{developer::output_activation, substrate};
 use evolution::{
     develop::Develop, environment::EnvironmentDescription, neat::developer::NetworkStats,
 };
 use network::{
+    activation::GroupActivation,
     connection,
     execute::{Action, Executor},
 };
@@ -15,6 +17,7 @@ pub struct Developer {
     input_nodes: Vec<(i64, i64)>,
     output_nodes: Vec<(i64, i64)>,
     depth: u64,
+    output_activation: GroupActivation,
 }
 
 impl From<EnvironmentDescription> for Developer {
@@ -24,12 +27,14 @@ impl From<EnvironmentDescription> for Developer {
             ESHYPERNEAT.resolution,
             description.inputs,
             -1.0,
+            description.input_grid,
         );
         let output_nodes = substrate::parse_nodes(
             &ESHYPERNEAT.output_config,
             ESHYPERNEAT.resolution,
             description.outputs,
             1.0,
+            None,
         );
 
         Self {
@@ -37,6 +42,7 @@ impl From<EnvironmentDescription> for Developer {
             input_nodes,
             output_nodes,
             depth: ESHYPERNEAT.iteration_level + 1,
+            output_activation: output_activation(&description, ESHYPERNEAT.output_group_activation),
         }
     }
 }
@@ -80,6 +86,17 @@ impl Develop<Genome> for Developer {
     type Stats = NetworkStats;
 
     fn develop(&self, genome: Genome) -> (Self::Phenotype, Self::Stats) {
+        self.develop_network(genome, false)
+    }
+
+    fn develop_analyzed(&self, genome: Genome) -> (Self::Phenotype, Self::Stats) {
+        self.develop_network(genome, true)
+    }
+}
+
+impl Developer {
+    /// Develop the network, analyzing the graph of searched connections if requested
+    fn develop_network(&self, genome: Genome, analyze: bool) -> (Executor, NetworkStats) {
         let mut cppn = self.neat_developer.develop(genome).0;
 
         // Forward search with depth
@@ -141,7 +158,7 @@ impl Develop<Genome> for Developer {
 
         // Map topologically sorted order to neural network actions.
         let actions = connections
-            .sort_topologically()
+            .sort_topologically_stable()
             .iter()
             .map(|action| match action {
                 connection::OrderedAction::Edge(from, to, weight) => Action::Link(
@@ -172,12 +189,19 @@ impl Develop<Genome> for Developer {
             })
             .collect::<Vec<_>>();
 
-        let stats = NetworkStats {
-            nodes: connections.get_all_nodes().len() as u64,
-            edges: connections.get_all_connections().len() as u64,
-        };
+        let mut stats = NetworkStats::new(
+            connections.get_all_nodes().len() as u64,
+            connections.get_all_connections().len() as u64,
+        );
         // Create neural network executor
-        let network = Executor::create(nodes.len(), inputs, outputs, actions);
+        let mut network = Executor::create(nodes.len(), inputs, outputs, actions)
+            .with_output_activation(self.output_activation);
+        if CONF.simplify_networks {
+            network = stats.simplify(network, CONF.simplify_epsilon);
+        }
+        if analyze {
+            stats.analyze(&connections);
+        }
 
         (network, stats)
     }
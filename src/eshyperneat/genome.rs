@@ -6,6 +6,7 @@ use evolution::{
         link::NeatLink,
         node::{NeatNode, NodeExtension, NodeRef},
         state::{InitConfig, NeatState, StateProvider},
+        topology::InitialTopology,
     },
 };
 use network::activation::Activation;
@@ -135,7 +136,11 @@ pub fn insert_identity(
 }
 
 pub fn identity_genome() -> (Genome, NeatState) {
-    let config = NeatConfig::default();
+    // Identity is built from an empty genome, regardless of the configured initial topology
+    let config = NeatConfig {
+        initial_topology: InitialTopology::Empty,
+        ..NeatConfig::default()
+    };
     let init_config = InitConfig::new(4, 2);
     let mut state = NeatState::default();
     let mut genome = Genome::new(&config, &init_config, &mut state);
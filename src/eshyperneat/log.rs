@@ -56,4 +56,8 @@ impl log::Log<Genome> for Logger {
     fn close(&mut self) {
         self.hyperneat_logger.close();
     }
+
+    fn is_logged(&self, iteration: u64) -> bool {
+        self.hyperneat_logger.is_logged(iteration)
+    }
 }
@@ -11,19 +11,21 @@ use crate::eshyperneat::{developer::Developer, log::Logger};
 use conf::MethodConfig;
 use evolution::{
     algorithm::Algorithm,
+    develop::FloatDeveloper,
     environment::{Environment, EnvironmentDescription},
     evolve,
     neat::{conf::NeatConfig, state::InitConfig},
 };
-use network::execute::Executor;
+use network::{execute::Executor, float::Float};
 use serde::Serialize;
+use std::sync::Arc;
 
 pub struct Eshyperneat;
 
-impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Eshyperneat {
+impl<F: Float, E: Environment<Phenotype = Executor<F>>> Algorithm<E> for Eshyperneat {
     type Config = MethodConfig;
     type Genome = Genome;
-    type Developer = Developer;
+    type Developer = FloatDeveloper<Developer, F>;
 
     fn genome_config(_: &EnvironmentDescription) -> NeatConfig {
         NeatConfig::default()
@@ -35,8 +37,11 @@ impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Eshyperneat {
 }
 
 pub fn eshyperneat<
-    E: Environment<Phenotype = Executor> + Default + 'static,
+    F: Float,
+    E: Environment<Phenotype = Executor<F>> + 'static,
     C: Serialize + Default,
->() {
-    evolve::<E, Eshyperneat, Logger, C>();
+>(
+    environment: Arc<E>,
+) -> Option<Executor<F>> {
+    evolve::<E, Eshyperneat, Logger, C>(environment)
 }
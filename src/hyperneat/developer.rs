@@ -1,3 +1,4 @@
+use crate::conf::CONF;
 use crate::cppn::{developer::Developer as CppnDeveloper, genome::Genome};
 use crate::hyperneat::{
     conf::HYPERNEAT,
@@ -7,20 +8,39 @@ use evolution::{
     develop::Develop, environment::EnvironmentDescription, neat::developer::NetworkStats,
 };
 use network::{
-    activation,
+    activation::{self, GroupActivation},
+    connection::Connections,
     execute::{Action, Executor},
 };
 
 pub struct Developer {
     neat_developer: CppnDeveloper,
     network: substrate::Network,
+    output_activation: GroupActivation,
+}
+
+/// Group activation of the outputs, only applied to one-hot classes
+pub fn output_activation(
+    description: &EnvironmentDescription,
+    activation: GroupActivation,
+) -> GroupActivation {
+    if description.one_hot_output {
+        activation
+    } else {
+        GroupActivation::None
+    }
 }
 
 impl From<EnvironmentDescription> for Developer {
     fn from(description: EnvironmentDescription) -> Self {
         Developer {
             neat_developer: CppnDeveloper::from(description),
-            network: Network::load(description.inputs, description.outputs),
+            network: Network::load(
+                description.inputs,
+                description.outputs,
+                description.input_grid,
+            ),
+            output_activation: output_activation(&description, HYPERNEAT.output_group_activation),
         }
     }
 }
@@ -30,9 +50,20 @@ impl Develop<Genome> for Developer {
     type Stats = NetworkStats;
 
     fn develop(&self, genome: Genome) -> (Self::Phenotype, Self::Stats) {
+        self.develop_network(genome, false)
+    }
+
+    fn develop_analyzed(&self, genome: Genome) -> (Self::Phenotype, Self::Stats) {
+        self.develop_network(genome, true)
+    }
+}
+
+impl Developer {
+    /// Develop the network, analyzing the graph of its substrate links if requested
+    fn develop_network(&self, genome: Genome, analyze: bool) -> (Executor, NetworkStats) {
         let mut neat_executor = self.neat_developer.develop(genome).0;
 
-        let network = Executor::create(
+        let mut network = Executor::create(
             self.network.length,
             self.network.inputs.iter().cloned().collect(),
             self.network.outputs.iter().cloned().collect(),
@@ -61,12 +92,26 @@ impl Develop<Genome> for Developer {
                     }
                 })
                 .collect(),
-        );
+        )
+        .with_output_activation(self.output_activation);
 
-        let stats = NetworkStats {
-            nodes: self.network.length as u64,
-            edges: self.network.actions.len() as u64,
-        };
+        let mut stats = NetworkStats::new(
+            self.network.length as u64,
+            self.network.actions.len() as u64,
+        );
+        if analyze {
+            // Substrate links are layered, and connect each pair of nodes at most once
+            let mut connections = Connections::new();
+            for action in network.actions.iter() {
+                if let Action::Link(from, to, weight) = action {
+                    connections.add(*from, *to, *weight);
+                }
+            }
+            stats.analyze(&connections);
+        }
+        if CONF.simplify_networks {
+            network = stats.simplify(network, CONF.simplify_epsilon);
+        }
 
         (network, stats)
     }
@@ -43,4 +43,8 @@ impl log::Log<Genome> for Logger {
     fn close(&mut self) {
         self.cppn_logger.close();
     }
+
+    fn is_logged(&self, iteration: u64) -> bool {
+        self.cppn_logger.is_logged(iteration)
+    }
 }
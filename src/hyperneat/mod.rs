@@ -9,20 +9,22 @@ use conf::MethodConfig;
 use developer::Developer;
 use evolution::{
     algorithm::Algorithm,
+    develop::FloatDeveloper,
     environment::{Environment, EnvironmentDescription},
     evolve,
     neat::{conf::NeatConfig, state::InitConfig},
 };
 use log::Logger;
-use network::execute::Executor;
+use network::{execute::Executor, float::Float};
 use serde::Serialize;
+use std::sync::Arc;
 
 pub struct Hyperneat;
 
-impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Hyperneat {
+impl<F: Float, E: Environment<Phenotype = Executor<F>>> Algorithm<E> for Hyperneat {
     type Config = MethodConfig;
     type Genome = Genome;
-    type Developer = Developer;
+    type Developer = FloatDeveloper<Developer, F>;
 
     fn genome_config(_: &EnvironmentDescription) -> NeatConfig {
         NeatConfig::default()
@@ -34,8 +36,11 @@ impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Hyperneat {
 }
 
 pub fn hyperneat<
-    E: Environment<Phenotype = Executor> + Default + 'static,
+    F: Float,
+    E: Environment<Phenotype = Executor<F>> + 'static,
     C: Serialize + Default,
->() {
-    evolve::<E, Hyperneat, Logger, C>();
+>(
+    environment: Arc<E>,
+) -> Option<Executor<F>> {
+    evolve::<E, Hyperneat, Logger, C>(environment)
 }
@@ -20,12 +20,13 @@ pub enum Action {
 
 #[allow(dead_code)]
 impl Network {
-    pub fn load(inputs: u64, outputs: u64) -> Self {
+    pub fn load(inputs: u64, outputs: u64, input_grid: Option<(u64, u64)>) -> Self {
         let input_layer = parse_nodes(
             &HYPERNEAT.input_config,
             ESHYPERNEAT.resolution,
             inputs,
             -1.0,
+            input_grid,
         );
         let hidden_layers = parse_hidden_nodes(
             &HYPERNEAT.hidden_layers,
@@ -37,6 +38,7 @@ impl Network {
             ESHYPERNEAT.resolution,
             outputs,
             1.0,
+            None,
         );
 
         Self::layered_from_layers(input_layer, hidden_layers, output_layer)
@@ -127,7 +129,7 @@ impl Network {
         }
 
         let actions = connections
-            .sort_topologically()
+            .sort_topologically_stable()
             .iter()
             .map(|action| match action {
                 connection::OrderedAction::Node(node) => Action::Activation(
@@ -194,10 +196,46 @@ pub fn horizontal_rows(layer_sizes: &Vec<u64>) -> Vec<Vec<Point>> {
         .collect()
 }
 
-pub fn parse_nodes(conf: &String, r: f64, num: u64, y: f64) -> Vec<(i64, i64)> {
-    match &conf[..] {
-        "line" => horizontal_row(num, (r * y) as i64),
-        _ => serde_json::from_str::<Vec<Vec<f64>>>(conf)
+/// Nodes of inputs that form an image, as a grid spanning the lower half of the substrate with
+/// the first row at the top. Any further inputs, like a bias, are placed in a row below it.
+pub fn grid(width: u64, height: u64, num: u64, r: f64) -> Vec<(i64, i64)> {
+    let spacing = |n: u64| if n > 1 { r / (n - 1) as f64 } else { 0.0 };
+    let offset = if width > 1 { r } else { 0.0 };
+    let bottom = if height > 1 { 0.0 } else { -r };
+
+    let mut nodes = (0..height)
+        .flat_map(|row| (0..width).map(move |column| (column, row)))
+        .take(num as usize)
+        .map(|(column, row)| {
+            (
+                (2.0 * spacing(width) * column as f64 - offset) as i64,
+                (bottom - spacing(height) * row as f64) as i64,
+            )
+        })
+        .collect::<Vec<(i64, i64)>>();
+    if num > width * height {
+        let below = -r - spacing(height).max(r / 4.0);
+        nodes.extend(horizontal_row(num - width * height, below as i64));
+    }
+    nodes
+}
+
+/// Nodes given by a layout: "line", "grid" of the image inputs, "auto" for a grid if the
+/// inputs form an image and a line otherwise, or a JSON list of points
+pub fn parse_nodes(
+    conf: &String,
+    r: f64,
+    num: u64,
+    y: f64,
+    grid_size: Option<(u64, u64)>,
+) -> Vec<(i64, i64)> {
+    match (&conf[..], grid_size) {
+        ("auto", Some((width, height))) | ("grid", Some((width, height))) => {
+            grid(width, height, num, r)
+        }
+        ("grid", None) => panic!("grid layout requires inputs that form an image"),
+        ("line", _) | ("auto", None) => horizontal_row(num, (r * y) as i64),
+        (_, _) => serde_json::from_str::<Vec<Vec<f64>>>(conf)
             .expect("unable to parse nodes")
             .iter()
             .map(|point| ((point[0] * r) as i64, (point[1] * r) as i64))
@@ -247,18 +285,38 @@ mod tests {
         let ri = ESHYPERNEAT.resolution as i64;
 
         let conf = "line".to_owned();
-        let nodes = parse_nodes(&conf, r, 3, -1.0);
+        let nodes = parse_nodes(&conf, r, 3, -1.0, None);
         assert_eq!(nodes, [(-ri, -ri), (0, -ri), (ri, -ri)]);
 
-        let nodes = parse_nodes(&conf, r, 2, 1.0);
+        let nodes = parse_nodes(&conf, r, 2, 1.0, None);
         assert_eq!(nodes, [(-ri, ri), (ri, ri)]);
 
-        let nodes = parse_nodes(&conf, r, 1, 0.0);
+        let nodes = parse_nodes(&conf, r, 1, 0.0, None);
         assert_eq!(nodes, [(0, 0)]);
 
         let conf = "[[-1, -1], [1, -0.5]]".to_owned();
-        let nodes = parse_nodes(&conf, r, 1, 0.0);
+        let nodes = parse_nodes(&conf, r, 1, 0.0, None);
         assert_eq!(nodes, [(-ri, -ri), (ri, -ri / 2)]);
+
+        // Rows of a 3x2 image from the top, then a bias input below
+        let conf = "auto".to_owned();
+        let nodes = parse_nodes(&conf, r, 7, -1.0, Some((3, 2)));
+        assert_eq!(
+            nodes,
+            [
+                (-ri, 0),
+                (0, 0),
+                (ri, 0),
+                (-ri, -ri),
+                (0, -ri),
+                (ri, -ri),
+                (0, -2 * ri)
+            ]
+        );
+        assert_eq!(
+            parse_nodes(&conf, r, 2, -1.0, None),
+            [(-ri, -ri), (ri, -ri)]
+        );
     }
 
     #[test]
@@ -18,22 +18,105 @@ mod sideshyperneat;
 use codeshyperneat::codeshyperneat;
 use conf::MainConfig;
 use cppn::cppn;
-use dataset_environment::DatasetEnvironment;
+use data::conf::DATA;
+use dataset_environment::{DatasetEnvironment, TestReport};
 use deshyperneat::deshyperneat;
 use eshyperneat::eshyperneat;
+use evolution::conf::EVOLUTION;
 use evolution::neat::neat;
 use hyperneat::hyperneat;
+use network::float::{Float, Precision};
 use sideshyperneat::sideshyperneat;
+use std::sync::Arc;
 
 fn main() {
-    match &conf::CONF.method[..] {
-        "NEAT" => neat::<DatasetEnvironment, MainConfig>(),
-        "CPPN" => cppn::<DatasetEnvironment, MainConfig>(),
-        "HyperNEAT" => hyperneat::<DatasetEnvironment, MainConfig>(),
-        "ES-HyperNEAT" => eshyperneat::<DatasetEnvironment, MainConfig>(),
-        "DES-HyperNEAT" => deshyperneat::<DatasetEnvironment, MainConfig>(),
-        "CoDES-HyperNEAT" => codeshyperneat::<DatasetEnvironment, MainConfig>(),
-        "SiDES-HyperNEAT" => sideshyperneat::<DatasetEnvironment, MainConfig>(),
-        _ => println!("Unknown method method"),
+    // Cross-validation evolves once for each fold, testing on that fold, while a saved genome
+    // is only developed once
+    let folds = if EVOLUTION.develop_genome.is_empty() {
+        DATA.folds.max(1)
+    } else {
+        1
+    };
+    let mut reports = Vec::new();
+    for fold in 0..folds {
+        let fold = if folds > 1 { Some(fold) } else { None };
+        let report = match conf::CONF.precision {
+            Precision::Double => evolve_and_test::<f64>(fold),
+            Precision::Single => evolve_and_test::<f32>(fold),
+        };
+        if let Some(report) = report {
+            if let Some(fold) = fold {
+                print!("Fold {}: ", fold);
+            }
+            println!("{}", report);
+            reports.push(report);
+        }
+    }
+
+    if reports.len() > 1 {
+        print_cross_validation(&reports);
+    }
+}
+
+/// Evolve with the configured method, and test the champion network of float type F on the
+/// given fold
+fn evolve_and_test<F: Float>(fold: Option<u64>) -> Option<TestReport> {
+    let environment = Arc::new(DatasetEnvironment::<F>::new(fold));
+    let champion = match &conf::CONF.method[..] {
+        "NEAT" => neat::<F, DatasetEnvironment<F>, MainConfig>(environment.clone()),
+        "CPPN" => cppn::<F, DatasetEnvironment<F>, MainConfig>(environment.clone()),
+        "HyperNEAT" => hyperneat::<F, DatasetEnvironment<F>, MainConfig>(environment.clone()),
+        "ES-HyperNEAT" => eshyperneat::<F, DatasetEnvironment<F>, MainConfig>(environment.clone()),
+        "DES-HyperNEAT" => {
+            deshyperneat::<F, DatasetEnvironment<F>, MainConfig>(environment.clone())
+        }
+        "CoDES-HyperNEAT" => {
+            codeshyperneat::<F, DatasetEnvironment<F>, MainConfig>(environment.clone())
+        }
+        "SiDES-HyperNEAT" => {
+            sideshyperneat::<F, DatasetEnvironment<F>, MainConfig>(environment.clone())
+        }
+        _ => {
+            println!("Unknown method method");
+            std::process::exit(1);
+        }
+    };
+    environment.test(&mut champion?)
+}
+
+/// Mean and standard deviation of the test results of each fold, and the sum of their
+/// confusion matrices
+fn print_cross_validation(reports: &[TestReport]) {
+    let summary = |values: Vec<f64>| {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        format!("{} ± {}", mean, variance.sqrt())
+    };
+
+    print!(
+        "Cross-validation of {} folds: Test fitness: {} \t Test acc: {}",
+        reports.len(),
+        summary(reports.iter().map(|report| report.fitness).collect()),
+        summary(reports.iter().map(|report| report.accuracy).collect()),
+    );
+    for (i, (metric, _)) in reports[0].metrics.iter().enumerate() {
+        let values = reports.iter().map(|report| report.metrics[i].1).collect();
+        print!(" \t Test {}: {}", metric, summary(values));
+    }
+    println!();
+
+    if let Some(mut total) = reports[0].confusion_matrix.clone() {
+        for matrix in reports[1..]
+            .iter()
+            .filter_map(|r| r.confusion_matrix.as_ref())
+        {
+            for (total, count) in total.iter_mut().flatten().zip(matrix.iter().flatten()) {
+                *total += count;
+            }
+        }
+        println!("Confusion matrix of all folds (target by row, prediction by column):");
+        for row in total.iter() {
+            println!("{:?}", row);
+        }
     }
 }
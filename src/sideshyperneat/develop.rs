@@ -32,7 +32,10 @@ impl DesGenome for Genome {
                 cppn.neat
                     .outputs
                     .retain(|node_ref, _| node_ref.id() == node.cppn_output_id);
-                (*node_ref, Node::new(node.neat.clone(), cppn, node.depth))
+                (
+                    *node_ref,
+                    Node::new(node.neat.clone(), cppn, node.depth, vec![node_ref.id()]),
+                )
             })
             .collect::<HashMap<NodeRef, Node>>();
         des_neat.hidden_nodes = self
@@ -47,7 +50,10 @@ impl DesGenome for Genome {
                 cppn.neat
                     .outputs
                     .retain(|node_ref, _| node_ref.id() == node.cppn_output_id);
-                (*node_ref, Node::new(node.neat.clone(), cppn, node.depth))
+                (
+                    *node_ref,
+                    Node::new(node.neat.clone(), cppn, node.depth, vec![]),
+                )
             })
             .collect::<HashMap<NodeRef, Node>>();
         des_neat.outputs = self
@@ -62,7 +68,10 @@ impl DesGenome for Genome {
                 cppn.neat
                     .outputs
                     .retain(|node_ref, _| node_ref.id() == node.cppn_output_id);
-                (*node_ref, Node::new(node.neat.clone(), cppn, node.depth))
+                (
+                    *node_ref,
+                    Node::new(node.neat.clone(), cppn, node.depth, vec![node_ref.id()]),
+                )
             })
             .collect::<HashMap<NodeRef, Node>>();
         des_neat.links = self
@@ -1,10 +1,14 @@
 use crate::cppn::{genome::Genome as CppnGenome, node::Node as CppnNode};
-use crate::deshyperneat::{conf::DESHYPERNEAT, genome::Genome as DesGenome};
+use crate::deshyperneat::{
+    conf::DESHYPERNEAT,
+    genome::{max_depth, Genome as DesGenome},
+};
 use crate::eshyperneat::genome::insert_identity;
 use crate::sideshyperneat::{
     conf::{GenomeConfig, SIDESHYPERNEAT},
     link::Link,
     node::Node,
+    serialize::SerializedGenome,
     state::State,
 };
 use evolution::{
@@ -46,11 +50,13 @@ impl GenericEvolvableGenome<GenomeConfig, State, InitConfig, SiDESgenomeStats> f
         let cppn = CppnGenome::new(&config.cppn, &InitConfig::new(4, 2), &mut state.cppn_state);
         let topology = NeatGenome::new(&config.topology, init_config, state);
 
-        Self {
+        let mut genome = Self {
             cppn,
             topology,
             des_genome: None,
-        }
+        };
+        genome.add_missing_cppn_outputs(config, state);
+        genome
     }
 
     fn crossover(
@@ -81,39 +87,7 @@ impl GenericEvolvableGenome<GenomeConfig, State, InitConfig, SiDESgenomeStats> f
             self.topology.mutate(&config.topology, state);
         }
 
-        // Add all missing cppn output nodes
-        for (output_id, is_identity) in self
-            .topology
-            .hidden_nodes
-            .values()
-            .map(|node| (node.cppn_output_id, false))
-            .chain(
-                self.topology
-                    .links
-                    .values()
-                    .map(|link| (link.cppn_output_id, link.is_identity)),
-            )
-            .collect::<Vec<(u64, bool)>>()
-            .iter()
-        {
-            if !self
-                .cppn
-                .neat
-                .outputs
-                .contains_key(&NodeRef::Output(*output_id))
-            {
-                if *is_identity {
-                    insert_identity(
-                        &config.cppn,
-                        &mut self.cppn,
-                        &mut state.cppn_state,
-                        *output_id,
-                    )
-                } else {
-                    self.add_cppn_output(config, *output_id, state);
-                }
-            }
-        }
+        self.add_missing_cppn_outputs(config, state);
 
         if rng.gen::<f64>() < SIDESHYPERNEAT.cppn_mutation_probability {
             self.cppn.mutate(&config.cppn, &mut state.cppn_state);
@@ -160,9 +134,105 @@ impl GenericEvolvableGenome<GenomeConfig, State, InitConfig, SiDESgenomeStats> f
             cppn: self.cppn.get_stats(),
         }
     }
+
+    fn validate(&self) -> Result<(), String> {
+        self.topology.validate()?;
+        self.cppn.validate().map_err(|e| format!("cppn: {}", e))?;
+
+        let has_output = |id: u64| self.cppn.neat.outputs.contains_key(&NodeRef::Output(id));
+
+        for (node_ref, node) in self
+            .topology
+            .inputs
+            .iter()
+            .chain(self.topology.hidden_nodes.iter())
+            .chain(self.topology.outputs.iter())
+        {
+            if !has_output(node.cppn_output_id) {
+                return Err(format!(
+                    "node {} refers to missing cppn output O{}",
+                    node_ref, node.cppn_output_id
+                ));
+            }
+
+            let limit = max_depth(node_ref);
+            if node.depth > limit {
+                return Err(format!(
+                    "node {} has depth {} above limit {}",
+                    node_ref, node.depth, limit
+                ));
+            }
+        }
+
+        for ((from, to), link) in self.topology.links.iter() {
+            if !has_output(link.cppn_output_id) {
+                return Err(format!(
+                    "link {}->{} refers to missing cppn output O{}",
+                    from, to, link.cppn_output_id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    const SAVABLE: bool = true;
+
+    fn save(&self) -> Option<String> {
+        serde_json::to_string_pretty(&SerializedGenome::from(self)).ok()
+    }
+
+    fn load(
+        config: &GenomeConfig,
+        init_config: &InitConfig,
+        serialized: &str,
+        state: &mut State,
+    ) -> Result<Self, String> {
+        serde_json::from_str::<SerializedGenome>(serialized)
+            .map_err(|e| e.to_string())?
+            .into_genome(config, init_config, state)
+    }
 }
 
 impl Genome {
+    /// Add a cppn output for each node and link without one, including input and output nodes
+    pub fn add_missing_cppn_outputs(&mut self, config: &GenomeConfig, state: &mut State) {
+        for (output_id, is_identity) in self
+            .topology
+            .inputs
+            .values()
+            .chain(self.topology.hidden_nodes.values())
+            .chain(self.topology.outputs.values())
+            .map(|node| (node.cppn_output_id, false))
+            .chain(
+                self.topology
+                    .links
+                    .values()
+                    .map(|link| (link.cppn_output_id, link.is_identity)),
+            )
+            .collect::<Vec<(u64, bool)>>()
+            .iter()
+        {
+            if !self
+                .cppn
+                .neat
+                .outputs
+                .contains_key(&NodeRef::Output(*output_id))
+            {
+                if *is_identity {
+                    insert_identity(
+                        &config.cppn,
+                        &mut self.cppn,
+                        &mut state.cppn_state,
+                        *output_id,
+                    )
+                } else {
+                    self.add_cppn_output(config, *output_id, state);
+                }
+            }
+        }
+    }
+
     fn add_cppn_output(&mut self, config: &GenomeConfig, id: u64, state: &mut State) {
         let node_ref = NodeRef::Output(id);
         self.cppn.neat.outputs.insert(
@@ -196,3 +266,19 @@ fn mutate_node<R: Rng>(node: &mut Node, limit: u64, rng: &mut R) {
 
     node.depth = node.depth.min(limit).max(0);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evolution::genome::check_random_evolution;
+
+    #[test]
+    fn test_random_mutations() {
+        let mut config = GenomeConfig::default();
+        config.topology.add_node_probability = 0.3;
+        config.topology.add_link_probability = 0.6;
+        config.topology.remove_node_probability = 0.05;
+        config.topology.remove_link_probability = 0.2;
+        check_random_evolution::<Genome>(&config, &InitConfig::new(2, 2), 5, 500, |_, _| {});
+    }
+}
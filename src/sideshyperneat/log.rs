@@ -36,4 +36,8 @@ impl log::Log<Genome> for Logger {
     fn close(&mut self) {
         <DeshyperneatLogger as log::Log<Genome>>::close(&mut self.deshyperneat_logger);
     }
+
+    fn is_logged(&self, iteration: u64) -> bool {
+        <DeshyperneatLogger as log::Log<Genome>>::is_logged(&self.deshyperneat_logger, iteration)
+    }
 }
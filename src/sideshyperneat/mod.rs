@@ -5,25 +5,27 @@ pub mod genome;
 pub mod link;
 pub mod log;
 pub mod node;
+pub mod serialize;
 pub mod state;
 
 use crate::deshyperneat::developer::{topology_init_config, Developer};
 use conf::{GenomeConfig, MethodConfig};
 use evolution::{
-    algorithm::Algorithm, environment::Environment, environment::EnvironmentDescription, evolve,
-    neat::state::InitConfig,
+    algorithm::Algorithm, develop::FloatDeveloper, environment::Environment,
+    environment::EnvironmentDescription, evolve, neat::state::InitConfig,
 };
 use genome::Genome;
 use log::Logger;
-use network::execute::Executor;
+use network::{execute::Executor, float::Float};
 use serde::Serialize;
+use std::sync::Arc;
 
 pub struct Sideshyperneat;
 
-impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Sideshyperneat {
+impl<F: Float, E: Environment<Phenotype = Executor<F>>> Algorithm<E> for Sideshyperneat {
     type Config = MethodConfig;
     type Genome = Genome;
-    type Developer = Developer;
+    type Developer = FloatDeveloper<Developer, F>;
 
     fn genome_config(_: &EnvironmentDescription) -> GenomeConfig {
         GenomeConfig::default()
@@ -35,8 +37,11 @@ impl<E: Environment<Phenotype = Executor>> Algorithm<E> for Sideshyperneat {
 }
 
 pub fn sideshyperneat<
-    E: Environment<Phenotype = Executor> + Default + 'static,
+    F: Float,
+    E: Environment<Phenotype = Executor<F>> + 'static,
     C: Serialize + Default,
->() {
-    evolve::<E, Sideshyperneat, Logger, C>();
+>(
+    environment: Arc<E>,
+) -> Option<Executor<F>> {
+    evolve::<E, Sideshyperneat, Logger, C>(environment)
 }
@@ -0,0 +1,293 @@
+use crate::cppn::serialize::SerializedGenome as SerializedCppn;
+use crate::sideshyperneat::{
+    conf::GenomeConfig, genome::Genome, link::Link, node::Node, state::State,
+};
+use evolution::{
+    genome::GenericGenome,
+    neat::{
+        genome::NeatGenome,
+        link::{LinkExtension, NeatLink},
+        node::{NeatNode, NodeExtension, NodeRef},
+        state::InitConfig,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// JSON form of a SiDES-HyperNEAT genome, its cppn in the form of cppn::serialize and the
+/// substrates and links of its topology, each with the cppn output it is developed from.
+/// Substrates are referred to as I0, H0 and O0, and I/O substrates left out of the file are
+/// created with default properties.
+#[derive(Serialize, Deserialize)]
+pub struct SerializedGenome {
+    pub cppn: SerializedCppn,
+    pub nodes: Vec<SerializedNode>,
+    pub links: Vec<SerializedLink>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedNode {
+    pub node: String,
+    pub depth: u64,
+    pub cppn_output: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedLink {
+    pub from: String,
+    pub to: String,
+    pub weight: f64,
+    pub depth: u64,
+    pub cppn_output: u64,
+    #[serde(default)]
+    pub identity: bool,
+}
+
+impl From<&Genome> for SerializedGenome {
+    fn from(genome: &Genome) -> Self {
+        let topology = &genome.topology;
+        let mut node_refs = topology
+            .inputs
+            .keys()
+            .chain(topology.hidden_nodes.keys())
+            .chain(topology.outputs.keys())
+            .cloned()
+            .collect::<Vec<NodeRef>>();
+        node_refs.sort();
+        let mut link_refs = topology.links.keys().cloned().collect::<Vec<_>>();
+        link_refs.sort();
+
+        Self {
+            cppn: SerializedCppn::from(&genome.cppn),
+            nodes: node_refs
+                .iter()
+                .map(|node_ref| {
+                    let node = topology.get_node(node_ref).unwrap();
+                    SerializedNode {
+                        node: node_ref.to_string(),
+                        depth: node.depth,
+                        cppn_output: node.cppn_output_id,
+                    }
+                })
+                .collect(),
+            links: link_refs
+                .iter()
+                .map(|key| {
+                    let link = &topology.links[key];
+                    SerializedLink {
+                        from: key.0.to_string(),
+                        to: key.1.to_string(),
+                        weight: link.neat.weight,
+                        depth: link.depth,
+                        cppn_output: link.cppn_output_id,
+                        identity: link.is_identity,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SerializedGenome {
+    /// Build a genome for the given numbers of input and output groups. Substrates and links
+    /// are registered in the state like new ones, and the cppn outputs they read are renumbered
+    /// to the outputs the state assigns to them.
+    pub fn into_genome(
+        mut self,
+        config: &GenomeConfig,
+        init_config: &InitConfig,
+        state: &mut State,
+    ) -> Result<Genome, String> {
+        let mut topology = NeatGenome::<Node, Link>::empty();
+        // Cppn output in the file -> cppn output assigned by the state
+        let mut cppn_outputs = HashMap::<u64, u64>::new();
+        let mut map_output = |old: u64, new: u64| {
+            if cppn_outputs.insert(old, new).is_some() {
+                Err(format!("cppn output O{} is read twice", old))
+            } else {
+                Ok(())
+            }
+        };
+
+        // I/O nodes are assigned cppn outputs before any hidden node or link, as in new genomes
+        for node_ref in (0..init_config.inputs)
+            .map(NodeRef::Input)
+            .chain((0..init_config.outputs).map(NodeRef::Output))
+        {
+            let node = <Node as NodeExtension>::new(&(), NeatNode::new(node_ref), state);
+            match node_ref {
+                NodeRef::Input(_) => topology.inputs.insert(node_ref, node),
+                _ => topology.outputs.insert(node_ref, node),
+            };
+        }
+
+        let mut hidden_ids = HashMap::<NodeRef, NodeRef>::new();
+        let mut loaded = HashSet::<NodeRef>::new();
+        for serialized in self.nodes.iter() {
+            let node_ref = match serialized.node.parse::<NodeRef>()? {
+                NodeRef::Input(id) if id >= init_config.inputs => {
+                    return Err(format!(
+                        "input {} is not in the environment",
+                        serialized.node
+                    ))
+                }
+                NodeRef::Output(id) if id >= init_config.outputs => {
+                    return Err(format!(
+                        "output {} is not in the environment",
+                        serialized.node
+                    ))
+                }
+                NodeRef::Hidden(id) if hidden_ids.contains_key(&NodeRef::Hidden(id)) => {
+                    return Err(format!("duplicate node {}", serialized.node));
+                }
+                NodeRef::Hidden(id) => {
+                    // Registered as the hidden node of a split, which reserves its cppn output
+                    let topology_state = &mut state.topology_state;
+                    let new_ref = NodeRef::Hidden(topology_state.next_innovation.node_number);
+                    topology_state
+                        .innovation_log
+                        .hidden_node_innovations
+                        .insert(new_ref.id(), topology_state.next_innovation.clone());
+                    topology_state.next_innovation.node_number += 1;
+                    topology_state.next_innovation.innovation_number += 3;
+                    hidden_ids.insert(NodeRef::Hidden(id), new_ref);
+
+                    let node = <Node as NodeExtension>::new(&(), NeatNode::new(new_ref), state);
+                    topology.hidden_nodes.insert(new_ref, node);
+                    new_ref
+                }
+                node_ref => node_ref,
+            };
+            if !loaded.insert(node_ref) {
+                return Err(format!("duplicate node {}", serialized.node));
+            }
+
+            let node = topology.get_node_mut(&node_ref).unwrap();
+            node.depth = serialized.depth;
+            map_output(serialized.cppn_output, node.cppn_output_id)?;
+        }
+
+        for serialized in self.links.iter() {
+            let resolve = |node: &String| -> Result<NodeRef, String> {
+                let unknown = || format!("link refers to unknown node {}", node);
+                match node.parse::<NodeRef>()? {
+                    node_ref @ NodeRef::Hidden(_) => {
+                        hidden_ids.get(&node_ref).cloned().ok_or_else(unknown)
+                    }
+                    node_ref if topology.get_node(&node_ref).is_some() => Ok(node_ref),
+                    _ => Err(unknown()),
+                }
+            };
+            let (from, to) = (resolve(&serialized.from)?, resolve(&serialized.to)?);
+            if topology.links.contains_key(&(from, to)) {
+                return Err(format!("duplicate link {}->{}", from, to));
+            }
+            if topology.connections.creates_cycle(from, to) {
+                return Err(format!("link {}->{} creates a cycle", from, to));
+            }
+
+            let innovation = state.topology_state.get_connect_innovation(from, to);
+            let neat = NeatLink::new(from, to, serialized.weight, innovation);
+            let mut link = if serialized.identity {
+                <Link as LinkExtension>::identity(&(), neat, state)
+            } else {
+                <Link as LinkExtension>::new(&(), neat, state)
+            };
+            link.depth = serialized.depth;
+            map_output(serialized.cppn_output, link.cppn_output_id)?;
+            topology.insert_link(link);
+        }
+
+        // The cppn is loaded with its outputs renumbered, leaving out those no longer read
+        let rename = |node: &str| -> Result<Option<String>, String> {
+            Ok(match node.parse::<NodeRef>()? {
+                NodeRef::Output(id) => cppn_outputs
+                    .get(&id)
+                    .map(|new_id| NodeRef::Output(*new_id).to_string()),
+                _ => Some(node.to_owned()),
+            })
+        };
+        let mut cppn_nodes = Vec::new();
+        for mut node in self.cppn.nodes.drain(..) {
+            if let Some(renamed) = rename(&node.node)? {
+                node.node = renamed;
+                cppn_nodes.push(node);
+            }
+        }
+        let mut cppn_links = Vec::new();
+        for mut link in self.cppn.links.drain(..) {
+            if let (Some(from), Some(to)) = (rename(&link.from)?, rename(&link.to)?) {
+                link.from = from;
+                link.to = to;
+                cppn_links.push(link);
+            }
+        }
+        self.cppn.nodes = cppn_nodes;
+        self.cppn.links = cppn_links;
+        let outputs = cppn_outputs.values().max().map_or(0, |max| max + 1);
+        let mut cppn = self
+            .cppn
+            .into_genome(&InitConfig::new(4, outputs), &mut state.cppn_state)
+            .map_err(|e| format!("cppn: {}", e))?;
+        let read = cppn_outputs.values().collect::<HashSet<_>>();
+        cppn.neat
+            .outputs
+            .retain(|node_ref, _| read.contains(&node_ref.id()));
+
+        let mut genome = Genome {
+            cppn,
+            topology,
+            des_genome: None,
+        };
+        genome.add_missing_cppn_outputs(config, state);
+        genome.validate()?;
+        Ok(genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deshyperneat::developer::{topology_init_config, Developer};
+    use evolution::{develop::Develop, environment::EnvironmentDescription};
+
+    #[test]
+    fn test_round_trip() {
+        let mut config = GenomeConfig::default();
+        config.topology.add_node_probability = 0.5;
+        config.topology.add_link_probability = 0.5;
+        let description = EnvironmentDescription::new(3, 2);
+        let init_config = topology_init_config(&description);
+        let mut state = State::default();
+        let mut genome = Genome::new(&config, &init_config, &mut state);
+        for _ in 0..20 {
+            genome.mutate(&config, &mut state);
+        }
+
+        let json = serde_json::to_string(&SerializedGenome::from(&genome)).unwrap();
+        let mut loaded_state = State::default();
+        let mut loaded = serde_json::from_str::<SerializedGenome>(&json)
+            .unwrap()
+            .into_genome(&config, &init_config, &mut loaded_state)
+            .unwrap();
+        assert_eq!(
+            loaded.topology.hidden_nodes.len(),
+            genome.topology.hidden_nodes.len()
+        );
+        assert_eq!(loaded.topology.links.len(), genome.topology.links.len());
+
+        // Both develop into the same network
+        let developer = Developer::from(description);
+        let inputs = vec![0.5, -0.25, 1.0];
+        assert_eq!(
+            developer.develop(genome).0.execute(&inputs),
+            developer.develop(loaded.clone()).0.execute(&inputs)
+        );
+
+        // The loaded genome keeps evolving in its state
+        for _ in 0..20 {
+            loaded.mutate(&config, &mut loaded_state);
+            assert_eq!(loaded.validate(), Ok(()));
+        }
+    }
+}